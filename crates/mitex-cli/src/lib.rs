@@ -43,7 +43,8 @@ pub enum CompileStage {
 pub struct CompileArgs {
     /// Path to workspace.
     ///
-    /// This is used to resolve imports in `\iftypst` blocks.
+    /// This is used to resolve the files included by `\input`, `\include`,
+    /// `\subfile` and `\import`, and imports in `\iftypst` blocks.
     ///
     /// ## Example
     ///
@@ -93,6 +94,53 @@ pub struct CompileArgs {
     #[clap(long, value_enum)]
     pub stage: Option<CompileStage>,
 
+    /// Keep converting on errors.
+    ///
+    /// Parts that cannot be converted are shown in red in the output, and
+    /// the errors are printed to stderr.
+    ///
+    /// ## Example
+    ///
+    /// ```bash
+    /// mitex compile --recover main.tex
+    /// ```
+    #[clap(long)]
+    pub recover: bool,
+
+    /// Write idiomatic Typst meant to be read and edited by people.
+    ///
+    /// ## Example
+    ///
+    /// ```bash
+    /// mitex compile --readable main.tex
+    /// ```
+    #[clap(long)]
+    pub readable: bool,
+
+    /// Write plain Typst that compiles without the MiTeX package.
+    ///
+    /// Native Typst is used where possible, and the helpers still needed are
+    /// defined at the top of the output.
+    ///
+    /// ## Example
+    ///
+    /// ```bash
+    /// mitex compile --standalone main.tex
+    /// ```
+    #[clap(long)]
+    pub standalone: bool,
+
+    /// Write fully qualified names in math, e.g. `sym.alpha`, `math.frac`
+    /// and `mitex.mitexsqrt`, which bindings of the document cannot shadow.
+    ///
+    /// ## Example
+    ///
+    /// ```bash
+    /// mitex compile --qualified main.tex
+    /// ```
+    #[clap(long)]
+    pub qualified: bool,
+
     /// Output to file, default to entry file name with `.typ` extension.
     ///
     /// ## Example
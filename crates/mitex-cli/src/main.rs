@@ -14,13 +14,13 @@ use std::path::Path;
 use std::process::exit;
 
 use anyhow::Context;
-use mitex_spec::{CmdShape, CommandSpecItem, EnvShape};
+use mitex::{ConvertOptions, Converted, ErrorPolicy, Target};
 use serde::{Deserialize, Serialize};
 
 use mitex_cli::utils::{Error, UnwrapOrExit};
 use mitex_cli::{
-    get_cli, get_os_opts, intercept_version, CompileStage, CompletionArgs, SpecSubCommands,
-    Subcommands,
+    get_cli, get_os_opts, intercept_version, CompileArgs, CompileStage, CompletionArgs,
+    SpecSubCommands, Subcommands,
 };
 use mitex_spec_gen::DEFAULT_SPEC;
 
@@ -36,12 +36,7 @@ fn main() -> anyhow::Result<()> {
 
     match opts.sub {
         Some(Subcommands::Compile(args)) => {
-            compile(
-                &args.input,
-                &args.output,
-                matches!(args.stage, Some(CompileStage::Syntax)),
-            )
-            .unwrap_or_exit();
+            compile(&args).unwrap_or_exit();
             exit(0);
         }
         Some(Subcommands::Completion(args)) => generate_completion(args),
@@ -69,51 +64,96 @@ fn main() -> anyhow::Result<()> {
     }
 }
 
-fn compile(input_path: &str, output_path: &str, is_ast: bool) -> Result<(), Error> {
-    let input = std::fs::read_to_string(input_path)
-        .with_context(|| format!("failed to read input file: {input_path}"))?;
-
-    let spec = DEFAULT_SPEC.clone();
+fn compile(args: &CompileArgs) -> Result<(), Error> {
+    let (input_path, output_path) = (&args.input, &args.output);
+    let is_ast = matches!(args.stage, Some(CompileStage::Syntax));
+    // Files included by `\input` and the like are inlined into the input
+    let inlined = mitex::inline_includes(
+        Path::new(input_path),
+        Path::new(&args.workspace),
+        &mut |path| std::fs::read_to_string(path),
+    )
+    .map_err(|e| e.to_string())?;
+    let input = &inlined.text;
 
     let output = if !is_ast {
-        mitex::convert_text(&input, Some(spec.clone())).map_err(|e| anyhow::anyhow!("{}", e))
+        let options = ConvertOptions::default()
+            .spec(DEFAULT_SPEC.clone())
+            .readable(args.readable)
+            .target(if args.standalone {
+                Target::Standalone
+            } else {
+                Target::MitexScope
+            })
+            .qualified(args.qualified)
+            // images are looked up next to the input file, as by LaTeX
+            .base_dir(Path::new(input_path).parent().unwrap_or(Path::new("")))
+            .error_policy(if args.recover {
+                ErrorPolicy::Recover
+            } else {
+                ErrorPolicy::Abort
+            });
+        let locate = |e: &mitex::ConvertError| match inlined.source(e.range.start) {
+            Some((path, text, offset)) => {
+                let e = mitex::ConvertError {
+                    range: offset..offset,
+                    ..e.clone()
+                };
+                let (line, column) = e.line_column(text);
+                format!("{}:{line}:{column}: {e}", path.display())
+            }
+            None => format!("{input_path}:1:1: {e}"),
+        };
+        let res = mitex::convert(input, &options);
+        if let Ok(res) = &res {
+            for e in &res.diagnostics {
+                eprintln!("{}", locate(e));
+            }
+        }
+        res.map_err(|e| anyhow::anyhow!(locate(&e)))
     } else {
-        Ok(format!(
-            "{:#?}",
-            mitex_parser::parse(&input, DEFAULT_SPEC.clone())
-        ))
+        Ok(Converted {
+            output: format!("{:#?}", mitex_parser::parse(input, DEFAULT_SPEC.clone())),
+            ..Default::default()
+        })
     };
 
-    let output = output.with_context(|| format!("failed to convert input file: {input_path}"))?;
-
-    // Insert preludes
-    // todo: better way?
-    let mut builtin_set = std::collections::HashSet::<&'static str>::new();
-    builtin_set.extend(["and", "or", "in", "not"]);
-    let mut alias_set = std::collections::HashSet::<Box<str>>::new();
-    for (_, cmd) in spec.items() {
-        let alias = match cmd {
-            CommandSpecItem::Cmd(CmdShape {
-                alias: Some(alias), ..
-            }) => alias.as_str(),
-            CommandSpecItem::Env(EnvShape {
-                alias: Some(alias), ..
-            }) => alias.as_str(),
-            _ => continue,
+    let Converted {
+        output,
+        aliases,
+        prelude,
+        ..
+    } = output.with_context(|| format!("failed to convert input file: {input_path}"))?;
+
+    // Standalone output only needs the helpers it uses
+    if args.standalone && !is_ast {
+        let output = if prelude.is_empty() {
+            output
+        } else {
+            format!("{prelude}\n{output}")
         };
-        if alias.is_empty() || !alias.chars().all(|c| c.is_ascii_alphanumeric()) {
-            continue;
-        }
-        if builtin_set.contains(alias) || alias_set.contains(alias) {
-            continue;
-        }
-        alias_set.insert(alias.into());
+        std::fs::write(output_path, output)?;
+        return Ok(());
+    }
+
+    // Qualified names all live in a single `mitex` namespace
+    if args.qualified && !is_ast {
+        let output = format!(
+            r#"#import "@preview/mitex:0.2.7": mitex-scope
+#let mitex = mitex-scope
+
+{output}"#
+        );
+        std::fs::write(output_path, output)?;
+        return Ok(());
     }
 
-    let mut alias_set = alias_set.into_iter().collect::<Vec<_>>();
-    alias_set.sort();
-    let preludes_str = alias_set
+    // Bind the names of mitex-scope used by the output
+    let keywords = ["and", "or", "in", "not"];
+    let preludes_str = aliases
         .into_iter()
+        .filter(|alias| alias.chars().all(|c| c.is_ascii_alphanumeric()))
+        .filter(|alias| !keywords.contains(&alias.as_str()))
         .map(|alias| format!(r#"#let {alias} = mitex-scope.at("{alias}", default: none);"#))
         .collect::<Vec<_>>()
         .join("\n");
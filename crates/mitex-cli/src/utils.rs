@@ -31,7 +31,8 @@ impl From<&str> for Error {
 
 impl From<anyhow::Error> for Error {
     fn from(err: anyhow::Error) -> Self {
-        Self(err.to_string().into_boxed_str())
+        // print the chain of causes
+        Self(format!("{err:#}").into_boxed_str())
     }
 }
 
@@ -81,7 +81,6 @@
 //! - \newcounter, See 13 Counters
 //! - \newlength, See 14 Lengths
 //! - \newsavebox, See 14 Boxes
-//! - \newtheorem
 //! - \newfont
 //! - class commands, e.g. \ProvidesClass, \LoadClass, \PassOptionsToClass,
 //!   \LoadClassWithOptions
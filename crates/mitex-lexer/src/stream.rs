@@ -79,6 +79,16 @@ pub struct StreamContext<'a> {
 impl<'a> StreamContext<'a> {
     #[inline]
     pub fn lex_one(l: &mut logos::Lexer<'a, Token>) -> Option<Tok<'a>> {
+        // raw text marked by the previous tokens, e.g. the argument of `\verb`
+        let start = l.span().end;
+        if let Some(raw) = l.extras.2.clone().filter(|raw| raw.start <= start) {
+            l.extras.2 = None;
+            if raw.end > start {
+                l.bump(raw.end - start);
+                return Some((Token::Verbatim, l.source().slice(start..raw.end).unwrap()));
+            }
+        }
+
         let tok = l.next()?.unwrap();
 
         let source_text = match tok {
@@ -18,7 +18,7 @@ pub enum BraceKind {
 ///
 /// It also specifies how logos would lex the token
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Logos)]
-#[logos(extras = (CommandSpec, logos::Span))]
+#[logos(extras = (CommandSpec, logos::Span, Option<logos::Span>))]
 pub enum Token {
     /// A line break
     /// Typically a `\r\n` or `\n`
@@ -123,6 +123,11 @@ pub enum Token {
     #[regex(r"\\", lex_command_name, priority = 3)]
     CommandName(CommandName),
 
+    /// Raw text that is not lexed as TeX, i.e. the delimited argument of
+    /// `\verb`, e.g. `|a_b%c|`, or the body of a verbatim environment like
+    /// `lstlisting`
+    Verbatim,
+
     /// Macro error
     Error,
 
@@ -294,10 +299,87 @@ fn lex_command_name(lexer: &mut logos::Lexer<Token>) -> CommandName {
         "right" => CommandName::Right,
         "begin" => lex_begin_end(lexer, true),
         "end" => lex_begin_end(lexer, false),
+        "verb" | "verb*" => {
+            lex_verb(lexer);
+            CommandName::Generic
+        }
+        "url" | "href" => {
+            lex_url(lexer);
+            CommandName::Generic
+        }
         _ => CommandName::Generic,
     }
 }
 
+/// Mark the delimited argument of `\verb` as raw text, which ends at the
+/// next delimiter on the same line, e.g. `|a_b%c|`
+fn lex_verb(lexer: &mut logos::Lexer<Token>) {
+    let start = lexer.span().end;
+    let mut chars = lexer.source()[start..].char_indices();
+    let Some((_, delim)) = chars.next() else {
+        return;
+    };
+    if delim.is_alphabetic() || delim.is_whitespace() {
+        return;
+    }
+    let end = chars
+        .take_while(|(_, c)| !matches!(c, '\r' | '\n'))
+        .find(|(_, c)| *c == delim);
+    if let Some((i, _)) = end {
+        lexer.extras.2 = Some(start..start + i + delim.len_utf8());
+    }
+}
+
+/// Mark the braced URL of `\url` and `\href` as raw text, where `%`, `#` and
+/// `~` are part of the URL, e.g. `a%20b` of `\url{a%20b}`
+fn lex_url(lexer: &mut logos::Lexer<Token>) {
+    if let Some(group) = group_span(lexer.source(), lexer.span().end, '{', '}') {
+        lexer.extras.2 = Some(group.start + 1..group.end - 1);
+    }
+}
+
+/// Get the span of a group like `[..]` or `{..}` after optional spaces, where
+/// braces are balanced
+pub fn group_span(source: &str, pos: usize, open: char, close: char) -> Option<logos::Span> {
+    let rest = source[pos..].trim_start_matches([' ', '\t']);
+    let start = source.len() - rest.len();
+    let mut depth = 0usize;
+    for (i, c) in rest.strip_prefix(open)?.char_indices() {
+        match c {
+            _ if c == close && depth == 0 => return Some(start..start + i + 2),
+            '{' => depth += 1,
+            '}' => depth = depth.checked_sub(1)?,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Mark the body of a verbatim environment as raw text, after the options
+/// like `[language=Python]` and the language of `minted`, which are still
+/// lexed as TeX. An environment without its `\end` runs to the end of the
+/// input, as in TeX
+fn lex_verbatim_env(lexer: &mut logos::Lexer<Token>) {
+    let source = lexer.source();
+    let name = &source[lexer.extras.1.clone()];
+    let language = match name {
+        "verbatim" | "verbatim*" | "Verbatim" | "lstlisting" => false,
+        "minted" => true,
+        _ => return,
+    };
+    let group_end = |pos: usize, open: char, close: char| {
+        group_span(source, pos, open, close).map_or(pos, |group| group.end)
+    };
+    let mut pos = lexer.span().end;
+    pos = group_end(pos, '[', ']');
+    if language {
+        pos = group_end(pos, '{', '}');
+    }
+    let end = format!("\\end{{{name}}}");
+    let body_end = source[pos..].find(&end).map_or(source.len(), |i| pos + i);
+    lexer.extras.2 = Some(pos..body_end);
+}
+
 fn advance_ascii_name(
     lexer: &mut logos::Lexer<Token>,
     ascii_str: &[u8],
@@ -414,7 +496,11 @@ fn lex_begin_end(lexer: &mut logos::Lexer<Token>, is_begin: bool) -> CommandName
     }
 
     let mut task = LexTask::new(lexer);
-    match (task.work(), is_begin) {
+    let res = task.work();
+    if res.is_some() && is_begin {
+        lex_verbatim_env(lexer);
+    }
+    match (res, is_begin) {
         (Some(..), true) => CommandName::BeginEnvironment,
         (Some(..), false) => CommandName::EndEnvironment,
         (None, true) => CommandName::ErrorBeginEnvironment,
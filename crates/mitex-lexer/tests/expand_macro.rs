@@ -46,6 +46,41 @@ fn begin_end() {
     "###);
 }
 
+#[test]
+fn raw_text() {
+    assert_snapshot!(assert_plain_tokens(r#"\verb|a%{|b"#), @r###"
+    CommandName(Generic)("\\verb")
+    Verbatim("|a%{|")
+    Word("b")
+    "###);
+    assert_snapshot!(assert_plain_tokens(r#"\verb|a
+b|"#), @r###"
+    CommandName(Generic)("\\verb")
+    Word("|a")
+    LineBreak("\n")
+    Word("b|")
+    "###);
+    assert_snapshot!(assert_plain_tokens(r#"\begin{lstlisting}[a={]}]%}
+\end{lstlisting}"#), @r###"
+    CommandName(BeginEnvironment)("lstlisting")
+    Left(Bracket)("[")
+    Word("a=")
+    Left(Curly)("{")
+    Right(Bracket)("]")
+    Right(Curly)("}")
+    Right(Bracket)("]")
+    Verbatim("%}\n")
+    CommandName(EndEnvironment)("lstlisting")
+    "###);
+    assert_snapshot!(assert_plain_tokens(r#"\url{a%20{b}#c_d~e}%"#), @r###"
+    CommandName(Generic)("\\url")
+    Left(Curly)("{")
+    Verbatim("a%20{b}#c_d~e")
+    Right(Curly)("}")
+    LineComment("%")
+    "###);
+}
+
 #[test]
 fn no_macros() {
     assert_snapshot!(assert_plain_tokens("hello world"), @r###"
@@ -15,12 +15,19 @@
 //!
 //! The untyped syntax node can also convert to [`rowan::cursor::SyntaxNode`] to
 //! modify the AST syntactically.
+//!
+//! ## Produce: Span Map
+//! The text of the AST is not always sliced from the input, e.g. when macros
+//! are expanded. [`parse_with_span_map`] additionally returns a [`SpanMap`] to
+//! locate syntax nodes in the input.
 
 mod arg_match;
 mod parser;
+mod span_map;
 pub mod syntax;
 
 pub use mitex_spec as spec;
+pub use span_map::SpanMap;
 pub use spec::preludes::command as command_preludes;
 pub use spec::*;
 use syntax::SyntaxNode;
@@ -39,3 +46,16 @@ pub fn parse(input: &str, spec: CommandSpec) -> SyntaxNode {
 pub fn parse_without_macro(input: &str, spec: CommandSpec) -> SyntaxNode {
     SyntaxNode::new_root(Parser::new(input, spec).parse())
 }
+
+/// Parse the input text like [`parse`], and also return a [`SpanMap`] from
+/// offsets in the syntax tree back to byte offsets in the input
+pub fn parse_with_span_map(input: &str, spec: CommandSpec) -> (SyntaxNode, SpanMap) {
+    let (green, spans) = Parser::new_macro(input, spec).parse_with_span_map();
+    (SyntaxNode::new_root(green), spans)
+}
+
+/// It is only for internal testing
+pub fn parse_without_macro_with_span_map(input: &str, spec: CommandSpec) -> (SyntaxNode, SpanMap) {
+    let (green, spans) = Parser::new(input, spec).parse_with_span_map();
+    (SyntaxNode::new_root(green), spans)
+}
@@ -1,6 +1,7 @@
 use rowan::{Checkpoint, GreenNode, GreenNodeBuilder};
 
 use crate::arg_match::{ArgMatcher, ArgMatcherBuilder};
+use crate::span_map::{SpanMap, SpanMapBuilder};
 use crate::spec::argument_kind::*;
 use crate::syntax::SyntaxKind::{self, *};
 use crate::{ArgPattern, ArgShape, CommandSpec};
@@ -111,6 +112,8 @@ pub struct Parser<'a, S: TokenStream<'a> = ()> {
     lexer: Lexer<'a, S>,
     /// Helper for building syntax tree
     builder: GreenNodeBuilder<'static>,
+    /// Helper for mapping the syntax tree back to the input text
+    spans: SpanMapBuilder,
 
     /// Command specification
     spec: CommandSpec,
@@ -131,6 +134,7 @@ impl<'a> Parser<'a> {
         Self {
             lexer: Lexer::new(text, spec.clone()),
             builder: GreenNodeBuilder::new(),
+            spans: SpanMapBuilder::new(text),
             spec,
             arg_matchers: ArgMatcherBuilder::default(),
             list_state: Default::default(),
@@ -144,6 +148,7 @@ impl<'a> Parser<'a> {
         Parser::<'a, MacroEngine<'a>> {
             lexer,
             builder: GreenNodeBuilder::new(),
+            spans: SpanMapBuilder::new(text),
             spec,
             arg_matchers: ArgMatcherBuilder::default(),
             list_state: Default::default(),
@@ -187,12 +192,25 @@ impl<'a, S: TokenStream<'a>> Parser<'a, S> {
         self.lexer.peek()
     }
 
+    /// Builder Interface
+    /// Attach a token to the syntax tree, recording where it comes from
+    fn token(&mut self, kind: SyntaxKind, text: &str) {
+        let offset = self.spans.input_offset(text);
+        self.token_at(kind, text, offset);
+    }
+
+    /// Builder Interface
+    /// Attach a token located at the given input offset to the syntax tree
+    fn token_at(&mut self, kind: SyntaxKind, text: &str, offset: Option<usize>) {
+        self.spans.record(offset, text.len());
+        self.builder.token(kind.into(), text);
+    }
+
     /// Lexer Interface
     /// Consume the next token and attach it to the syntax tree
     fn eat(&mut self) {
         let (kind, text) = self.lexer.eat().unwrap();
-        let kind: SyntaxKind = kind.into();
-        self.builder.token(kind.into(), text);
+        self.token(kind.into(), text);
     }
 
     /// Lexer Interface
@@ -206,7 +224,7 @@ impl<'a, S: TokenStream<'a>> Parser<'a, S> {
     /// syntax kind
     fn eat_as(&mut self, kind: SyntaxKind) {
         let (_, text) = self.lexer.eat().unwrap();
-        self.builder.token(kind.into(), text);
+        self.token(kind, text);
     }
 
     /// Lexer Interface
@@ -230,17 +248,16 @@ impl<'a, S: TokenStream<'a>> Parser<'a, S> {
 
     /// Lexer Interface
     fn extract_holding_trivia(&mut self) {
-        for (kind, text) in self.trivia_buffer.drain(..) {
-            let kind: SyntaxKind = kind.into();
-            self.builder.token(kind.into(), text);
+        for (kind, text) in std::mem::take(&mut self.trivia_buffer) {
+            self.token(kind.into(), text);
         }
     }
 
     /// Lexer Interface
     fn single_char(&mut self) -> Option<()> {
         let first_char = self.lexer.peek_char()?;
-        self.builder
-            .token(TokenWord.into(), &first_char.to_string());
+        let offset = self.spans.input_offset(self.lexer.peek_text()?);
+        self.token_at(TokenWord, &first_char.to_string(), offset);
         self.lexer.consume_utf8_bytes(first_char.len_utf8());
 
         Some(())
@@ -256,11 +273,17 @@ impl<'a, S: TokenStream<'a>> Parser<'a, S> {
 
     /// Entry point
     /// The main entry point of the parser
-    pub fn parse(mut self) -> GreenNode {
+    pub fn parse(self) -> GreenNode {
+        self.parse_with_span_map().0
+    }
+
+    /// Entry point
+    /// Parse the input and also return a map from the tree back to the input
+    pub fn parse_with_span_map(mut self) -> (GreenNode, SpanMap) {
         self.builder.start_node(ScopeRoot.into());
         self.item_list(ParseScope::Root);
         self.builder.finish_node();
-        self.builder.finish()
+        (self.builder.finish(), self.spans.finish())
     }
 
     /// Parsing Helper
@@ -403,7 +426,8 @@ impl<'a, S: TokenStream<'a>> Parser<'a, S> {
             | Token::Tilde
             | Token::Slash
             | Token::Ditto
-            | Token::Semicolon => self.eat(),
+            | Token::Semicolon
+            | Token::Verbatim => self.eat(),
             Token::Word => {
                 if not_prefer_single_char {
                     self.text()
@@ -704,17 +728,20 @@ impl<'a, S: TokenStream<'a>> Parser<'a, S> {
                 Token::Word if !GREEDY => {
                     // Split the word into single characters for term matching
                     let mut split_cnt = 0usize;
-                    for c in self.lexer.peek_text().unwrap().chars() {
+                    let word = self.lexer.peek_text().unwrap();
+                    let offset = self.spans.input_offset(word);
+                    for c in word.chars() {
                         if !searcher.try_match(ARGUMENT_KIND_TERM) {
                             if split_cnt > 0 {
                                 self.lexer.consume_utf8_bytes(split_cnt);
                             }
                             return;
                         }
+                        let char_offset = offset.map(|offset| offset + split_cnt);
                         split_cnt += c.len_utf8();
 
                         arg::<GREEDY, _, _>(self, |this| {
-                            this.builder.token(TokenWord.into(), &c.to_string())
+                            this.token_at(TokenWord, &c.to_string(), char_offset)
                         });
                     }
 
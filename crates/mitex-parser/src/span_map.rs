@@ -0,0 +1,95 @@
+//! Mapping from offsets in a syntax tree back to byte offsets in the input.
+//!
+//! The text of a syntax tree is not always identical to the input. For
+//! example, `\begin{name}` is stored as `name`, and tokens expanded from a
+//! macro are copied from its definition. A [`SpanMap`] records where each run
+//! of tree text comes from, so that consumers of the tree can report locations
+//! in the original input.
+
+use std::ops::Range;
+
+use rowan::TextRange;
+
+/// A sorted list of `(tree offset, input offset)` anchors.
+///
+/// Tree text between two anchors is copied verbatim from the input, starting
+/// at the input offset of the former anchor.
+#[derive(Debug, Clone, Default)]
+pub struct SpanMap {
+    anchors: Vec<(usize, usize)>,
+    /// Length of the tree text
+    tree_len: usize,
+    /// Length of the input text
+    input_len: usize,
+}
+
+impl SpanMap {
+    /// Translate an offset in the syntax tree into a byte offset in the input.
+    pub fn to_input(&self, tree_offset: usize) -> usize {
+        let idx = self
+            .anchors
+            .partition_point(|(tree, _)| *tree <= tree_offset);
+        let Some(&(tree, input)) = idx.checked_sub(1).and_then(|idx| self.anchors.get(idx)) else {
+            return tree_offset.min(self.input_len);
+        };
+        (input + (tree_offset - tree)).min(self.input_len)
+    }
+
+    /// Translate a range in the syntax tree into a byte range in the input.
+    ///
+    /// The end of a non-empty range is translated by its last byte, so that a
+    /// range never spans over text that is not part of it in the input.
+    pub fn to_input_range(&self, range: TextRange) -> Range<usize> {
+        let start = self.to_input(range.start().into());
+        if range.is_empty() {
+            return start..start;
+        }
+        let end = self.to_input(usize::from(range.end()) - 1) + 1;
+        start..end.max(start).min(self.input_len)
+    }
+}
+
+/// Incrementally builds a [`SpanMap`] while tokens are attached to a tree.
+#[derive(Debug)]
+pub(crate) struct SpanMapBuilder {
+    input_start: usize,
+    map: SpanMap,
+    /// The input offset right after the last recorded token
+    next_input: usize,
+}
+
+impl SpanMapBuilder {
+    pub fn new(input: &str) -> Self {
+        Self {
+            input_start: input.as_ptr() as usize,
+            map: SpanMap {
+                input_len: input.len(),
+                ..Default::default()
+            },
+            next_input: 0,
+        }
+    }
+
+    /// Get the input offset of a slice, if it is borrowed from the input.
+    pub fn input_offset(&self, text: &str) -> Option<usize> {
+        let offset = (text.as_ptr() as usize).checked_sub(self.input_start)?;
+        (offset + text.len() <= self.map.input_len).then_some(offset)
+    }
+
+    /// Record a token of `len` bytes located at `input_offset` in the input.
+    ///
+    /// Tokens that are not borrowed from the input are assumed to follow the
+    /// previous token.
+    pub fn record(&mut self, input_offset: Option<usize>, len: usize) {
+        let input = input_offset.unwrap_or(self.next_input);
+        if input != self.next_input || self.map.anchors.is_empty() {
+            self.map.anchors.push((self.map.tree_len, input));
+        }
+        self.map.tree_len += len;
+        self.next_input = input + len;
+    }
+
+    pub fn finish(self) -> SpanMap {
+        self.map
+    }
+}
@@ -76,6 +76,7 @@ pub enum SyntaxKind {
     TokenApostrophe,
     TokenDitto,
     TokenSemicolon,
+    TokenVerbatim,
     TokenCommandSym,
 
     // Clauses
@@ -123,6 +124,7 @@ impl From<Token> for SyntaxKind {
             Token::Apostrophe => SyntaxKind::TokenApostrophe,
             Token::Ditto => SyntaxKind::TokenDitto,
             Token::Semicolon => SyntaxKind::TokenSemicolon,
+            Token::Verbatim => SyntaxKind::TokenVerbatim,
             Token::Caret => SyntaxKind::TokenCaret,
             Token::Word => SyntaxKind::TokenWord,
             Token::Dollar => SyntaxKind::TokenDollar,
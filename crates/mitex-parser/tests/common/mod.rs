@@ -83,6 +83,7 @@ pub mod ast_snapshot {
                 SyntaxKind::TokenApostrophe => "apostrophe'",
                 SyntaxKind::TokenDitto => "ditto'",
                 SyntaxKind::TokenSemicolon => "semicolon'",
+                SyntaxKind::TokenVerbatim => "verbatim'",
                 SyntaxKind::TokenCommandSym => "sym'",
                 SyntaxKind::ClauseCommandName => "cmd-name",
                 SyntaxKind::ClauseArgument => "args",
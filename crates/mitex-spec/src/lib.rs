@@ -53,10 +53,17 @@ pub struct CommandSpecRepr {
 }
 
 /// Command specification that is cheap to clone
+///
+/// A command specification may be layered over a base specification by
+/// [`CommandSpec::overlay`], in which case its items shadow the items of the
+/// base with the same names.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "rkyv", derive(Archive, rDeser, rSer))]
 #[cfg_attr(feature = "rkyv-validation", archive(check_bytes))]
-pub struct CommandSpec(Arc<CommandSpecRepr>);
+pub struct CommandSpec(
+    Arc<CommandSpecRepr>,
+    #[cfg_attr(feature = "rkyv", with(rkyv::with::Skip))] Option<Arc<CommandSpec>>,
+);
 
 #[cfg(feature = "rkyv")]
 impl CommandSpec {
@@ -70,7 +77,16 @@ impl CommandSpec {
         use rkyv::ser::{serializers::AllocSerializer, Serializer};
 
         let mut serializer = AllocSerializer::<0>::default();
-        serializer.serialize_value(self.0.as_ref()).unwrap();
+        if self.1.is_some() {
+            // flatten the layers
+            let commands = self.items().map(|(k, v)| (k.to_owned(), v.clone()));
+            let repr = CommandSpecRepr {
+                commands: commands.collect(),
+            };
+            serializer.serialize_value(&repr).unwrap();
+        } else {
+            serializer.serialize_value(self.0.as_ref()).unwrap();
+        }
         let bytes = serializer.into_serializer().into_inner();
 
         bytes.into_vec()
@@ -81,7 +97,7 @@ impl CommandSpec {
     pub fn from_bytes(bytes: &[u8]) -> Self {
         let s = stream::BytesModuleStream::from_slice(bytes);
 
-        Self(Arc::new(s.checkout_owned()))
+        Self(Arc::new(s.checkout_owned()), None)
     }
 
     /// # Safety
@@ -89,24 +105,54 @@ impl CommandSpec {
     pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> Self {
         let s = stream::BytesModuleStream::from_slice(bytes);
 
-        Self(Arc::new(s.checkout_owned_unchecked()))
+        Self(Arc::new(s.checkout_owned_unchecked()), None)
     }
 }
 
 impl CommandSpec {
     /// Create a new command specification
     pub fn new(commands: rustc_hash::FxHashMap<String, CommandSpecItem>) -> Self {
-        Self(Arc::new(CommandSpecRepr { commands }))
+        Self(Arc::new(CommandSpecRepr { commands }), None)
+    }
+
+    /// Layer `top` over this command specification, without copying the
+    /// items of either. Items of `top` shadow the items of `self` with the
+    /// same names.
+    ///
+    /// For example, a project may add a few commands to the default
+    /// specification by `DEFAULT_SPEC.overlay(project_spec)`.
+    pub fn overlay(&self, top: CommandSpec) -> Self {
+        let base = match &top.1 {
+            Some(top_base) => self.overlay(top_base.as_ref().clone()),
+            None => self.clone(),
+        };
+        Self(top.0, Some(Arc::new(base)))
     }
 
     /// Get an item by name
     pub fn get(&self, name: &str) -> Option<&CommandSpecItem> {
-        self.0.commands.get(name)
+        match self.0.commands.get(name) {
+            Some(item) => Some(item),
+            None => self.1.as_ref()?.get(name),
+        }
     }
 
-    /// Iterate all items
+    /// Iterate all items, skipping the items shadowed by an overlay
     pub fn items(&self) -> impl Iterator<Item = (&str, &CommandSpecItem)> {
-        self.0.commands.iter().map(|(k, v)| (k.as_str(), v))
+        self.items_dyn()
+    }
+
+    fn items_dyn(&self) -> Box<dyn Iterator<Item = (&str, &CommandSpecItem)> + '_> {
+        let items = self.0.commands.iter().map(|(k, v)| (k.as_str(), v));
+        match &self.1 {
+            Some(base) => Box::new(
+                items.chain(
+                    base.items_dyn()
+                        .filter(|(k, _)| !self.0.commands.contains_key(*k)),
+                ),
+            ),
+            None => Box::new(items),
+        }
     }
 
     /// Get an item by name in kind of _command_
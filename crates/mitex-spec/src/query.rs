@@ -141,7 +141,7 @@ pub struct CommandSpecRepr {
 
 impl From<CommandSpecRepr> for crate::CommandSpec {
     fn from(repr: CommandSpecRepr) -> Self {
-        Self(Arc::new(repr.into()))
+        Self(Arc::new(repr.into()), None)
     }
 }
 
@@ -42,19 +42,30 @@ mod impls {
         (!spec.is_empty()).then(|| mitex_spec::CommandSpec::from_bytes(spec))
     }
 
+    /// Converts LaTeX code starting in `mode` into a plain text.
+    fn convert(input: &str, mode: mitex::LaTeXMode, spec: &[u8]) -> Result<String, String> {
+        let mut options = mitex::ConvertOptions::default().mode(mode);
+        if let Some(spec) = extract_spec(spec) {
+            options = options.spec(spec);
+        }
+        mitex::convert(input, &options)
+            .map(|res| res.output)
+            .map_err(|e| e.to_string())
+    }
+
     /// Converts a LaTeX math equation into a plain text. You can pass an binary
     /// (rkyv) command specification by `spec` at the same time to customize
     /// parsing.
     #[cfg_attr(feature = "web", wasm_bindgen)]
     pub fn convert_math(input: &str, spec: &[u8]) -> Result<String, String> {
-        mitex::convert_math(input, extract_spec(spec))
+        convert(input, mitex::LaTeXMode::Math, spec)
     }
 
     /// Converts a LaTeX code into a plain text. You can pass an binary (rkyv)
     /// command specification by `spec` at the same time to customize parsing.
     #[cfg_attr(feature = "web", wasm_bindgen)]
     pub fn convert_text(input: &str, spec: &[u8]) -> Result<String, String> {
-        mitex::convert_text(input, extract_spec(spec))
+        convert(input, mitex::LaTeXMode::Text, spec)
     }
 }
 
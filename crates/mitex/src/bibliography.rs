@@ -0,0 +1,352 @@
+//! Citations and bibliographies, e.g. `\cite` and `thebibliography`.
+
+use core::fmt;
+use std::fmt::Write;
+
+use mitex_parser::spec::*;
+use mitex_parser::syntax::{CmdItem, EnvItem, SyntaxElement as LatexSyntaxElem};
+use mitex_parser::syntax::{SyntaxKind as LatexSyntaxKind, SyntaxNode};
+use rowan::ast::AstNode;
+
+use crate::converter::{argument_elems, argument_text, is_blank, Converter, Error, LaTeXMode};
+use crate::escape::{is_label_char, is_plain_label, write_label, write_label_value, write_string};
+
+/// The form of a citation, see `cite` of Typst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CiteForm {
+    /// `[1]` for `\cite` and `\citep`
+    Normal,
+    /// `Doe [1]` for `\citet`
+    Prose,
+    /// `Doe` for `\citeauthor`
+    Author,
+    /// `2020` for `\citeyear`
+    Year,
+    /// The full reference for `\fullcite`
+    Full,
+}
+
+impl CiteForm {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Prose => "prose",
+            Self::Author => "author",
+            Self::Year => "year",
+            Self::Full => "full",
+        }
+    }
+}
+
+/// The settings of the bibliography of a document, given by
+/// `\bibliographystyle`, `\addbibresource` and the like
+#[derive(Debug, Default)]
+pub(crate) struct BibSettings {
+    style: Option<&'static str>,
+    // the files of `\addbibresource` for `\printbibliography`
+    resources: Vec<String>,
+    // whether `\nocite{*}` lists all the entries
+    full: bool,
+}
+
+impl BibSettings {
+    /// Collect the settings from the commands among some nodes
+    pub(crate) fn collect(&mut self, nodes: impl Iterator<Item = SyntaxNode>) {
+        for cmd in nodes.filter_map(CmdItem::cast) {
+            let mut args = cmd
+                .arguments()
+                .map(|arg| argument_text(&arg))
+                .collect::<Vec<_>>();
+            let Some(last) = args.pop() else {
+                continue;
+            };
+            match cmd.name_tok().unwrap().text() {
+                "\\bibliographystyle" => self.style = typst_bib_style(&last),
+                "\\addbibresource" => self.resources.push(last),
+                "\\nocite" => self.full |= last.split(',').any(|key| key.trim() == "*"),
+                // `\usepackage[style=apa]{biblatex}`
+                "\\usepackage" if last.split(',').any(|name| name.trim() == "biblatex") => {
+                    let options = args.first().map_or("", |options| {
+                        options.trim_start_matches('[').trim_end_matches(']')
+                    });
+                    for option in options.split(',') {
+                        let Some((key, value)) = option.split_once('=') else {
+                            continue;
+                        };
+                        if matches!(key.trim(), "style" | "bibstyle") {
+                            self.style = typst_bib_style(value.trim());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Get the Typst style of a bibliography from the style of BibTeX or
+/// BibLaTeX, e.g. `ieee` for `IEEEtran`
+fn typst_bib_style(name: &str) -> Option<&'static str> {
+    Some(match name.trim() {
+        "plain" | "unsrt" | "abbrv" | "ieeetr" | "IEEEtran" | "ieee" | "numeric"
+        | "numeric-comp" | "siam" => "ieee",
+        "alpha" | "amsalpha" | "alphabetic" => "alphanumeric",
+        "apalike" | "apa" | "apacite" => "apa",
+        "plainnat" | "abbrvnat" | "unsrtnat" | "authoryear" | "chicago" => "chicago-author-date",
+        "chicago-notes" | "verbose" => "chicago-notes",
+        "harvard" | "agsm" | "dcu" => "harvard-cite-them-right",
+        "mla" => "mla",
+        "nature" => "nature",
+        "vancouver" => "vancouver",
+        "acm" | "ACM-Reference-Format" => "association-for-computing-machinery",
+        "splncs04" | "splncs" => "springer-lecture-notes-in-computer-science",
+        "elsarticle-num" => "elsevier-vancouver",
+        "elsarticle-harv" => "elsevier-harvard",
+        "achemso" | "chem-acs" => "american-chemical-society",
+        "apsrev4-1" | "apsrev4-2" | "phys" => "american-physics-society",
+        _ => return None,
+    })
+}
+
+impl Converter<'_> {
+    /// Convert citations like `\cite[p.~5]{a,b}` into `@a @b[p.~5]`, or
+    /// `cite` with the form of the command. The options of `natbib` are the
+    /// prenote and the postnote if there are two.
+    pub(crate) fn convert_command_cite(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        cmd: &CmdItem,
+        form: CiteForm,
+        spec: &CommandSpec,
+    ) -> Result<(), Error> {
+        let mut args = cmd.arguments().collect::<Vec<_>>();
+        let keys = args
+            .pop()
+            .map(|arg| argument_text(&arg))
+            .unwrap_or_default();
+        let keys = keys
+            .split(',')
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .collect::<Vec<_>>();
+        let (prenote, postnote) = match args.as_slice() {
+            [post] => (None, Some(post)),
+            [pre, post] => (Some(pre), Some(post)),
+            _ => (None, None),
+        };
+        let non_blank = |arg: &&SyntaxNode| !argument_elems(arg).iter().all(is_blank);
+        let (prenote, postnote) = (prenote.filter(non_blank), postnote.filter(non_blank));
+
+        let text = matches!(self.mode, LaTeXMode::Text);
+        if let Some(prenote) = prenote {
+            self.convert_cite_note(f, prenote, spec)?;
+            f.write_char(' ')?;
+        }
+        // a word right after `@key` would continue its name
+        let runs_on = cmd
+            .syntax()
+            .next_sibling_or_token()
+            .and_then(|elem| match elem {
+                LatexSyntaxElem::Node(node) => node.first_token(),
+                LatexSyntaxElem::Token(token) => Some(token),
+            })
+            .filter(|token| token.kind() == LatexSyntaxKind::TokenWord)
+            .is_some_and(|token| {
+                let rest = token.text().trim_start_matches(['.', ':']);
+                rest.starts_with(is_label_char)
+            });
+        for (index, key) in keys.iter().enumerate() {
+            let last = index + 1 == keys.len();
+            if index > 0 {
+                f.write_char(' ')?;
+            }
+            let postnote = postnote.filter(|_| last);
+            // trailing `.` and `:` are not part of the citation syntax
+            let syntax = text
+                && form == CiteForm::Normal
+                && is_plain_label(key)
+                && !key.ends_with(['.', ':'])
+                && !(last && runs_on && postnote.is_none());
+            if syntax {
+                write!(f, "@{key}")?;
+                if let Some(postnote) = postnote {
+                    f.write_char('[')?;
+                    self.convert_cite_note(f, postnote, spec)?;
+                    f.write_char(']')?;
+                }
+                continue;
+            }
+            f.write_str("#cite(")?;
+            write_label_value(f, key)?;
+            if form != CiteForm::Normal {
+                write!(f, ", form: \"{}\"", form.name())?;
+            }
+            if let Some(postnote) = postnote {
+                f.write_str(", supplement: [")?;
+                self.convert_cite_note(f, postnote, spec)?;
+                f.write_char(']')?;
+            }
+            f.write_char(')')?;
+            if text {
+                f.write_char(';')?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Convert a note of a citation like `p.~5`, whose `~` is a tie, i.e. a
+    /// non-breaking space
+    fn convert_cite_note(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        note: &SyntaxNode,
+        spec: &CommandSpec,
+    ) -> Result<(), Error> {
+        let elems = argument_elems(note)
+            .into_iter()
+            .flat_map(|elem| match elem.kind() {
+                LatexSyntaxKind::ItemText => {
+                    elem.as_node().unwrap().children_with_tokens().collect()
+                }
+                _ => vec![elem],
+            })
+            .collect::<Vec<_>>();
+        let start = elems
+            .iter()
+            .position(|e| !is_blank(e))
+            .unwrap_or(elems.len());
+        let end = elems
+            .iter()
+            .rposition(|e| !is_blank(e))
+            .map_or(start, |i| i + 1);
+        for elem in elems.into_iter().take(end).skip(start) {
+            match elem.kind() {
+                LatexSyntaxKind::TokenTilde if matches!(self.mode, LaTeXMode::Text) => {
+                    f.write_char('~')?
+                }
+                _ => self.convert(f, elem, spec)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Convert `\bibliography{refs}` and `\printbibliography` into
+    /// `#bibliography("refs.bib")` with the style of the document
+    pub(crate) fn convert_command_bibliography(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        cmd: &CmdItem,
+    ) -> Result<(), Error> {
+        let files = match cmd.name_tok().unwrap().text() {
+            "\\bibliography" => cmd
+                .arguments()
+                .last()
+                .map(|arg| argument_text(&arg))
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|file| !file.is_empty())
+                .map(|file| match file.ends_with(".bib") {
+                    true => file.to_owned(),
+                    false => format!("{file}.bib"),
+                })
+                .collect(),
+            _ => self.bib.resources.clone(),
+        };
+        if files.is_empty() {
+            return Ok(());
+        }
+
+        f.write_str("#bibliography(")?;
+        if files.len() > 1 {
+            f.write_char('(')?;
+        }
+        for (i, file) in files.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            f.write_char('"')?;
+            write_string(f, file)?;
+            f.write_char('"')?;
+        }
+        if files.len() > 1 {
+            f.write_char(')')?;
+        }
+        if self.bib.full {
+            f.write_str(", full: true")?;
+        }
+        if let Some(style) = self.bib.style {
+            write!(f, ", style: \"{style}\"")?;
+        }
+        f.write_char(')')?;
+        Ok(())
+    }
+
+    /// Convert `thebibliography` into a list of references, each one a
+    /// figure labelled by the key of its `\bibitem`, so that `@key` refers to
+    /// it by its number or the label given to `\bibitem`
+    pub(crate) fn convert_env_thebibliography(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        env: &EnvItem,
+        spec: &CommandSpec,
+    ) -> Result<(), Error> {
+        let mut items: Vec<(CmdItem, Vec<LatexSyntaxElem>)> = vec![];
+        for child in env.syntax().children_with_tokens() {
+            match child.kind() {
+                LatexSyntaxKind::ItemBegin | LatexSyntaxKind::ItemEnd => {}
+                LatexSyntaxKind::ItemCmd => {
+                    let cmd = CmdItem::cast(child.as_node().unwrap().clone()).unwrap();
+                    if cmd.name_tok().unwrap().text() == "\\bibitem" {
+                        items.push((cmd, vec![]));
+                    } else if let Some((_, elems)) = items.last_mut() {
+                        elems.push(child);
+                    }
+                }
+                // so that the line break ending an item is trimmed
+                LatexSyntaxKind::ItemText => {
+                    if let Some((_, elems)) = items.last_mut() {
+                        elems.extend(child.as_node().unwrap().children_with_tokens());
+                    }
+                }
+                _ => {
+                    if let Some((_, elems)) = items.last_mut() {
+                        elems.push(child);
+                    }
+                }
+            }
+        }
+
+        f.write_str("#heading(numbering: none)[References]\n#[\n")?;
+        f.write_str(
+            "#show figure.where(kind: \"thebibliography\"): it => grid(columns: (auto, 1fr), \
+             column-gutter: 0.6em, it.counter.display(it.numbering), align(left, it.body))\n",
+        )?;
+        for (bibitem, elems) in items {
+            let mut args = bibitem.arguments().collect::<Vec<_>>();
+            let key = args
+                .pop()
+                .map(|arg| argument_text(&arg))
+                .unwrap_or_default();
+            f.write_str("#figure(kind: \"thebibliography\", supplement: none, numbering: ")?;
+            match args.first() {
+                Some(label) => {
+                    f.write_str("_ => [\\[")?;
+                    self.convert_elems(f, argument_elems(label), spec)?;
+                    f.write_str("\\]]")?;
+                }
+                None => f.write_str("\"[1]\"")?,
+            }
+            f.write_str(")[")?;
+            self.convert_elems(f, elems, spec)?;
+            f.write_str("]")?;
+            if !key.is_empty() {
+                f.write_char(' ')?;
+                write_label(f, &key)?;
+            }
+            f.write_char('\n')?;
+        }
+        f.write_str("]")?;
+        Ok(())
+    }
+}
@@ -1,6 +1,8 @@
 use core::fmt;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt::Write;
+use std::ops::Range;
 use std::rc::Rc;
 
 pub use mitex_parser::spec::*;
@@ -9,18 +11,42 @@ use mitex_parser::syntax::CmdItem;
 use mitex_parser::syntax::EnvItem;
 use mitex_parser::syntax::FormulaItem;
 use mitex_parser::syntax::SyntaxNode;
+use mitex_parser::SpanMap;
 use mitex_spec_gen::DEFAULT_SPEC;
 use rowan::ast::AstNode;
-
-#[derive(Debug, Clone, Copy, Default)]
+use rowan::{TextRange, TextSize};
+
+use crate::bibliography::BibSettings;
+use crate::error::{ConvertError, ConvertErrorKind};
+use crate::escape::{
+    clean_label, control_symbol_markup, is_label_char, is_plain_label, write_label,
+    write_label_value, write_markup, write_raw, write_string,
+};
+use crate::front_matter::FrontMatter;
+use crate::graphics::{graphics_dirs, resolve_image, GraphicsOptions};
+use crate::handler::HandlerContext;
+use crate::length::typst_length;
+use crate::options::{ConvertOptions, ErrorPolicy, LabelPolicy, MathStyle, Target};
+use crate::qualified::math_module;
+use crate::source_map::{SourceMap, SourceMapping};
+use crate::standalone::{scope_item, write_prelude, ScopeItem};
+use crate::tabular::{parse_columns, Columns};
+use crate::theorem::Theorems;
+use crate::tidy::{tidy, Context};
+use crate::unicode::{compose, ligatures, text_symbol};
+
+/// The mode of LaTeX code being converted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum LaTeXMode {
+    /// Text mode, e.g. the body of a document
     #[default]
     Text,
+    /// Math mode, e.g. the body of `$..$`
     Math,
 }
 
 #[derive(Debug, Clone, Copy, Default)]
-enum LaTeXEnv {
+pub(crate) enum LaTeXEnv {
     #[default]
     // Text mode
     None,
@@ -28,6 +54,8 @@ enum LaTeXEnv {
     Table,
     Itemize,
     Enumerate,
+    Heading,
+    Theorem,
     // Math mode
     Math,
     Matrix,
@@ -36,8 +64,35 @@ enum LaTeXEnv {
     MathCurlyGroup,
 }
 
-pub struct Converter {
-    mode: LaTeXMode,
+/// How a reference shows its target
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum RefStyle {
+    /// The number only, e.g. `1` for `\ref`
+    Number,
+    /// The number with a lowercase supplement, e.g. `figure 1` for `\cref`
+    Lower,
+    /// The number with the supplement, e.g. `Figure 1` for `\autoref`
+    Upper,
+}
+
+pub struct Converter<'a> {
+    // input text, for reporting and recovering from errors
+    input: &'a str,
+    // map from the syntax tree back to the input text
+    spans: &'a SpanMap,
+    pub(crate) options: &'a ConvertOptions,
+    // errors recovered from so far
+    diagnostics: Vec<ConvertError>,
+    // output written so far, to drop what an element failing to convert has
+    // written before recovering
+    output: &'a RefCell<String>,
+    // length of the output written so far
+    written: &'a Cell<usize>,
+    // whether the output is at the start of a line or of a content block
+    line_start: &'a Cell<bool>,
+    // output ranges of the nodes converted so far, if a source map is wanted
+    mappings: Option<Vec<SourceMapping>>,
+    pub(crate) mode: LaTeXMode,
     env: LaTeXEnv,
     // indent for itemize and enumerate
     indent: usize,
@@ -45,46 +100,122 @@ pub struct Converter {
     label: Option<String>,
     // skip the space at the beginning of the line
     skip_next_space: bool,
+    // output offset of the current matrix cell, after its leading spaces
+    cell_start: usize,
+    // tokens already converted along with a previous element, e.g. `[4pt]`
+    // of `\\[4pt]`
+    skip: Option<TextRange>,
+    // the `&` in the current row of an alignment, and the space between its
+    // column pairs if any
+    column: usize,
+    column_gap: Option<&'static str>,
+    // horizontal alignments of the columns of the current matrix, when they
+    // are mixed and aligned by alignment points
+    cell_aligns: Vec<&'static str>,
+    // names written by the output, of the MiTeX scope or native Typst
+    aliases: BTreeSet<String>,
+    // title, authors and the like of the document, for `\maketitle`
+    pub(crate) front: FrontMatter,
+    // style and files of the bibliography of the document
+    pub(crate) bib: BibSettings,
+    // theorem environments defined by `\newtheorem`
+    pub(crate) theorems: Theorems,
+    // `\footnotetext`s not written yet, joined with the `\footnotemark`s
+    // before them
+    footnote_texts: Vec<CmdItem>,
+    // directories of `\graphicspath` to look up images in
+    graphics_dirs: Vec<String>,
+    // Typst names of the label keys of the document
+    labels: HashMap<String, String>,
 }
 
-impl Converter {
-    fn new(mode: LaTeXMode) -> Self {
+/// Rule telling the short captions in the outline from the captions
+const OUTLINE_RULE: &str =
+    "#show outline: it => { state(\"mitex-outline\", false).update(true); it; \
+                            state(\"mitex-outline\", false).update(false) }\n\n";
+
+impl<'a> Converter<'a> {
+    fn new(
+        input: &'a str,
+        spans: &'a SpanMap,
+        output: &'a RefCell<String>,
+        written: &'a Cell<usize>,
+        line_start: &'a Cell<bool>,
+        options: &'a ConvertOptions,
+    ) -> Self {
         Self {
-            mode,
+            input,
+            spans,
+            options,
+            diagnostics: Vec::new(),
+            output,
+            written,
+            line_start,
+            mappings: options.source_map.then(Vec::new),
+            mode: options.mode,
             env: LaTeXEnv::default(),
             indent: 0,
             label: None,
             skip_next_space: true,
+            cell_start: 0,
+            skip: None,
+            column: 0,
+            column_gap: None,
+            cell_aligns: Vec::new(),
+            aliases: BTreeSet::new(),
+            front: FrontMatter::default(),
+            bib: BibSettings::default(),
+            theorems: Theorems::default(),
+            footnote_texts: vec![],
+            graphics_dirs: vec![],
+            labels: HashMap::new(),
         }
     }
 
     #[must_use]
-    fn enter_mode(&mut self, context: LaTeXMode) -> LaTeXMode {
+    pub(crate) fn enter_mode(&mut self, context: LaTeXMode) -> LaTeXMode {
         let prev = self.mode;
         self.mode = context;
         prev
     }
 
-    fn exit_mode(&mut self, prev: LaTeXMode) {
+    pub(crate) fn exit_mode(&mut self, prev: LaTeXMode) {
         self.mode = prev;
     }
 
     #[must_use]
-    fn enter_env(&mut self, context: LaTeXEnv) -> LaTeXEnv {
+    pub(crate) fn enter_env(&mut self, context: LaTeXEnv) -> LaTeXEnv {
         let prev = self.env;
         self.env = context;
         if matches!(self.env, LaTeXEnv::Itemize | LaTeXEnv::Enumerate) {
-            self.indent += 2;
+            self.indent += self.options.list_indent;
         }
         prev
     }
 
-    fn exit_env(&mut self, prev: LaTeXEnv) {
+    pub(crate) fn exit_env(&mut self, prev: LaTeXEnv) {
         if matches!(self.env, LaTeXEnv::Itemize | LaTeXEnv::Enumerate) {
-            self.indent -= 2;
+            self.indent -= self.options.list_indent;
         }
         self.env = prev;
     }
+
+    /// Start an alignment whose column pairs are separated by `gap`, e.g.
+    /// `quad` for `align`, returning the state of the outer alignment
+    #[must_use]
+    pub(crate) fn enter_columns(
+        &mut self,
+        gap: Option<&'static str>,
+    ) -> (usize, Option<&'static str>) {
+        let prev = (self.column, self.column_gap);
+        (self.column, self.column_gap) = (0, gap);
+        prev
+    }
+
+    /// Restore the alignment returned by [`Converter::enter_columns`]
+    pub(crate) fn exit_columns(&mut self, prev: (usize, Option<&'static str>)) {
+        (self.column, self.column_gap) = prev;
+    }
 }
 
 // fn empty_node() -> GreenNode {
@@ -95,42 +226,693 @@ use mitex_parser::syntax::SyntaxElement as LatexSyntaxElem;
 use mitex_parser::syntax::SyntaxKind as LatexSyntaxKind;
 // use mitex_parser::syntax::SyntaxNode as LatexSyntaxNode;
 
+/// Errors raised inside the converter.
+///
+/// The range of a [`ConvertError`] is an offset in the syntax tree until it is
+/// mapped back to the input by [`convert_inner`].
 #[derive(Debug)]
-enum ConvertError {
+pub(crate) enum Error {
     Fmt(fmt::Error),
-    Str(String),
+    Convert(ConvertError),
 }
 
-impl fmt::Display for ConvertError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::Fmt(e) => write!(f, "fmt: {}", e),
-            Self::Str(e) => write!(f, "error: {}", e),
+impl From<fmt::Error> for Error {
+    fn from(e: fmt::Error) -> Self {
+        Self::Fmt(e)
+    }
+}
+
+impl From<ConvertError> for Error {
+    fn from(e: ConvertError) -> Self {
+        Self::Convert(e)
+    }
+}
+
+/// Write a word in math mode, keeping numbers like `1.5` together and
+/// separating other characters by spaces
+fn write_math_word(f: &mut fmt::Formatter<'_>, word: &str) -> fmt::Result {
+    let chars = word.chars().collect::<Vec<_>>();
+    for (i, &ch) in chars.iter().enumerate() {
+        f.write_char(ch)?;
+        let next = chars.get(i + 1).copied();
+        let in_number = |c: Option<char>| c.is_some_and(|c| c.is_ascii_digit());
+        let joined = if ch.is_ascii_digit() {
+            in_number(next) || next == Some('.') && in_number(chars.get(i + 2).copied())
+        } else {
+            ch == '.' && i > 0 && in_number(Some(chars[i - 1])) && in_number(next)
+        };
+        if !joined {
+            f.write_char(' ')?;
         }
     }
+    Ok(())
 }
 
-impl From<fmt::Error> for ConvertError {
-    fn from(e: fmt::Error) -> Self {
-        Self::Fmt(e)
+/// Get the key in the argument of a command like `\label{key}`
+fn label_key(cmd: &CmdItem) -> String {
+    cmd.arguments()
+        .next()
+        .map(|arg| argument_text(&arg))
+        .unwrap_or_default()
+}
+
+/// Get the text of an argument like `{2-3}` without its braces
+pub(crate) fn argument_text(arg: &SyntaxNode) -> String {
+    // remove { and } then trim
+    let text = arg.text().to_string();
+    let text = text.trim();
+    let text = text.strip_prefix('{').unwrap_or(text);
+    let text = text.strip_suffix('}').unwrap_or(text);
+    text.trim().to_owned()
+}
+
+/// Get the letters of an accented argument, e.g. `i` for `{\i}`, or `None`
+/// if it is not plain text
+pub(crate) fn accent_base(arg: &SyntaxNode) -> Option<String> {
+    let mut base = String::new();
+    for elem in arg.descendants_with_tokens() {
+        let LatexSyntaxElem::Token(token) = elem else {
+            continue;
+        };
+        match token.kind() {
+            LatexSyntaxKind::TokenWord => base.push_str(token.text()),
+            LatexSyntaxKind::TokenLBrace
+            | LatexSyntaxKind::TokenRBrace
+            | LatexSyntaxKind::TokenWhiteSpace => {}
+            // the dotless `\i` and `\j` are the letters under accents
+            LatexSyntaxKind::ClauseCommandName => match &token.text()[1..] {
+                "i" => base.push('i'),
+                "j" => base.push('j'),
+                name => {
+                    base.push_str(text_symbol(name).filter(|s| s.chars().all(char::is_alphabetic))?)
+                }
+            },
+            _ => return None,
+        }
+    }
+    Some(base)
+}
+
+/// A figure to write, see [`Converter::write_figure`]
+struct FigureItem {
+    // the arguments like `kind: image` except the caption
+    args: String,
+    // the short caption, if any, and the caption
+    caption: Option<(Option<SyntaxNode>, SyntaxNode)>,
+    // whether it is a subfigure, numbered like `(a)`
+    sub: bool,
+    // the alignment of the content, `left` unless `\centering`
+    align: &'static str,
+    // `\label`s of the figure, apart from its content
+    labels: Vec<LatexSyntaxElem>,
+    elems: Vec<LatexSyntaxElem>,
+}
+
+/// Get the placement of a figure from its option like `[tb]`, or `None` if
+/// it stays in the flow of the text, e.g. for `[h]` and `[H]`
+fn figure_placement(option: &str) -> Option<&'static str> {
+    if option.contains('H') {
+        return None;
+    }
+    let (top, bottom, page) = (
+        option.contains('t'),
+        option.contains('b'),
+        option.contains('p'),
+    );
+    match (top, bottom, page) {
+        (false, false, false) => None,
+        (true, false, false) => Some("top"),
+        (false, true, false) => Some("bottom"),
+        _ => Some("auto"),
+    }
+}
+
+/// Get the elements in an argument like `{a b}` or `[a b]` without its
+/// delimiters
+pub(crate) fn argument_elems(arg: &SyntaxNode) -> Vec<LatexSyntaxElem> {
+    let mut children = arg.children_with_tokens().collect::<Vec<_>>();
+    if let [LatexSyntaxElem::Node(group)] = children.as_slice() {
+        if matches!(
+            group.kind(),
+            LatexSyntaxKind::ItemCurly | LatexSyntaxKind::ItemBracket
+        ) {
+            children = group
+                .children_with_tokens()
+                .filter(|e| {
+                    !matches!(
+                        e.kind(),
+                        LatexSyntaxKind::TokenLBrace
+                            | LatexSyntaxKind::TokenRBrace
+                            | LatexSyntaxKind::TokenLBracket
+                            | LatexSyntaxKind::TokenRBracket
+                    )
+                })
+                .collect();
+        }
+    }
+    children
+}
+
+/// Whether an element is only spacing or a comment
+pub(crate) fn is_blank(elem: &LatexSyntaxElem) -> bool {
+    matches!(
+        elem.kind(),
+        LatexSyntaxKind::TokenWhiteSpace
+            | LatexSyntaxKind::TokenLineBreak
+            | LatexSyntaxKind::TokenComment
+    )
+}
+
+/// Get the only command in some elements, if it is named `name`
+pub(crate) fn only_cmd(elems: &[LatexSyntaxElem], name: &str) -> Option<CmdItem> {
+    let mut elems = elems.iter().filter(|e| !is_blank(e));
+    let elem = elems.next().filter(|_| elems.next().is_none())?;
+    let cmd = CmdItem::cast(elem.as_node()?.clone())?;
+    (cmd.name_tok()?.text() == name).then_some(cmd)
+}
+
+/// Commands standing between the rows of a table
+const TABLE_RULES: &[&str] = &[
+    "\\hline",
+    "\\toprule",
+    "\\midrule",
+    "\\bottomrule",
+    "\\cline",
+    "\\cmidrule",
+    "\\endfirsthead",
+    "\\endhead",
+    "\\endfoot",
+    "\\endlastfoot",
+];
+
+/// A row of a table, or a command between rows like `\hline`
+enum TableItem {
+    Row(Vec<Vec<LatexSyntaxElem>>),
+    Rule(CmdItem),
+}
+
+/// Split the body of a tabular environment into rows of cells and rules
+fn table_items(env: &EnvItem) -> Vec<TableItem> {
+    let mut items = vec![];
+    let mut cells = vec![];
+    let mut cell = vec![];
+    let mut tail_end = TextSize::from(0);
+    for child in env.syntax().children_with_tokens() {
+        if child.text_range().start() < tail_end {
+            continue;
+        }
+        match child.kind() {
+            LatexSyntaxKind::ItemBegin | LatexSyntaxKind::ItemEnd => {}
+            LatexSyntaxKind::TokenAmpersand => cells.push(std::mem::take(&mut cell)),
+            LatexSyntaxKind::ItemNewLine => {
+                tail_end = newline_tail(&child).1;
+                cells.push(std::mem::take(&mut cell));
+                items.push(TableItem::Row(std::mem::take(&mut cells)));
+            }
+            LatexSyntaxKind::ItemCmd if cells.is_empty() && cell.iter().all(is_blank) => {
+                let cmd = CmdItem::cast(child.as_node().unwrap().clone()).unwrap();
+                let name = cmd.name_tok().unwrap();
+                if TABLE_RULES.contains(&name.text()) {
+                    cell.clear();
+                    items.push(TableItem::Rule(cmd));
+                } else {
+                    cell.push(child);
+                }
+            }
+            _ => cell.push(child),
+        }
+    }
+    if !cells.is_empty() || !cell.iter().all(is_blank) {
+        cells.push(cell);
+        items.push(TableItem::Row(cells));
+    }
+    items
+}
+
+/// A cell of a table, spanning the columns of `\multicolumn` and the rows of
+/// `\multirow`
+struct TableCell {
+    colspan: usize,
+    rowspan: usize,
+    align: Option<String>,
+    content: Vec<LatexSyntaxElem>,
+}
+
+impl TableCell {
+    fn new(elems: &[LatexSyntaxElem]) -> Self {
+        let start = elems.iter().take_while(|e| is_blank(e)).count();
+        let mut cell = Self {
+            colspan: 1,
+            rowspan: 1,
+            align: None,
+            content: elems[start..].to_vec(),
+        };
+        if let Some(cmd) = only_cmd(&cell.content, "\\multicolumn") {
+            if let [cols, align, content] = cmd.arguments().collect::<Vec<_>>().as_slice() {
+                cell.colspan = argument_text(cols).parse().unwrap_or(1).max(1);
+                cell.align = parse_columns(&argument_text(align), 0)
+                    .ok()
+                    .and_then(|cols| cols.columns.into_iter().next())
+                    .map(|col| col.align);
+                cell.content = argument_elems(content);
+            }
+        }
+        if let Some(cmd) = only_cmd(&cell.content, "\\multirow") {
+            // skip the optional arguments like `[t]`
+            let args = cmd
+                .arguments()
+                .filter(|arg| !arg.text().to_string().trim_start().starts_with('['))
+                .collect::<Vec<_>>();
+            if let [rows, _, content] = args.as_slice() {
+                // a negative count spans the rows above, which is not supported
+                cell.rowspan = argument_text(rows).parse().unwrap_or(1).max(1);
+                cell.content = argument_elems(content);
+            }
+        }
+        cell
+    }
+}
+
+/// Get the command named `name` among some elements, e.g. the `\caption`
+/// in a row of a table
+fn find_cmd(elems: &[LatexSyntaxElem], name: &str) -> Option<CmdItem> {
+    elems
+        .iter()
+        .filter_map(|e| CmdItem::cast(e.as_node()?.clone()))
+        .find(|cmd| cmd.name_tok().is_some_and(|tok| tok.text() == name))
+}
+
+/// Parse the columns of a rule like `\cline{2-3}`, as the start and the end of
+/// a `table.hline`
+fn rule_columns(cmd: &CmdItem) -> Option<(usize, usize)> {
+    let range = argument_text(&cmd.arguments().last()?);
+    let (start, end) = range.split_once('-')?;
+    let start = start.trim().parse::<usize>().ok()?;
+    let end = end.trim().parse::<usize>().ok()?;
+    (start > 0 && start <= end).then_some((start - 1, end))
+}
+
+/// A row of an equation environment, e.g. a line of `align` ended by `\\`
+struct EquationRow {
+    elems: Vec<LatexSyntaxElem>,
+    // `\tag{..}`, and whether it is `\tag*{..}`
+    tag: Option<(CmdItem, bool)>,
+    // `\nonumber` or `\notag`
+    nonumber: bool,
+    label: bool,
+    // the spacing below the row, e.g. `4pt` of `\\[4pt]`
+    gap: Option<String>,
+}
+
+impl EquationRow {
+    fn new(mut elems: Vec<LatexSyntaxElem>) -> Self {
+        let is_space = |e: &LatexSyntaxElem| {
+            matches!(
+                e.kind(),
+                LatexSyntaxKind::TokenWhiteSpace | LatexSyntaxKind::TokenLineBreak
+            )
+        };
+        // drop a trailing `\\` along with its spacing
+        if let Some(pos) = elems
+            .iter()
+            .rposition(|e| e.kind() == LatexSyntaxKind::ItemNewLine)
+        {
+            let (_, end) = newline_tail(&elems[pos]);
+            if elems[pos + 1..]
+                .iter()
+                .all(|e| e.text_range().end() <= end || is_space(e))
+            {
+                elems.truncate(pos);
+            }
+        }
+        while elems.last().is_some_and(is_space) {
+            elems.pop();
+        }
+        let start = elems
+            .iter()
+            .take_while(|e| is_space(e) || e.kind() == LatexSyntaxKind::ItemNewLine)
+            .count();
+        elems.drain(..start);
+
+        let mut row = Self {
+            elems,
+            tag: None,
+            nonumber: false,
+            label: false,
+            gap: None,
+        };
+        let cmds = row
+            .elems
+            .iter()
+            .filter_map(|e| e.as_node())
+            .flat_map(|node| node.descendants())
+            .filter_map(CmdItem::cast);
+        for cmd in cmds {
+            match cmd.name_tok().unwrap().text() {
+                "\\tag" => row.tag = Some((cmd, false)),
+                "\\tag*" => row.tag = Some((cmd, true)),
+                "\\nonumber" | "\\notag" => row.nonumber = true,
+                "\\label" => row.label = true,
+                _ => {}
+            }
+        }
+        row
     }
+
+    /// Whether the row shows a number in an environment numbering its rows
+    /// by default or not
+    fn numbered(&self, numbered: bool) -> bool {
+        self.tag.is_some() || numbered && !self.nonumber
+    }
+}
+
+/// Get the tokens following a `\\`, i.e. an optional `*` and spacing like
+/// `[4pt]`, returning the spacing if any and the end of the tokens
+fn newline_tail(newline: &LatexSyntaxElem) -> (Option<String>, TextSize) {
+    let mut end = newline.text_range().end();
+    let mut next = newline.next_sibling_or_token();
+    if let Some(star) = next
+        .clone()
+        .filter(|e| e.kind() == LatexSyntaxKind::TokenAsterisk)
+    {
+        end = star.text_range().end();
+        next = star.next_sibling_or_token();
+    }
+    match next {
+        Some(e) if e.kind() == LatexSyntaxKind::TokenLBracket => {}
+        _ => return (None, end),
+    }
+    let mut gap = String::new();
+    let mut next = newline.next_sibling_or_token();
+    while let Some(elem) = next {
+        if elem.text_range().end() <= end || elem.kind() == LatexSyntaxKind::TokenLBracket {
+            next = elem.next_sibling_or_token();
+            continue;
+        }
+        if elem.kind() == LatexSyntaxKind::TokenRBracket {
+            return (Some(gap), elem.text_range().end());
+        }
+        match &elem {
+            LatexSyntaxElem::Node(node) => gap.push_str(&node.text().to_string()),
+            LatexSyntaxElem::Token(token) => gap.push_str(token.text()),
+        }
+        next = elem.next_sibling_or_token();
+    }
+    // an unclosed bracket is not spacing
+    (None, end)
+}
+
+/// Split the body of an alignment into its rows, or keep it a single row
+/// if at most one row has a number or a label
+fn equation_rows(body: Vec<LatexSyntaxElem>, numbered: bool) -> Vec<EquationRow> {
+    let mut rows = vec![];
+    let mut elems = vec![];
+    let mut tail_end = TextSize::from(0);
+    for child in body.iter() {
+        if child.text_range().start() < tail_end {
+            continue;
+        }
+        if child.kind() == LatexSyntaxKind::ItemNewLine {
+            let (gap, end) = newline_tail(child);
+            tail_end = end;
+            let mut row = EquationRow::new(std::mem::take(&mut elems));
+            row.gap = gap;
+            rows.push(row);
+        } else {
+            elems.push(child.clone());
+        }
+    }
+    rows.push(EquationRow::new(elems));
+    rows.retain(|row| !row.elems.is_empty());
+
+    let marked = rows
+        .iter()
+        .filter(|row| row.numbered(numbered) || row.label)
+        .count();
+    if rows.len() > 1 && marked <= 1 {
+        // a single equation keeps the alignment of the rows, taking the
+        // number of the marked row if any
+        let mut row = EquationRow::new(body);
+        row.nonumber = !rows.iter().any(|row| row.numbered(numbered));
+        rows = vec![row];
+    }
+    rows
 }
 
-impl From<String> for ConvertError {
-    fn from(e: String) -> Self {
-        Self::Str(e)
+/// Write the `(` of a call, followed by its leading arguments if any
+fn write_call_open(f: &mut fmt::Formatter<'_>, args: Option<&str>) -> fmt::Result {
+    f.write_char('(')?;
+    if let Some(args) = args {
+        write!(f, "{args}, ")?;
     }
+    Ok(())
+}
+
+/// Convert a range in the syntax tree to a plain range
+fn tree_range(range: TextRange) -> Range<usize> {
+    range.start().into()..range.end().into()
 }
 
-impl Converter {
-    fn convert(
+impl Converter<'_> {
+    pub(crate) fn convert(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        elem: LatexSyntaxElem,
+        spec: &CommandSpec,
+    ) -> Result<(), Error> {
+        let mut track = elem.as_node().is_some() && self.mappings.is_some();
+        let recover = matches!(self.options.error_policy, ErrorPolicy::Recover);
+        if !recover && !track {
+            return self.convert_elem(f, elem, spec);
+        }
+
+        let range = self.input_range(&elem);
+        let output_start = self.written.get();
+        let line_start = self.line_start.get();
+        let (mode, env, indent) = (self.mode, self.env, self.indent);
+        let res = match self.convert_elem(f, elem, spec) {
+            // keep converting the rest of the document on errors, in place of
+            // what the element has written so far
+            Err(Error::Convert(e)) if recover => {
+                self.diagnostics.push(e);
+                (self.mode, self.env, self.indent) = (mode, env, indent);
+                self.output.borrow_mut().truncate(output_start);
+                self.written.set(output_start);
+                self.line_start.set(line_start);
+                if let Some(mappings) = self.mappings.as_mut() {
+                    mappings.retain(|m| m.output.end <= output_start);
+                }
+                track = self.mappings.is_some();
+                self.write_placeholder(f, range.clone())
+            }
+            res => res,
+        };
+
+        let output_end = self.written.get();
+        if let Some(mappings) = self.mappings.as_mut().filter(|_| track) {
+            if res.is_ok() && output_start < output_end {
+                mappings.push(SourceMapping {
+                    output: output_start..output_end,
+                    input: range,
+                });
+            }
+        }
+        res
+    }
+
+    /// Give the label keys of a document their Typst names, where a key
+    /// outside the label syntax is cleaned and told apart from the other
+    /// names by a number, e.g. `a-b-2` for `a/b` next to `a-b`
+    fn collect_labels(&mut self, root: &SyntaxNode) {
+        let keys = root
+            .descendants()
+            .filter_map(CmdItem::cast)
+            .filter(|cmd| cmd.name_tok().is_some_and(|name| name.text() == "\\label"))
+            .map(|cmd| label_key(&cmd))
+            .filter(|key| !key.is_empty())
+            .collect::<Vec<_>>();
+        let mut taken = keys
+            .iter()
+            .filter(|key| is_plain_label(key))
+            .cloned()
+            .collect::<HashSet<_>>();
+        for key in keys {
+            if is_plain_label(&key) || self.labels.contains_key(&key) {
+                continue;
+            }
+            let base = clean_label(&key);
+            let mut name = base.clone();
+            for n in 2.. {
+                if taken.insert(name.clone()) {
+                    break;
+                }
+                name = format!("{base}-{n}");
+            }
+            self.labels.insert(key, name);
+        }
+    }
+
+    /// Get the Typst name of the label of a key, as given by
+    /// [`Self::collect_labels`]
+    pub(crate) fn label_name(&self, key: &str) -> String {
+        match self.labels.get(key.trim()) {
+            Some(name) => name.clone(),
+            None => clean_label(key),
+        }
+    }
+
+    /// Get the range of a syntax element in the input text
+    fn input_range(&self, elem: &LatexSyntaxElem) -> Range<usize> {
+        let mut range = self.spans.to_input_range(elem.text_range());
+        let Some(node) = elem.as_node() else {
+            return range;
+        };
+        // `\begin{` and the closing `}` of `\end{..}` are not kept in the syntax tree
+        if let Some(env) = EnvItem::cast(node.clone()) {
+            if let Some(start) = self.input[..range.start].rfind("\\begin") {
+                range.start = start;
+            }
+            if env.end().is_some() {
+                if let Some(end) = self.input[range.end..].find('}') {
+                    range.end += end + 1;
+                }
+            }
+        } else {
+            // an environment may start or end the node
+            if let Some(first) = node.first_child() {
+                range.start = range.start.min(self.input_range(&first.into()).start);
+            }
+            if let Some(last) = node.last_child() {
+                range.end = range.end.max(self.input_range(&last.into()).end);
+            }
+        }
+        range
+    }
+
+    /// Write the Typst name of a command or environment, e.g. `#emph` or
+    /// `pmatrix`, returning the leading arguments to add to its call
+    pub(crate) fn write_alias(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        name: &str,
+    ) -> Result<Option<&'static str>, Error> {
+        self.write_name(f, name, false)
+    }
+
+    /// Write the Typst name of a function called with the arguments written
+    /// after it, like [`Self::write_alias`]
+    pub(crate) fn write_call_alias(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        name: &str,
+    ) -> Result<Option<&'static str>, Error> {
+        self.write_name(f, name, true)
+    }
+
+    fn write_name(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        name: &str,
+        called: bool,
+    ) -> Result<Option<&'static str>, Error> {
+        let (hash, bare) = match name.strip_prefix('#') {
+            Some(bare) => ("#", bare),
+            None => ("", name),
+        };
+        // names without `#` are plain text in text mode
+        let evaluated = !hash.is_empty() || matches!(self.mode, LaTeXMode::Math);
+        // the module of a Typst builtin written in math
+        let qualified = self.options.qualified && hash.is_empty() && evaluated;
+        let module = |native: &str| qualified.then(|| math_module(native, called)).flatten();
+        if evaluated {
+            self.aliases.insert(bare.to_owned());
+        }
+        let Some((bare, item)) = scope_item(bare).filter(|_| evaluated) else {
+            write!(f, "{}{name}", module(name).unwrap_or_default())?;
+            return Ok(None);
+        };
+        if !matches!(self.options.target, Target::Standalone) {
+            match self.options.qualified {
+                true => write!(f, "{hash}mitex.{bare}")?,
+                false => f.write_str(name)?,
+            }
+            return Ok(None);
+        }
+        match item {
+            ScopeItem::Native => write!(f, "{}{name}", module(name).unwrap_or_default())?,
+            ScopeItem::Helper(..) => f.write_str(name)?,
+            ScopeItem::Rename(native) => {
+                write!(f, "{hash}{}{native}", module(native).unwrap_or_default())?
+            }
+            ScopeItem::Call(native, args) => {
+                write!(f, "{hash}{}{native}", module(native).unwrap_or_default())?;
+                return Ok(Some(args));
+            }
+            ScopeItem::Symbol(code) => write!(f, "{code} ")?,
+        }
+        Ok(None)
+    }
+
+    /// Write `,` or `;` after a cell of a matrix
+    fn write_cell_separator(&mut self, f: &mut fmt::Formatter<'_>, sep: char) -> Result<(), Error> {
+        // the readable output only marks empty cells by `zws`
+        if !self.options.readable || self.written.get() == self.cell_start {
+            self.write_alias(f, "zws")?;
+            f.write_char(' ')?;
+        }
+        self.write_cell_end(f)?;
+        f.write_char(sep)?;
+        self.column = match sep {
+            ';' => 0,
+            _ => self.column + 1,
+        };
+        self.cell_start = self.written.get();
+        self.write_cell_begin(f)
+    }
+
+    /// Write the alignment point before a left-aligned cell of a matrix
+    fn write_cell_begin(&mut self, f: &mut fmt::Formatter<'_>) -> Result<(), Error> {
+        if self.cell_aligns.get(self.column) == Some(&"left") {
+            f.write_char('&')?;
+        }
+        Ok(())
+    }
+
+    /// Write the alignment point after a right-aligned cell of a matrix
+    fn write_cell_end(&mut self, f: &mut fmt::Formatter<'_>) -> Result<(), Error> {
+        if self.cell_aligns.get(self.column) == Some(&"right") {
+            f.write_str("& ")?;
+        }
+        Ok(())
+    }
+
+    /// Write the original LaTeX of a failing element as a red raw box
+    fn write_placeholder(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        range: Range<usize>,
+    ) -> Result<(), Error> {
+        f.write_str("#text(fill: red, raw(\"")?;
+        write_string(f, &self.input[range])?;
+        f.write_str("\"));")?;
+        Ok(())
+    }
+
+    fn convert_elem(
         &mut self,
         f: &mut fmt::Formatter<'_>,
         elem: LatexSyntaxElem,
         spec: &CommandSpec,
-    ) -> Result<(), ConvertError> {
+    ) -> Result<(), Error> {
         use LatexSyntaxKind::*;
 
+        if self
+            .skip
+            .is_some_and(|skip| skip.contains_range(elem.text_range()))
+        {
+            return Ok(());
+        }
+
         match elem.kind() {
             TokenWhiteSpace => {}
             _ => {
@@ -138,10 +920,17 @@ impl Converter {
             }
         }
         match elem.kind() {
-            TokenError => Err(match elem {
-                LatexSyntaxElem::Node(node) => format!("error unexpected: {:?}", node.text()),
-                LatexSyntaxElem::Token(token) => format!("error unexpected: {:?}", token.text()),
-            })?,
+            TokenError => Err(ConvertError::with_name(
+                ConvertErrorKind::UnexpectedToken,
+                tree_range(elem.text_range()),
+                match &elem {
+                    LatexSyntaxElem::Node(node) => node.text().to_string(),
+                    LatexSyntaxElem::Token(token) => token.text().to_owned(),
+                },
+            ))?,
+            ScopeRoot if matches!(self.mode, LaTeXMode::Text) => {
+                self.convert_root(f, elem.as_node().unwrap(), spec)?;
+            }
             ItemLR | ClauseArgument | ScopeRoot | ItemText | ItemBracket | ItemParen => {
                 for child in elem.as_node().unwrap().children_with_tokens() {
                     self.convert(f, child, spec)?;
@@ -159,19 +948,29 @@ impl Converter {
             ItemAttachComponent => {
                 self.convert_attach_component(f, elem, spec)?;
             }
-            ClauseCommandName => Err("command name outside of command".to_owned())?,
-            ItemBegin | ItemEnd => Err("clauses outside of environment".to_owned())?,
+            ClauseCommandName => Err(ConvertError::new(
+                ConvertErrorKind::CommandNameOutsideCommand,
+                tree_range(elem.text_range()),
+            ))?,
+            ItemBegin | ItemEnd => Err(ConvertError::new(
+                ConvertErrorKind::ClauseOutsideEnvironment,
+                tree_range(elem.text_range()),
+            ))?,
             TokenWord => {
                 if matches!(self.mode, LaTeXMode::Math) {
                     // break up words into individual characters and add a space
-                    let text = elem.as_token().unwrap().text().to_string();
-                    for prev in text.chars() {
-                        f.write_char(prev)?;
-                        f.write_char(' ')?;
+                    let text = elem.as_token().unwrap().text();
+                    if self.options.readable {
+                        write_math_word(f, text)?;
+                    } else {
+                        for prev in text.chars() {
+                            f.write_char(prev)?;
+                            f.write_char(' ')?;
+                        }
                     }
                 } else {
-                    // write the word directly in text mode
-                    f.write_str(elem.as_token().unwrap().text())?;
+                    let text = elem.as_token().unwrap().text();
+                    write_markup(f, &ligatures(text), self.line_start.get())?;
                 }
             }
             // do nothing
@@ -184,19 +983,39 @@ impl Converter {
                     self.skip_next_space = false;
                     return Ok(());
                 }
+                let cell_empty = self.written.get() == self.cell_start;
                 write!(f, "{}", elem.as_token().unwrap().text())?;
+                if cell_empty {
+                    self.cell_start = self.written.get();
+                }
             }
             TokenLineBreak => {
+                let cell_empty = self.written.get() == self.cell_start;
                 write!(f, "{}", elem.as_token().unwrap().text())?;
                 // indent for itemize and enumerate
                 for _ in 0..self.indent {
                     f.write_char(' ')?;
                 }
+                if cell_empty {
+                    self.cell_start = self.written.get();
+                }
                 self.skip_next_space = true;
             }
             // escapes
             TokenApostrophe => {
-                f.write_char('\'')?;
+                if matches!(self.mode, LaTeXMode::Math) {
+                    f.write_char('\'')?;
+                    return Ok(());
+                }
+                // `''` is a closing double quote in text
+                let next = elem.as_token().unwrap().next_token();
+                match next.filter(|next| next.kind() == TokenApostrophe) {
+                    Some(next) => {
+                        self.skip = Some(next.text_range());
+                        f.write_char('”')?;
+                    }
+                    None => f.write_char('’')?,
+                }
             }
             TokenComma => {
                 f.write_str("\\,")?;
@@ -222,6 +1041,11 @@ impl Converter {
             TokenDitto => {
                 f.write_str("\\\"")?;
             }
+            // raw text outside of `\verb` and verbatim environments
+            TokenVerbatim => {
+                let markup = matches!(self.mode, LaTeXMode::Text);
+                write_raw(f, elem.as_token().unwrap().text(), None, false, markup)?;
+            }
             TokenSemicolon => {
                 f.write_str("\\;")?;
             }
@@ -239,21 +1063,46 @@ impl Converter {
             }
             TokenTilde => {
                 if matches!(self.mode, LaTeXMode::Math) {
-                    f.write_str("space.nobreak ")?;
+                    self.write_alias(f, "space.nobreak")?;
+                    f.write_char(' ')?;
                 } else {
                     f.write_str("\\~")?;
                 }
             }
             TokenAmpersand => match self.env {
-                LaTeXEnv::Matrix => f.write_str("zws ,")?,
+                LaTeXEnv::Matrix => self.write_cell_separator(f, ',')?,
+                LaTeXEnv::Math => {
+                    self.column += 1;
+                    // space out the column pairs, e.g. the `&` of `a &= b & c`
+                    if let Some(gap) = self.column_gap.filter(|_| matches!(self.column % 2, 0)) {
+                        self.write_alias(f, gap)?;
+                        f.write_char(' ')?;
+                    }
+                    f.write_str("&")?;
+                }
                 _ => f.write_str("&")?,
             },
-            ItemNewLine => match self.env {
-                LaTeXEnv::Matrix => f.write_str("zws ;")?,
-                LaTeXEnv::Cases => f.write_str(",")?,
-                LaTeXEnv::MathCurlyGroup => {}
-                _ => f.write_str("\\ ")?,
-            },
+            ItemNewLine => {
+                let (gap, end) = newline_tail(&elem);
+                self.skip = Some(TextRange::new(elem.text_range().end(), end));
+                match self.env {
+                    LaTeXEnv::Matrix => self.write_cell_separator(f, ';')?,
+                    LaTeXEnv::Cases => f.write_str(",")?,
+                    LaTeXEnv::MathCurlyGroup => {}
+                    _ => f.write_str("\\ ")?,
+                }
+                self.column = 0;
+                let gap = gap.as_deref().and_then(typst_length);
+                let in_rows = matches!(
+                    self.env,
+                    LaTeXEnv::Math | LaTeXEnv::Matrix | LaTeXEnv::Cases
+                );
+                if let Some(gap) = gap.filter(|gap| in_rows && !gap.starts_with('-')) {
+                    // raise the next row by a strut as tall as a line and the
+                    // spacing
+                    write!(f, "#box(height: 0.7em + {gap}) ")?;
+                }
+            }
             TokenCommandSym => {
                 self.convert_command_sym(f, elem, spec)?;
             }
@@ -264,21 +1113,18 @@ impl Converter {
                 // remove prefix \
                 let name = &name[1..];
 
-                match name {
-                    "item" => {
-                        self.convert_command_item(f)?;
+                let handlers = &self.options.handlers;
+                match handlers.command(name) {
+                    Some(handler) => {
+                        let mut ctx = HandlerContext {
+                            conv: self,
+                            f,
+                            spec,
+                        };
+                        handler.convert(&mut ctx, &cmd).map_err(|e| e.0)?;
                     }
-                    "label" => {
-                        self.convert_command_label(f, &cmd)?;
-                    }
-                    "includegraphics" => {
-                        self.convert_command_includegraphics(f, &cmd)?;
-                    }
-                    "color" | "textcolor" | "colorbox" => {
-                        self.convert_command_color(f, &cmd, spec)?;
-                    }
-                    _ => {
-                        self.convert_normal_command(f, elem, spec)?;
+                    None => {
+                        self.convert_normal_command(f, &cmd, spec, None)?;
                     }
                 }
             }
@@ -293,20 +1139,133 @@ impl Converter {
         Ok(())
     }
 
+    /// Convert a document in text mode. If it has a `document` environment,
+    /// only its body is converted along with the Typst code of the preamble,
+    /// and the metadata of its front matter is set by `#set document`.
+    fn convert_root(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        root: &SyntaxNode,
+        spec: &CommandSpec,
+    ) -> Result<(), Error> {
+        let document = root.children().filter_map(EnvItem::cast).find(|env| {
+            env.name_tok()
+                .is_some_and(|name| name.text().trim() == "document")
+        });
+        // short captions are shown in the outline
+        let short_captions = root.descendants().filter_map(CmdItem::cast).any(|cmd| {
+            cmd.name_tok()
+                .is_some_and(|name| name.text() == "\\caption")
+                && cmd.arguments().count() > 1
+        });
+        self.footnote_texts = root
+            .descendants()
+            .filter_map(CmdItem::cast)
+            .filter(|cmd| {
+                cmd.name_tok()
+                    .is_some_and(|name| name.text() == "\\footnotetext")
+            })
+            .collect();
+        self.graphics_dirs = root
+            .descendants()
+            .filter_map(CmdItem::cast)
+            .filter(|cmd| {
+                cmd.name_tok()
+                    .is_some_and(|name| name.text() == "\\graphicspath")
+            })
+            .filter_map(|cmd| cmd.arguments().last())
+            .flat_map(|arg| graphics_dirs(&argument_text(&arg)))
+            .collect();
+        let Some(document) = document else {
+            self.front.collect(root.children());
+            self.bib.collect(root.children());
+            self.theorems.collect(root.children());
+            if !self.theorems.is_empty() {
+                self.write_theorem_definitions(f)?;
+            }
+            if short_captions {
+                f.write_str(OUTLINE_RULE)?;
+            }
+            for child in root.children_with_tokens() {
+                self.convert(f, child, spec)?;
+            }
+            return Ok(());
+        };
+
+        self.front.collect(root.children());
+        self.front.collect(document.syntax().children());
+        self.bib.collect(root.children());
+        self.bib.collect(document.syntax().children());
+        self.theorems.collect(root.children());
+        self.theorems.collect(document.syntax().children());
+        if !self.front.is_empty() {
+            self.write_document_metadata(f)?;
+        }
+        if !self.theorems.is_empty() {
+            self.write_theorem_definitions(f)?;
+        }
+        if short_captions {
+            f.write_str(OUTLINE_RULE)?;
+        }
+        // the preamble only keeps the code in `\iftypst`
+        for child in root.children_with_tokens() {
+            if child.as_node() == Some(document.syntax()) {
+                break;
+            }
+            if child.kind() == LatexSyntaxKind::ItemTypstCode {
+                self.convert(f, child, spec)?;
+            }
+        }
+        let body = document.syntax().children_with_tokens().filter(|child| {
+            !matches!(
+                child.kind(),
+                LatexSyntaxKind::ItemBegin | LatexSyntaxKind::ItemEnd
+            )
+        });
+        self.convert_elems(f, body.collect(), spec)
+    }
+
+    /// Convert some elements, skipping the spaces around them
+    pub(crate) fn convert_elems(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        elems: Vec<LatexSyntaxElem>,
+        spec: &CommandSpec,
+    ) -> Result<(), Error> {
+        let start = elems
+            .iter()
+            .position(|e| !is_blank(e))
+            .unwrap_or(elems.len());
+        let end = elems
+            .iter()
+            .rposition(|e| !is_blank(e))
+            .map_or(start, |i| i + 1);
+        for elem in elems.into_iter().take(end).skip(start) {
+            self.convert(f, elem, spec)?;
+        }
+        Ok(())
+    }
+
     /// Convert formula like `$x$` or `$$x$$`
     fn convert_formula(
         &mut self,
         f: &mut fmt::Formatter<'_>,
         elem: LatexSyntaxElem,
         spec: &CommandSpec,
-    ) -> Result<(), ConvertError> {
+    ) -> Result<(), Error> {
         let formula = FormulaItem::cast(elem.as_node().unwrap().clone()).unwrap();
         if !formula.is_valid() {
-            Err("formula is not valid".to_owned())?
+            Err(ConvertError::new(
+                ConvertErrorKind::InvalidFormula,
+                tree_range(elem.text_range()),
+            ))?
         }
+        let wrap = self.options.wrap_inline_equations;
         if matches!(self.mode, LaTeXMode::Text) {
-            if formula.is_inline() {
+            if formula.is_inline() && wrap {
                 f.write_str("#math.equation(block: false, $")?;
+            } else if formula.is_inline() {
+                f.write_char('$')?;
             } else {
                 f.write_str("$ ")?;
             }
@@ -317,8 +1276,10 @@ impl Converter {
         }
         self.exit_mode(prev);
         if matches!(self.mode, LaTeXMode::Text) {
-            if formula.is_inline() {
+            if formula.is_inline() && wrap {
                 f.write_str("$);")?;
+            } else if formula.is_inline() {
+                f.write_char('$')?;
             } else {
                 f.write_str(" $")?;
             }
@@ -332,7 +1293,7 @@ impl Converter {
         f: &mut fmt::Formatter<'_>,
         elem: LatexSyntaxElem,
         spec: &CommandSpec,
-    ) -> Result<(), ConvertError> {
+    ) -> Result<(), Error> {
         use LatexSyntaxKind::*;
         // deal with case like `\begin{pmatrix}x{\\}x\end{pmatrix}`
         let mut prev = LaTeXEnv::None;
@@ -356,7 +1317,8 @@ impl Converter {
         if matches!(self.mode, LaTeXMode::Math) {
             if zws {
                 // deal with case like `{}_1^2x_3^4`
-                f.write_str("zws ")?;
+                self.write_alias(f, "zws")?;
+                f.write_char(' ')?;
             }
             if enter_new_env {
                 self.exit_env(prev);
@@ -371,7 +1333,7 @@ impl Converter {
         f: &mut fmt::Formatter<'_>,
         elem: LatexSyntaxElem,
         spec: &CommandSpec,
-    ) -> Result<(), ConvertError> {
+    ) -> Result<(), Error> {
         let name_and_args = elem
             .as_node()
             .unwrap()
@@ -382,7 +1344,8 @@ impl Converter {
         let name = &name[1..];
         let args = name_and_args[1..].to_owned();
         if name == "left" {
-            f.write_str("lr(")?;
+            self.write_call_alias(f, "lr")?;
+            f.write_char('(')?;
         }
         for arg in args {
             match arg {
@@ -409,7 +1372,7 @@ impl Converter {
         f: &mut fmt::Formatter<'_>,
         elem: LatexSyntaxElem,
         spec: &CommandSpec,
-    ) -> Result<(), ConvertError> {
+    ) -> Result<(), Error> {
         if matches!(self.mode, LaTeXMode::Math) {
             // if there is already a base, if not, we need to add zws, like `_1^2`
             let mut based = false;
@@ -424,7 +1387,7 @@ impl Converter {
                         Some(LatexSyntaxKind::TokenUnderscore | LatexSyntaxKind::TokenCaret)
                     ) {
                         if !based {
-                            f.write_str("zws")?;
+                            self.write_alias(f, "zws")?;
                         }
                         write!(f, "{}(", child.as_token().unwrap().text())?;
                         first = false;
@@ -453,26 +1416,44 @@ impl Converter {
         f: &mut fmt::Formatter<'_>,
         elem: LatexSyntaxElem,
         spec: &CommandSpec,
-    ) -> Result<(), ConvertError> {
+    ) -> Result<(), Error> {
         let name = elem.as_token().unwrap().text();
         // remove prefix \
         let name = &name[1..];
         // get cmd_shape and arg_shape from spec
-        let cmd_shape = spec
-            .get_cmd(name)
-            .ok_or_else(|| format!("unknown command: \\{}", name))?;
+        let cmd_shape = spec.get_cmd(name).ok_or_else(|| {
+            ConvertError::with_name(
+                ConvertErrorKind::UnknownCommand,
+                tree_range(elem.text_range()),
+                name,
+            )
+        })?;
+        // characters like `\#` are written as themselves in text mode
+        if matches!(self.mode, LaTeXMode::Text) {
+            if let Some(markup) = control_symbol_markup(name) {
+                f.write_str(markup)?;
+                return Ok(());
+            }
+            if let Some(text) = text_symbol(name) {
+                return self.write_text_symbol(f, name, text);
+            }
+        }
         // typst alias name
         let typst_name = cmd_shape.alias.as_deref().unwrap_or(name);
         // write to output
-        write!(f, "{}", typst_name)?;
+        self.write_alias(f, typst_name)?;
         Ok(())
     }
 
     /// Convert command `\item` for itemize and enumerate
-    fn convert_command_item(&mut self, f: &mut fmt::Formatter<'_>) -> Result<(), ConvertError> {
+    pub(crate) fn convert_command_item(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        cmd: &CmdItem,
+    ) -> Result<(), Error> {
         if matches!(self.env, LaTeXEnv::Itemize | LaTeXEnv::Enumerate) {
             f.write_char('\n')?;
-            for _ in 0..(self.indent - 2) {
+            for _ in 0..(self.indent - self.options.list_indent) {
                 f.write_char(' ')?;
             }
             if matches!(self.env, LaTeXEnv::Itemize) {
@@ -481,121 +1462,387 @@ impl Converter {
                 f.write_str("+ ")?;
             }
         } else {
-            Err("item command outside of itemize or enumerate".to_owned())?;
+            Err(ConvertError::new(
+                ConvertErrorKind::ItemOutsideList,
+                tree_range(cmd.syntax().text_range()),
+            ))?;
         }
         Ok(())
     }
 
     /// Convert command `\label`
-    fn convert_command_label(
+    pub(crate) fn convert_command_label(
         &mut self,
         f: &mut fmt::Formatter<'_>,
         cmd: &CmdItem,
-    ) -> Result<(), ConvertError> {
-        let arg = cmd
-            .arguments()
-            .next()
-            .expect("\\label command must have one argument");
-        // remove { and } then trim
-        let label = arg.text().to_string();
-        let label = &label[1..(label.len() - 1)];
-        let label = label.trim();
+    ) -> Result<(), Error> {
+        let key = label_key(cmd);
+        if matches!(self.options.labels, LabelPolicy::Drop) || key.is_empty() {
+            return Ok(());
+        }
+        let label = self.label_name(&key);
         match self.env {
             LaTeXEnv::None | LaTeXEnv::Itemize | LaTeXEnv::Enumerate => {
                 if matches!(self.mode, LaTeXMode::Text) {
-                    f.write_char('<')?;
-                    f.write_str(label)?;
-                    f.write_char('>')?;
+                    write_label(f, &label)?;
                 }
             }
             _ => {
-                self.label = Some(label.to_string());
+                self.label = Some(label);
             }
         }
         Ok(())
     }
 
-    /// Convert command `\includegraphics[width=0.5\textwidth]{example-image}`
-    fn convert_command_includegraphics(
+    /// Convert reference commands like `\ref{key}` into `@key[]`, or into a
+    /// call of `ref` where the reference syntax does not fit the key
+    pub(crate) fn convert_command_ref(
         &mut self,
         f: &mut fmt::Formatter<'_>,
         cmd: &CmdItem,
-    ) -> Result<(), ConvertError> {
-        let opt_arg = cmd.arguments().find(|arg| {
-            matches!(
-                arg.first_child().unwrap().kind(),
-                LatexSyntaxKind::ItemBracket
-            )
-        });
-        let arg = cmd
-            .arguments()
-            .find(|arg| {
-                matches!(
-                    arg.first_child().unwrap().kind(),
-                    LatexSyntaxKind::ItemCurly
-                )
+        style: RefStyle,
+    ) -> Result<(), Error> {
+        let key = label_key(cmd);
+        // `\cref{a,b}` refers to several labels
+        let keys = match style {
+            RefStyle::Number => vec![key.as_str()],
+            RefStyle::Lower | RefStyle::Upper => key.split(',').map(str::trim).collect(),
+        };
+        let keys = keys
+            .into_iter()
+            .filter(|key| !key.is_empty())
+            .map(|key| self.label_name(key))
+            .collect::<Vec<_>>();
+        let text = matches!(self.mode, LaTeXMode::Text);
+        // a word right after `@key` would continue its name
+        let next = cmd
+            .syntax()
+            .next_sibling_or_token()
+            .and_then(|elem| match elem {
+                LatexSyntaxElem::Node(node) => node.first_token(),
+                LatexSyntaxElem::Token(token) => Some(token),
             })
-            .expect("\\includegraphics command must have one argument");
-        // remove { and } then trim
-        let body = arg.text().to_string();
-        let body = &body[1..(body.len() - 1)];
-        let body = body.trim();
-        f.write_str("#image(")?;
-        // optional arguments
-        if let Some(opt_arg) = opt_arg {
-            let arg_text = opt_arg.text().to_string();
-            let arg_text = &arg_text[1..(arg_text.len() - 1)];
-            let arg_text = arg_text.trim();
-            // example: \includegraphics[width=0.5\textwidth, height=3cm,
-            // angle=45]{example-image} split by comma and convert
-            // to key-value pairs
-            let args = arg_text.split(',').collect::<Vec<_>>();
-            let args = args
-                .iter()
-                .map(|arg| {
-                    let arg = arg.trim();
-                    let arg = arg.split('=').collect::<Vec<_>>();
-                    let key = arg[0].trim();
-                    let value = if arg.len() == 2 { arg[1].trim() } else { "" };
-                    (key, value)
-                })
-                .collect::<Vec<_>>();
-            for (key, value) in args.iter() {
-                if matches!(key, &"width" | &"height") {
-                    f.write_str(key)?;
-                    f.write_char(':')?;
-                    f.write_char(' ')?;
-                    if value.ends_with("\\textwidth") {
-                        let value = value.trim_end_matches("\\textwidth");
-                        f.write_str(value)?;
-                        f.write_str(" * 100%")?;
-                    } else if value.ends_with("\\textheight") {
-                        let value = value.trim_end_matches("\\textheight");
-                        f.write_str(value)?;
-                        f.write_str(" * 100%")?;
-                    } else {
-                        f.write_str(value)?;
+            .filter(|token| token.kind() == LatexSyntaxKind::TokenWord);
+        let runs_on = next.is_some_and(|token| {
+            let rest = token.text().trim_start_matches(['.', ':']);
+            rest.starts_with(is_label_char)
+        });
+        for (index, key) in keys.iter().enumerate() {
+            let last = index + 1 == keys.len();
+            if index > 0 {
+                f.write_str(if last { " and " } else { ", " })?;
+            }
+            // trailing `.` and `:` are not part of the reference syntax
+            let syntax = text && is_plain_label(key) && !key.ends_with(['.', ':']);
+            match style {
+                RefStyle::Number if syntax => write!(f, "@{key}[]")?,
+                RefStyle::Upper if syntax && !(last && runs_on) => write!(f, "@{key}")?,
+                _ => {
+                    f.write_char('#')?;
+                    let lower = matches!(style, RefStyle::Lower);
+                    if lower {
+                        f.write_str("lower(")?;
+                    }
+                    f.write_str("ref(")?;
+                    write_label_value(f, key)?;
+                    if matches!(style, RefStyle::Number) {
+                        f.write_str(", supplement: none")?;
+                    }
+                    f.write_char(')')?;
+                    if lower {
+                        f.write_char(')')?;
+                    }
+                    if text {
+                        f.write_char(';')?;
                     }
-                    f.write_char(',')?;
-                    f.write_char(' ')?;
                 }
             }
         }
+        Ok(())
+    }
+
+    /// Convert sectioning commands like `\section`, moving the labels in
+    /// their titles after the heading
+    pub(crate) fn convert_command_heading(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        cmd: &CmdItem,
+        spec: &CommandSpec,
+    ) -> Result<(), Error> {
+        let outer = self.label.take();
+        let prev = self.enter_env(LaTeXEnv::Heading);
+        let res = self.convert_normal_command(f, cmd, spec, None);
+        self.exit_env(prev);
+        res?;
+        self.write_env_label(f)?;
+        self.label = outer;
+        Ok(())
+    }
+
+    /// Write the text of a special letter or a symbol like `\ss`, skipping
+    /// the space after a command name of letters as TeX does
+    fn write_text_symbol(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        name: &str,
+        text: &str,
+    ) -> Result<(), Error> {
+        write_markup(f, text, self.line_start.get())?;
+        if name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            self.skip_next_space = true;
+        }
+        Ok(())
+    }
+
+    /// Convert accent commands like `\"o` into accented letters in text mode,
+    /// e.g. `ö`, or follow the spec in math mode. The spec gives no argument
+    /// to the accents that are not math accents, like `\^` or `\d`, so in
+    /// text mode their argument is the group or the first letter after them.
+    pub(crate) fn convert_command_accent(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        cmd: &CmdItem,
+        spec: &CommandSpec,
+        mark: char,
+    ) -> Result<(), Error> {
+        if matches!(self.mode, LaTeXMode::Math) {
+            return self.convert_normal_command(f, cmd, spec, None);
+        }
+        let mut arg = cmd.arguments().next();
+        if arg.is_none() {
+            let end = cmd.syntax().text_range().end();
+            // the words of text are in `ItemText` nodes
+            let next = std::iter::successors(cmd.syntax().next_sibling_or_token(), |elem| {
+                elem.next_sibling_or_token()
+            })
+            .flat_map(|elem| match elem {
+                LatexSyntaxElem::Node(node) if node.kind() == LatexSyntaxKind::ItemText => {
+                    node.children_with_tokens().collect()
+                }
+                elem => vec![elem],
+            })
+            .find(|elem| {
+                !matches!(
+                    elem.kind(),
+                    LatexSyntaxKind::TokenWhiteSpace | LatexSyntaxKind::TokenLineBreak
+                )
+            });
+            match next {
+                Some(LatexSyntaxElem::Node(group))
+                    if group.kind() == LatexSyntaxKind::ItemCurly =>
+                {
+                    self.skip = Some(TextRange::new(end, group.text_range().end()));
+                    arg = Some(group);
+                }
+                Some(LatexSyntaxElem::Token(word)) if word.kind() == LatexSyntaxKind::TokenWord => {
+                    self.skip = Some(TextRange::new(end, word.text_range().end()));
+                    let text = word.text();
+                    let len = text.chars().next().map_or(0, char::len_utf8);
+                    let accented = compose(&text[..len], mark) + &ligatures(&text[len..]);
+                    return Ok(write_markup(f, &accented, self.line_start.get())?);
+                }
+                _ => {}
+            }
+        }
+        match arg.as_ref().map_or(Some(String::new()), accent_base) {
+            Some(base) => write_markup(f, &compose(&base, mark), self.line_start.get())?,
+            // the mark follows the converted argument
+            None => {
+                if let Some(arg) = arg {
+                    self.convert(f, rowan::NodeOrToken::Node(arg), spec)?;
+                }
+                f.write_char(mark)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Convert `\url{..}` into `#link("..")`, `\href{..}{text}` into
+    /// `#link("..")[text]` and `\hyperref[key]{text}` into a link to the
+    /// label
+    pub(crate) fn convert_command_link(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        cmd: &CmdItem,
+        spec: &CommandSpec,
+    ) -> Result<(), Error> {
+        let mut args = cmd.arguments();
+        let Some(target) = args.next() else {
+            return Ok(());
+        };
+        f.write_str("#link(")?;
+        if cmd.name_tok().unwrap().text() == "\\hyperref" {
+            let key = argument_text(&target);
+            let key = key.trim_start_matches('[').trim_end_matches(']');
+            write_label_value(f, &self.label_name(key))?;
+        } else {
+            // the URL is raw text, where `\#` and the like of `\href` are
+            // escaped characters
+            let url = target
+                .descendants_with_tokens()
+                .find(|elem| elem.kind() == LatexSyntaxKind::TokenVerbatim)
+                .map_or_else(|| argument_text(&target), |raw| raw.to_string());
+            let url = url.trim();
+            let mut chars = url.chars().peekable();
+            f.write_char('"')?;
+            while let Some(c) = chars.next() {
+                let escaped = chars.next_if(|next| c == '\\' && "#$%&_{}~".contains(*next));
+                let c = escaped.unwrap_or(c);
+                match c {
+                    '"' | '\\' => write!(f, "\\{c}")?,
+                    _ => f.write_char(c)?,
+                }
+            }
+            f.write_char('"')?;
+        }
+        f.write_char(')')?;
+        if let Some(text) = args.next() {
+            f.write_char('[')?;
+            self.convert_elems(f, argument_elems(&text), spec)?;
+            f.write_char(']')?;
+        }
+        if matches!(self.mode, LaTeXMode::Text) {
+            f.write_char(';')?;
+        }
+        Ok(())
+    }
+
+    /// Convert `\footnotemark` into a footnote with the text of the next
+    /// `\footnotetext`, or of the one with the same number, and a
+    /// `\footnotetext` without a mark before it into a footnote
+    pub(crate) fn convert_command_footnote_mark(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        cmd: &CmdItem,
+        spec: &CommandSpec,
+    ) -> Result<(), Error> {
+        let index = match cmd.name_tok().unwrap().text() {
+            "\\footnotetext" => self
+                .footnote_texts
+                .iter()
+                .position(|text| text.syntax() == cmd.syntax()),
+            _ => {
+                // `\footnotemark[2]` and `\footnotetext[2]{..}`
+                let number_of = |cmd: &CmdItem| {
+                    let arg = cmd.arguments().next()?;
+                    let text = argument_text(&arg);
+                    let number = text.strip_prefix('[')?.strip_suffix(']')?;
+                    Some(number.trim().to_owned())
+                };
+                let number = number_of(cmd);
+                self.footnote_texts
+                    .iter()
+                    .position(|text| number.is_none() || number_of(text) == number)
+            }
+        };
+        let Some(index) = index else {
+            return Ok(());
+        };
+        let text = self.footnote_texts.remove(index);
+        f.write_str("#footnote[")?;
+        if let Some(arg) = text.arguments().last() {
+            self.convert_elems(f, argument_elems(&arg), spec)?;
+        }
+        f.write_char(']')?;
+        if matches!(self.mode, LaTeXMode::Text) {
+            f.write_char(';')?;
+        }
+        Ok(())
+    }
+
+    /// Convert command `\includegraphics[width=0.5\textwidth]{example-image}`
+    pub(crate) fn convert_command_includegraphics(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        cmd: &CmdItem,
+    ) -> Result<(), Error> {
+        let is_group = |arg: &SyntaxNode, kind| arg.first_child().is_some_and(|c| c.kind() == kind);
+        let opt_arg = cmd
+            .arguments()
+            .find(|arg| is_group(arg, LatexSyntaxKind::ItemBracket));
+        let Some(arg) = cmd
+            .arguments()
+            .find(|arg| is_group(arg, LatexSyntaxKind::ItemCurly))
+        else {
+            Err(ConvertError::with_name(
+                ConvertErrorKind::MissingArgument,
+                tree_range(cmd.syntax().text_range()),
+                &cmd.name_tok().unwrap().text()[1..],
+            ))?
+        };
+        let name = argument_text(&arg);
+        let path = resolve_image(
+            name.trim(),
+            &self.graphics_dirs,
+            self.options.base_dir.as_deref(),
+        );
+        // example: \includegraphics[width=0.5\textwidth, angle=45]{example-image}
+        let options = match opt_arg {
+            Some(opt_arg) => {
+                let text = opt_arg.text().to_string();
+                GraphicsOptions::parse(text.trim_start_matches('[').trim_end_matches(']'))
+            }
+            None => GraphicsOptions::default(),
+        };
+        let mut close = String::new();
+        // LaTeX rotates counterclockwise and Typst clockwise
+        if let Some(angle) = options.angle {
+            write!(f, "#rotate({}deg, reflow: true)[", -angle)?;
+            close.push(']');
+        }
+        if let Some(scale) = options.scale {
+            write!(f, "#scale({scale}%, reflow: true)[")?;
+            close.push(']');
+        }
+        // the trimmed edges are hidden by clipping, or else overlap the text
+        if let Some([left, bottom, right, top]) = &options.trim {
+            if options.clip {
+                f.write_str("#box(clip: true)[")?;
+                close.push(']');
+            }
+            let neg = |length: &str| match length.strip_prefix('-') {
+                Some(length) => length.to_owned(),
+                None => format!("-{length}"),
+            };
+            write!(
+                f,
+                "#pad(left: {}, bottom: {}, right: {}, top: {})[",
+                neg(left),
+                neg(bottom),
+                neg(right),
+                neg(top)
+            )?;
+            close.push(']');
+        }
+        f.write_str("#image(")?;
+        if let Some(width) = &options.width {
+            write!(f, "width: {width}, ")?;
+        }
+        if let Some(height) = &options.height {
+            write!(f, "height: {height}, ")?;
+        }
+        if let Some(fit) = options.fit() {
+            write!(f, "fit: \"{fit}\", ")?;
+        }
+        if let Some(page) = options.page {
+            write!(f, "page: {page}, ")?;
+        }
         // image path
         f.write_char('"')?;
-        f.write_str(body)?;
+        write_string(f, &path)?;
         f.write_char('"')?;
         f.write_char(')')?;
+        f.write_str(&close)?;
         Ok(())
     }
 
     // \color, \textcolor, \colorbox commands
-    fn convert_command_color(
+    pub(crate) fn convert_command_color(
         &mut self,
         f: &mut fmt::Formatter<'_>,
         cmd: &CmdItem,
         spec: &CommandSpec,
-    ) -> Result<(), ConvertError> {
+    ) -> Result<(), Error> {
         let cmd_name = cmd
             .name_tok()
             .map(|t| t.text().to_string())
@@ -674,11 +1921,16 @@ impl Converter {
             }
         };
 
-        write!(f, "{}(", alias)?;
+        self.write_call_alias(f, alias)?;
+        f.write_char('(')?;
 
         // Arg 1: Model
         match model {
-            Some(m) => write!(f, "[{}], ", m)?,
+            Some(m) => {
+                f.write_char('[')?;
+                write_markup(f, &m, true)?;
+                f.write_str("], ")?;
+            }
             None => f.write_str("none, ")?,
         }
 
@@ -713,45 +1965,59 @@ impl Converter {
         Ok(())
     }
 
-    /// Convert normal command
-    fn convert_normal_command(
+    /// Convert normal command, optionally overriding its typst name
+    pub(crate) fn convert_normal_command(
         &mut self,
         f: &mut fmt::Formatter<'_>,
-        elem: LatexSyntaxElem,
+        cmd: &CmdItem,
         spec: &CommandSpec,
-    ) -> Result<(), ConvertError> {
-        let cmd = CmdItem::cast(elem.as_node().unwrap().clone()).unwrap();
-        let name = cmd.name_tok().unwrap();
-        let name = name.text();
+        typst_name: Option<&str>,
+    ) -> Result<(), Error> {
+        let name_tok = cmd.name_tok().unwrap();
+        let name = name_tok.text();
         // remove prefix \
         let name = &name[1..];
-        let args = elem
-            .as_node()
-            .unwrap()
+        let args = cmd
+            .syntax()
             .children_with_tokens()
             .filter(|node| node.kind() != LatexSyntaxKind::ClauseCommandName)
             .collect::<Vec<_>>();
 
         // get cmd_shape and arg_shape from spec
-        let cmd_shape = spec
-            .get_cmd(name)
-            .ok_or_else(|| format!("unknown command: \\{}", name))?;
+        let cmd_shape = spec.get_cmd(name).ok_or_else(|| {
+            ConvertError::with_name(
+                ConvertErrorKind::UnknownCommand,
+                tree_range(name_tok.text_range()),
+                name,
+            )
+        })?;
         let arg_shape = &cmd_shape.args;
 
-        // typst alias name
-        let mut typst_name = cmd_shape.alias.as_deref().unwrap_or(name);
-
-        // hack for textbf and textit
+        // characters like `\#` are written as themselves in text mode
         if matches!(self.mode, LaTeXMode::Text) {
-            if name == "textbf" {
-                typst_name = "#strong";
-            } else if name == "textit" {
-                typst_name = "#emph";
+            if let Some(markup) = control_symbol_markup(name) {
+                f.write_str(markup)?;
+                return Ok(());
+            }
+            if let Some(text) = text_symbol(name) {
+                return self.write_text_symbol(f, name, text);
             }
         }
 
-        // normal command
-        write!(f, "{}", typst_name)?;
+        // typst alias name
+        let typst_name = typst_name.unwrap_or(cmd_shape.alias.as_deref().unwrap_or(name));
+
+        // normal command, called unless it takes no arguments
+        let called = !matches!(
+            arg_shape,
+            ArgShape::Right {
+                pattern: ArgPattern::None
+            }
+        );
+        let call_args = match called {
+            true => self.write_call_alias(f, typst_name)?,
+            false => self.write_alias(f, typst_name)?,
+        };
 
         // hack for \substack{abc \\ bcd}
         let mut prev = LaTeXEnv::None;
@@ -768,7 +2034,7 @@ impl Converter {
             pattern: ArgPattern::Greedy,
         } = arg_shape
         {
-            f.write_char('(')?;
+            write_call_open(f, call_args)?;
             // there is only one arg in greedy
             let args = args
                 .first()
@@ -790,7 +2056,7 @@ impl Converter {
 
             f.write_char(')')?;
         } else if matches!(self.mode, LaTeXMode::Math) && !typst_name.starts_with('#') {
-            f.write_char('(')?;
+            write_call_open(f, call_args)?;
 
             let mut cnt = 0;
             let args_len = args.len();
@@ -833,19 +2099,51 @@ impl Converter {
         f: &mut fmt::Formatter<'_>,
         elem: LatexSyntaxElem,
         spec: &CommandSpec,
-    ) -> Result<(), ConvertError> {
+    ) -> Result<(), Error> {
         let env = EnvItem::cast(elem.as_node().unwrap().clone()).unwrap();
-        let name = env
-            .name_tok()
-            .expect("environment name must be non-empty")
-            .text()
-            .to_string();
+        let name_tok = env.name_tok().expect("environment name must be non-empty");
+        let name = name_tok.text().to_string();
+        let name = name.trim();
+
+        let handlers = &self.options.handlers;
+        if let Some(handler) = handlers.env(name) {
+            let mut ctx = HandlerContext {
+                conv: self,
+                f,
+                spec,
+            };
+            handler.convert(&mut ctx, &env).map_err(|e| e.0)?;
+            self.write_env_label(f)?;
+            return Ok(());
+        }
+        if self.theorems.get(name).is_some() {
+            self.convert_env_theorem(f, &env, spec, Some(name))?;
+            return self.write_env_label(f);
+        }
+
+        self.convert_normal_env(f, &env, spec)
+    }
+
+    /// Convert environments by the spec
+    pub(crate) fn convert_normal_env(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        env: &EnvItem,
+        spec: &CommandSpec,
+    ) -> Result<(), Error> {
+        let elem = env.syntax();
+        let name_tok = env.name_tok().expect("environment name must be non-empty");
+        let name = name_tok.text().to_string();
         let name = name.trim();
         let args = env.arguments();
 
-        let env_shape = spec
-            .get_env(name)
-            .ok_or_else(|| format!("unknown environment: \\{}", name))?;
+        let env_shape = spec.get_env(name).ok_or_else(|| {
+            ConvertError::with_name(
+                ConvertErrorKind::UnknownEnvironment,
+                tree_range(name_tok.text_range()),
+                name,
+            )
+        })?;
         let typst_name = env_shape.alias.as_deref().unwrap_or(name);
 
         let env_kind = match env_shape.ctx_feature {
@@ -863,7 +2161,7 @@ impl Converter {
         if matches!(env_kind, LaTeXEnv::Itemize | LaTeXEnv::Enumerate) {
             let prev = self.enter_env(env_kind);
 
-            for child in elem.as_node().unwrap().children_with_tokens() {
+            for child in elem.children_with_tokens() {
                 if matches!(
                     child.kind(),
                     LatexSyntaxKind::ItemBegin | LatexSyntaxKind::ItemEnd
@@ -896,22 +2194,23 @@ impl Converter {
             let with_dollar = matches!(self.mode, LaTeXMode::Text) && is_math_env;
             let prev = self.enter_env(env_kind);
             let prev_mode = self.enter_mode(LaTeXMode::Math);
+            let prev_aligns = std::mem::take(&mut self.cell_aligns);
             if with_dollar {
                 f.write_str("$ ")?;
             }
 
             // environment name
-            f.write_str(typst_name)?;
-
-            f.write_char('(')?;
+            let call_args = self.write_call_alias(f, typst_name)?;
+            write_call_open(f, call_args)?;
             // named args
             for (index, arg) in args.enumerate() {
                 f.write_str(format!("arg{}: ", index).as_str())?;
                 self.convert(f, rowan::NodeOrToken::Node(arg), spec)?;
                 f.write_char(',')?;
             }
+            self.cell_start = self.written.get();
 
-            for child in elem.as_node().unwrap().children_with_tokens() {
+            for child in elem.children_with_tokens() {
                 // skip \begin and \end commands
                 if matches!(
                     child.kind(),
@@ -925,6 +2224,7 @@ impl Converter {
             f.write_char(')')?;
 
             self.exit_env(prev);
+            self.cell_aligns = prev_aligns;
 
             if with_dollar {
                 f.write_str(" $")?;
@@ -940,18 +2240,17 @@ impl Converter {
             // environment name
             match env_kind {
                 LaTeXEnv::Figure => {
-                    self.convert_env_figure(f, elem, spec, env_kind, typst_name)?;
+                    self.convert_env_figure(f, env, spec, env_kind, typst_name)?;
                 }
                 LaTeXEnv::Table => {
-                    self.convert_env_table(f, elem, spec, env_kind, typst_name)?;
+                    self.convert_env_table(f, env, spec, env_kind, typst_name)?;
                 }
                 _ => {
                     // normal environment
                     let prev = self.enter_env(env_kind);
-                    f.write_char('#')?;
-                    f.write_str(typst_name)?;
+                    self.write_alias(f, &format!("#{typst_name}"))?;
                     f.write_char('[')?;
-                    for child in elem.as_node().unwrap().children_with_tokens() {
+                    for child in elem.children_with_tokens() {
                         // skip \begin and \end commands
                         if matches!(
                             child.kind(),
@@ -967,143 +2266,862 @@ impl Converter {
             }
         }
 
+        self.write_env_label(f)
+    }
+
+    /// Write the label collected in an environment, after the outermost
+    /// environment holding it
+    fn write_env_label(&mut self, f: &mut fmt::Formatter<'_>) -> Result<(), Error> {
+        let outermost = matches!(
+            self.env,
+            LaTeXEnv::None | LaTeXEnv::Itemize | LaTeXEnv::Enumerate
+        );
         // handle label, only add <label> for text mode
-        if matches!(self.mode, LaTeXMode::Text) {
+        if outermost && matches!(self.mode, LaTeXMode::Text) {
             if let Some(label) = self.label.take() {
-                f.write_char('<')?;
-                f.write_str(label.as_str())?;
-                f.write_char('>')?;
-                self.label = None;
+                write_label(f, &label)?;
             }
         }
 
         Ok(())
     }
 
-    /// Convert figure environment
-    fn convert_env_figure(
+    /// Convert equation environments like `align` in text mode into display
+    /// equations, numbered unless the environment is starred.
+    ///
+    /// The rows of `align` and `gather` are numbered one by one if more than
+    /// one row has a number or a label, so they are written as an equation
+    /// each, laid out in a grid keeping the cells of `align` aligned. `\tag`
+    /// replaces the number of a row, `\nonumber` drops it and a `\label` is
+    /// attached to the equation of its row. `\intertext` splits the rows by
+    /// a paragraph.
+    pub(crate) fn convert_env_equation(
         &mut self,
         f: &mut fmt::Formatter<'_>,
-        elem: LatexSyntaxElem,
+        env: &EnvItem,
+        spec: &CommandSpec,
+        numbered: bool,
+        by_row: bool,
+    ) -> Result<(), Error> {
+        // split the rows at `\intertext`
+        let mut blocks = vec![];
+        let mut elems = vec![];
+        for child in env.syntax().children_with_tokens() {
+            match child.kind() {
+                LatexSyntaxKind::ItemBegin | LatexSyntaxKind::ItemEnd => {}
+                LatexSyntaxKind::ItemCmd if by_row => {
+                    let cmd = CmdItem::cast(child.as_node().unwrap().clone()).unwrap();
+                    let name = cmd.name_tok().unwrap();
+                    if matches!(name.text(), "\\intertext" | "\\shortintertext") {
+                        blocks.push((
+                            equation_rows(std::mem::take(&mut elems), numbered),
+                            Some(cmd),
+                        ));
+                    } else {
+                        elems.push(child);
+                    }
+                }
+                _ => elems.push(child),
+            }
+        }
+        let rows = match by_row {
+            true => equation_rows(elems, numbered),
+            false => vec![EquationRow::new(elems)],
+        };
+        blocks.push((rows, None));
+        if blocks
+            .iter()
+            .all(|(rows, text)| rows.is_empty() && text.is_none())
+        {
+            blocks[0].0.push(EquationRow::new(vec![]));
+        }
+
+        let outer_label = self.label.take();
+        let prev_env = self.enter_env(LaTeXEnv::Math);
+        // the rows without a `\tag` or `\nonumber` take the numbering of the
+        // environment, which is scoped by the content block
+        let numbering = if numbered { "\"(1)\"" } else { "none" };
+        write!(f, "#[#set math.equation(numbering: {numbering}); ")?;
+        let mut first = true;
+        for (rows, intertext) in blocks.iter() {
+            // the rows of `gather` are centered on their own, while the cells
+            // of `align` are aligned in a grid
+            let aligned = rows.len() > 1
+                && rows.iter().any(|row| {
+                    row.elems
+                        .iter()
+                        .any(|e| e.kind() == LatexSyntaxKind::TokenAmpersand)
+                });
+            if aligned {
+                if !std::mem::take(&mut first) {
+                    f.write_char('\n')?;
+                }
+                self.write_equation_grid(f, rows, numbered, spec)?;
+            }
+            for row in rows.iter().filter(|_| !aligned) {
+                if !std::mem::take(&mut first) {
+                    f.write_char('\n')?;
+                }
+                let custom = self.write_row_numbering(f, row, numbered, spec)?;
+
+                let prev_mode = self.enter_mode(LaTeXMode::Math);
+                self.column = 0;
+                f.write_str("$ ")?;
+                for child in row.elems.iter() {
+                    self.convert(f, child.clone(), spec)?;
+                }
+                f.write_str(" $")?;
+                self.exit_mode(prev_mode);
+                if custom {
+                    f.write_char(')')?;
+                }
+                self.write_row_number_end(f, row)?;
+                if let Some(gap) = row.gap.as_deref().and_then(typst_length) {
+                    write!(f, " #v({gap});")?;
+                }
+            }
+            if let Some(intertext) = intertext {
+                if !std::mem::take(&mut first) {
+                    f.write_char('\n')?;
+                }
+                let prev_env = self.enter_env(LaTeXEnv::None);
+                let prev_mode = self.enter_mode(LaTeXMode::Text);
+                for arg in intertext.arguments() {
+                    self.convert(f, rowan::NodeOrToken::Node(arg), spec)?;
+                }
+                self.exit_mode(prev_mode);
+                self.exit_env(prev_env);
+            }
+        }
+        f.write_char(']')?;
+        self.exit_env(prev_env);
+        self.label = outer_label;
+
+        Ok(())
+    }
+
+    /// Open the equation of a row with its own `numbering` if it differs
+    /// from the one of the environment, e.g. for a `\tag` or `\nonumber`,
+    /// and give back whether the equation is to be closed by `)`
+    fn write_row_numbering(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        row: &EquationRow,
+        numbered: bool,
+        spec: &CommandSpec,
+    ) -> Result<bool, Error> {
+        if row.tag.is_none() && row.numbered(numbered) == numbered {
+            return Ok(false);
+        }
+        f.write_str("#math.equation(block: true, numbering: ")?;
+        match &row.tag {
+            Some((tag, starred)) => {
+                f.write_str("_ => [")?;
+                if !starred {
+                    f.write_char('(')?;
+                }
+                let prev_mode = self.enter_mode(LaTeXMode::Text);
+                for arg in tag.arguments() {
+                    self.convert(f, rowan::NodeOrToken::Node(arg), spec)?;
+                }
+                self.exit_mode(prev_mode);
+                if !starred {
+                    f.write_char(')')?;
+                }
+                f.write_char(']')?;
+            }
+            None => f.write_str("none")?,
+        }
+        f.write_str(", ")?;
+        Ok(true)
+    }
+
+    /// Write the label of the equation of a row, and give back the number a
+    /// `\tag` took from the counter
+    fn write_row_number_end(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        row: &EquationRow,
+    ) -> Result<(), Error> {
+        if let Some(label) = self.label.take() {
+            write_label(f, &label)?;
+        }
+        if row.tag.is_some() {
+            f.write_str(" #counter(math.equation).update(n => n - 1);")?;
+        }
+        Ok(())
+    }
+
+    /// Write the rows of an alignment numbered one by one as a grid, with a
+    /// column per cell of the alignment to keep the cells of the rows
+    /// aligned, and a last column for the numbers. The last cell of a
+    /// numbered row is the equation of the row, spanning the column of the
+    /// numbers so that its number is placed there.
+    fn write_equation_grid(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        rows: &[EquationRow],
+        numbered: bool,
+        spec: &CommandSpec,
+    ) -> Result<(), Error> {
+        // split the rows at `&`
+        let rows = rows
+            .iter()
+            .map(|row| {
+                let cells = row
+                    .elems
+                    .split(|e| e.kind() == LatexSyntaxKind::TokenAmpersand)
+                    .collect::<Vec<_>>();
+                (row, cells)
+            })
+            .collect::<Vec<_>>();
+        let columns = rows.iter().map(|(_, cells)| cells.len()).max().unwrap_or(1);
+        // the cells are paired as right and left aligned, like `a &= b`, and
+        // a single column is centered, like the rows of `gather`
+        let align = |column: usize| match column % 2 {
+            _ if columns == 1 => "center",
+            0 => "right",
+            _ => "left",
+        };
+
+        // the equations start at the left of their cells like the other
+        // cells of their columns
+        f.write_str("#[#show math.equation: set align(left); #grid(columns: (1fr, ")?;
+        for _ in 0..columns {
+            f.write_str("auto, ")?;
+        }
+        f.write_str("1fr), align: (horizon, ")?;
+        for column in 0..columns {
+            write!(f, "{} + horizon, ", align(column))?;
+        }
+        f.write_str("horizon)")?;
+        // space out the column pairs like `quad`
+        if self.column_gap.is_some() && columns > 2 {
+            f.write_str(", column-gutter: (0pt")?;
+            for column in 0..columns {
+                let gap = if column % 2 == 1 && column + 1 < columns {
+                    "1em"
+                } else {
+                    "0pt"
+                };
+                write!(f, ", {gap}")?;
+            }
+            f.write_char(')')?;
+        }
+        // the spacing below each row, e.g. `4pt` of `\\[4pt]`, is added to the
+        // leading of the lines
+        let gutters = rows[..rows.len() - 1]
+            .iter()
+            .map(|(row, _)| match row.gap.as_deref().and_then(typst_length) {
+                Some(gap) => format!("0.65em + {gap}"),
+                None => "0.65em".to_owned(),
+            })
+            .collect::<Vec<_>>();
+        write!(f, ", row-gutter: ({}", gutters.join(", "))?;
+        if gutters.len() == 1 {
+            f.write_char(',')?;
+        }
+        f.write_str("),\n")?;
+
+        for (row, cells) in rows.iter() {
+            f.write_str("  [], ")?;
+            let row_numbered = row.numbered(numbered);
+            // the last cell of a numbered row holds its equation
+            let last = match row_numbered {
+                true => cells.iter().rposition(|cell| !cell.iter().all(is_blank)),
+                false => None,
+            };
+            let last = last.unwrap_or(cells.len() - 1);
+            for column in 0..columns {
+                let cell = cells.get(column).copied().unwrap_or_default();
+                if row_numbered && column == last {
+                    write!(f, "grid.cell(colspan: {})[", columns - column + 1)?;
+                    let custom = self.write_row_numbering(f, row, numbered, spec)?;
+                    let prev_mode = self.enter_mode(LaTeXMode::Math);
+                    f.write_str("$ ")?;
+                    if align(column) == "left" {
+                        self.write_alias(f, "zws")?;
+                        f.write_char(' ')?;
+                    }
+                    for child in cell.iter() {
+                        self.convert(f, child.clone(), spec)?;
+                    }
+                    f.write_str(" $")?;
+                    self.exit_mode(prev_mode);
+                    if custom {
+                        f.write_char(')')?;
+                    }
+                    self.write_row_number_end(f, row)?;
+                    f.write_str("],\n")?;
+                    break;
+                }
+                if cell.iter().all(is_blank) {
+                    f.write_str("[], ")?;
+                    continue;
+                }
+                let prev_mode = self.enter_mode(LaTeXMode::Math);
+                f.write_char('$')?;
+                self.write_call_alias(f, "display")?;
+                f.write_char('(')?;
+                // keep the spacing of a relation like `=` after the `&`
+                if align(column) == "left" {
+                    self.write_alias(f, "zws")?;
+                    f.write_char(' ')?;
+                }
+                for child in cell.iter() {
+                    self.convert(f, child.clone(), spec)?;
+                }
+                f.write_str(")$, ")?;
+                self.exit_mode(prev_mode);
+            }
+            if !row_numbered {
+                self.label = None;
+                f.write_str("[],\n")?;
+            }
+        }
+        f.write_str(")]")?;
+        Ok(())
+    }
+
+    /// Convert figure environments like `figure`, `table*` and `subfigure`
+    /// into `figure` of the kind of their content. The option like `[tb]` is
+    /// the placement of the figure, several captions split it into several
+    /// figures, and subfigures are figures boxed in their widths, numbered
+    /// `(a)`, `(b)` and so on in each figure. Typst does not wrap text around
+    /// figures, so `wrapfigure` is only approximated by a figure boxed in its
+    /// width and floating to the top of the page at its side, with the text
+    /// running above or below it instead of beside it.
+    pub(crate) fn convert_env_figure(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        env: &EnvItem,
         spec: &CommandSpec,
         env_kind: LaTeXEnv,
         typst_name: &str,
-    ) -> Result<(), ConvertError> {
-        fn is_named_arg(child: &LatexSyntaxElem) -> bool {
-            matches!(
-                child.kind(),
-                LatexSyntaxKind::ItemCmd
-                    if matches!(
-                        CmdItem::cast(child.as_node().unwrap().clone())
-                            .unwrap()
-                            .name_tok()
-                            .unwrap()
-                            .text(),
-                        "\\caption" | "\\centering"
-                    )
-            )
+    ) -> Result<(), Error> {
+        let name = env.name_tok().unwrap().text().trim().to_owned();
+        let base = name.trim_end_matches('*');
+        let sub = matches!(base, "subfigure" | "subtable");
+        let wrap = matches!(base, "wrapfigure" | "wraptable");
+        let kind = match base {
+            "table" | "wraptable" => "table",
+            "subfigure" => "\"subfigure\"",
+            "subtable" => "\"subtable\"",
+            _ => "image",
+        };
+
+        // options like `[tb]` and arguments like `{0.5\textwidth}`
+        let (mut options, mut braced) = (vec![], vec![]);
+        for arg in env.arguments() {
+            let text = argument_text(&arg);
+            match text.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+                Some(option) => options.push(option.trim().to_owned()),
+                None => braced.push(text),
+            }
         }
-        // collect named args
-        let mut caption = None;
-        for child in elem.as_node().unwrap().children_with_tokens() {
-            if is_named_arg(&child) {
-                let cmd = CmdItem::cast(child.as_node().unwrap().clone()).unwrap();
-                let name = cmd.name_tok().unwrap();
-                let name = name.text();
-                // remove prefix \
-                let name = &name[1..];
-                if name == "caption" {
-                    let arg = cmd
-                        .arguments()
-                        .next()
-                        .expect("\\caption command must have one argument");
-                    caption = Some(arg);
+        // `figure*` spans the columns of the page
+        let span = name.ends_with('*') && !sub;
+        let placement = match options.first() {
+            Some(option) if !sub && !wrap => figure_placement(option),
+            _ => None,
+        };
+        let placement = placement.or(span.then_some("auto"));
+
+        // split the content at the captions, which are above or below it, and
+        // the text into tokens to trim the spaces around each part
+        let mut children = env
+            .syntax()
+            .children_with_tokens()
+            .flat_map(|child| match child.kind() {
+                LatexSyntaxKind::ItemText => {
+                    child.as_node().unwrap().children_with_tokens().collect()
+                }
+                LatexSyntaxKind::ItemBegin | LatexSyntaxKind::ItemEnd => vec![],
+                _ => vec![child],
+            })
+            .collect::<Vec<_>>();
+        let command = |child: &LatexSyntaxElem| {
+            let cmd = CmdItem::cast(child.as_node()?.clone())?;
+            Some(cmd.name_tok()?.text().to_owned())
+        };
+        let caption_first = children
+            .iter()
+            .filter(|child| !is_blank(child))
+            .find_map(|child| match command(child).as_deref() {
+                Some("\\centering" | "\\raggedright" | "\\raggedleft" | "\\label") => None,
+                name => Some(name == Some("\\caption")),
+            })
+            .unwrap_or(false);
+        let mut align = "left";
+        let mut segments = vec![(None, vec![], vec![])];
+        let mut closed = false;
+        for child in children.drain(..) {
+            match command(&child).as_deref() {
+                Some("\\centering") => align = "center",
+                Some("\\raggedright") => align = "left",
+                Some("\\raggedleft") => align = "right",
+                Some("\\caption") => {
+                    if segments.last().unwrap().0.is_some() {
+                        segments.push((None, vec![], vec![]));
+                    }
+                    let caption = CmdItem::cast(child.as_node().unwrap().clone());
+                    segments.last_mut().unwrap().0 = caption;
+                    closed = !caption_first;
+                }
+                Some("\\label") => segments.last_mut().unwrap().1.push(child),
+                _ if is_blank(&child) => segments.last_mut().unwrap().2.push(child),
+                _ => {
+                    if closed {
+                        segments.push((None, vec![], vec![]));
+                        closed = false;
+                    }
+                    segments.last_mut().unwrap().2.push(child);
                 }
             }
         }
-        // convert to #figure
+        // the content after the last caption without another caption
+        if segments.len() > 1 && segments.last().unwrap().0.is_none() {
+            let (_, mut labels, mut elems) = segments.pop().unwrap();
+            let last = segments.last_mut().unwrap();
+            last.1.append(&mut labels);
+            last.2.append(&mut elems);
+        }
+        let several = segments.len() > 1;
+
         let prev = self.enter_env(env_kind);
-        f.write_char('#')?;
-        f.write_str(typst_name)?;
-        f.write_char('(')?;
-        if let Some(caption) = caption {
-            f.write_str("caption: [")?;
-            self.convert(f, caption.into(), spec)?;
-            f.write_str("],")?;
+        let outer_label = self.label.take();
+        let mut close = "";
+        if wrap {
+            let side = match braced.first().map(String::as_str) {
+                Some("l" | "L" | "i" | "I") => "left",
+                _ => "right",
+            };
+            // an approximation, as the text does not flow around it
+            write!(f, "#place(top + {side}, float: true)[")?;
+            close = "]";
+        } else if let Some(placement) = placement.filter(|_| several) {
+            write!(f, "#place({placement}, float: true")?;
+            if span {
+                f.write_str(", scope: \"parent\"")?;
+            }
+            f.write_str(")[")?;
+            close = "]";
+        }
+        if sub || wrap {
+            let width = braced.last().and_then(|width| typst_length(width));
+            match width {
+                Some(width) => write!(f, "#box(width: {width})[")?,
+                None => f.write_str("#box[")?,
+            }
+        }
+        for (i, (caption, labels, elems)) in segments.into_iter().enumerate() {
+            if i > 0 {
+                f.write_char('\n')?;
+            }
+            let mut args = format!("kind: {kind}");
+            if sub {
+                args.push_str(", supplement: none, numbering: \"(a)\"");
+            }
+            if let Some(placement) = placement.filter(|_| !several) {
+                write!(args, ", placement: {placement}")?;
+                if span {
+                    args.push_str(", scope: \"parent\"");
+                }
+            }
+            let caption = caption.and_then(|caption| {
+                let mut args = caption.arguments().collect::<Vec<_>>();
+                let long = args.pop()?;
+                Some((args.pop(), long))
+            });
+            let figure = FigureItem {
+                args,
+                caption,
+                sub,
+                align,
+                labels,
+                elems,
+            };
+            self.write_figure(f, typst_name, figure, spec)?;
+        }
+        if sub || wrap {
+            f.write_char(']')?;
+        }
+        f.write_str(close)?;
+        self.label = outer_label;
+        self.exit_env(prev);
+
+        Ok(())
+    }
+
+    /// Convert `\subcaptionbox{caption}[width]{content}` into a subfigure
+    /// boxed in its width
+    pub(crate) fn convert_command_subcaptionbox(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        cmd: &CmdItem,
+        spec: &CommandSpec,
+    ) -> Result<(), Error> {
+        let mut args = cmd.arguments().collect::<Vec<_>>();
+        let (Some(content), Some(caption)) = (args.pop(), args.first().cloned()) else {
+            return Ok(());
+        };
+        // the first option is the width, and the second one the position of
+        // the content
+        let width = args.get(1).and_then(|width| {
+            let width = argument_text(width);
+            typst_length(width.trim_start_matches('[').trim_end_matches(']'))
+        });
+
+        let prev = self.enter_env(LaTeXEnv::Figure);
+        let outer_label = self.label.take();
+        match width {
+            Some(width) => write!(f, "#box(width: {width})[")?,
+            None => f.write_str("#box[")?,
+        }
+        let figure = FigureItem {
+            args: "kind: \"subfigure\", supplement: none, numbering: \"(a)\"".to_owned(),
+            caption: Some((None, caption)),
+            sub: true,
+            align: "center",
+            labels: vec![],
+            elems: argument_elems(&content),
+        };
+        self.write_figure(f, "figure", figure, spec)?;
+        f.write_char(']')?;
+        self.label = outer_label;
+        self.exit_env(prev);
+        Ok(())
+    }
+
+    /// Write a figure with the label in it after the figure
+    fn write_figure(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        typst_name: &str,
+        figure: FigureItem,
+        spec: &CommandSpec,
+    ) -> Result<(), Error> {
+        for label in figure.labels {
+            self.convert(f, label, spec)?;
+        }
+        write!(f, "#{typst_name}({}", figure.args)?;
+        if let Some((short, long)) = figure.caption {
+            f.write_str(", caption: ")?;
+            // `(a) caption` instead of `(a): caption`
+            if figure.sub {
+                f.write_str("figure.caption(separator: [ ], ")?;
+            }
+            f.write_char('[')?;
+            // the short caption is shown in the outline
+            if let Some(short) = short {
+                f.write_str("#context if state(\"mitex-outline\", false).get() [")?;
+                self.convert_elems(f, argument_elems(&short), spec)?;
+                f.write_str("] else [")?;
+                self.convert_elems(f, argument_elems(&long), spec)?;
+                f.write_char(']')?;
+            } else {
+                self.convert_elems(f, argument_elems(&long), spec)?;
+            }
+            f.write_char(']')?;
+            if figure.sub {
+                f.write_char(')')?;
+            }
         }
         f.write_str(")[")?;
-        for child in elem.as_node().unwrap().children_with_tokens() {
+        // subfigures are numbered again in each figure
+        let mut sub_kinds = BTreeSet::new();
+        for elem in &figure.elems {
+            let Some(node) = elem.as_node() else {
+                continue;
+            };
+            for node in node.descendants() {
+                if let Some(env) = EnvItem::cast(node.clone()) {
+                    match env.name_tok().as_ref().map(|name| name.text().trim()) {
+                        Some("subfigure") => sub_kinds.insert("subfigure"),
+                        Some("subtable") => sub_kinds.insert("subtable"),
+                        _ => false,
+                    };
+                } else if CmdItem::cast(node)
+                    .and_then(|cmd| cmd.name_tok())
+                    .is_some_and(|name| name.text() == "\\subcaptionbox")
+                {
+                    sub_kinds.insert("subfigure");
+                }
+            }
+        }
+        for kind in sub_kinds {
+            write!(f, "#counter(figure.where(kind: \"{kind}\")).update(0)")?;
+        }
+        // the content is centered by `\centering`
+        let aligned = figure.align != "center" && !figure.elems.iter().all(is_blank);
+        if aligned {
+            write!(f, "#align({})[", figure.align)?;
+        }
+        self.convert_elems(f, figure.elems, spec)?;
+        if aligned {
+            f.write_char(']')?;
+        }
+        f.write_str("];")?;
+        if let Some(label) = self.label.take() {
+            write_label(f, &label)?;
+        }
+        Ok(())
+    }
+
+    /// Convert matrices like `pmatrix` and `array` into `mat`. The vertical
+    /// rules of `array` and the `\hline`s between rows become the
+    /// augmentation of the matrix, and the columns of `array` or the optional
+    /// alignment of `pmatrix*` its alignment.
+    pub(crate) fn convert_env_matrix(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        env: &EnvItem,
+        spec: &CommandSpec,
+    ) -> Result<(), Error> {
+        let elem = env.syntax();
+        let name_tok = env.name_tok().expect("environment name must be non-empty");
+        let name = name_tok.text().trim();
+        let bare = name.trim_end_matches('*');
+        let env_shape = spec.get_env(bare).ok_or_else(|| {
+            ConvertError::with_name(
+                ConvertErrorKind::UnknownEnvironment,
+                tree_range(name_tok.text_range()),
+                name,
+            )
+        })?;
+        let is_array = matches!(bare, "array" | "subarray");
+
+        // horizontal alignments of the columns and the vertical rules
+        let mut aligns = vec![];
+        let mut vlines = vec![];
+        for arg in env.arguments() {
+            let text = argument_text(&arg);
+            let raw = arg.text().to_string();
+            if raw.trim_start().starts_with('[') {
+                // `[r]` of `pmatrix*`
+                let text = text.trim_start_matches('[').trim_end_matches(']').trim();
+                aligns.extend(match text {
+                    "l" => Some("left"),
+                    "r" => Some("right"),
+                    "c" => Some("center"),
+                    _ => None,
+                });
+            } else if let Some(brace) = raw.find('{').filter(|_| is_array) {
+                let offset = usize::from(arg.text_range().start()) + brace + 1;
+                let columns = parse_columns(&text, offset)?;
+                aligns = columns
+                    .columns
+                    .iter()
+                    .map(|col| match col.align.split(" + ").next() {
+                        Some("left") => "left",
+                        Some("right") => "right",
+                        _ => "center",
+                    })
+                    .collect();
+                let n = columns.columns.len();
+                vlines = columns
+                    .vlines
+                    .into_iter()
+                    .filter(|x| (1..n).contains(x))
+                    .collect();
+            }
+        }
+
+        // the rows before each `\hline`, skipping the tails of `\\`
+        let mut hlines = vec![];
+        let mut rows = 0;
+        let mut in_row = false;
+        let mut tail = None;
+        for child in elem.children_with_tokens() {
+            if tail.is_some_and(|end| child.text_range().end() <= end) || is_blank(&child) {
+                continue;
+            }
+            match child.kind() {
+                LatexSyntaxKind::ItemBegin | LatexSyntaxKind::ItemEnd => {}
+                LatexSyntaxKind::ItemNewLine => {
+                    tail = Some(newline_tail(&child).1);
+                    rows += 1;
+                    in_row = false;
+                }
+                LatexSyntaxKind::ItemCmd
+                    if only_cmd(std::slice::from_ref(&child), "\\hline").is_some() =>
+                {
+                    if !in_row && hlines.last() != Some(&rows) {
+                        hlines.push(rows);
+                    }
+                }
+                _ => in_row = true,
+            }
+        }
+        let rows = rows + usize::from(in_row);
+        hlines.retain(|y| (1..rows).contains(y));
+
+        let with_dollar = matches!(self.mode, LaTeXMode::Text);
+        let prev = self.enter_env(LaTeXEnv::Matrix);
+        let prev_mode = self.enter_mode(LaTeXMode::Math);
+        let prev_columns = self.enter_columns(None);
+        let prev_aligns = std::mem::take(&mut self.cell_aligns);
+        if with_dollar {
+            f.write_str("$ ")?;
+        }
+
+        let typst_name = match is_array {
+            true => "mat",
+            false => env_shape.alias.as_deref().unwrap_or(bare),
+        };
+        let call_args = self.write_call_alias(f, typst_name)?;
+        write_call_open(f, call_args)?;
+        if is_array {
+            f.write_str("delim: #none, ")?;
+        }
+        match aligns.as_slice() {
+            [] => {}
+            // a single alignment for all the columns
+            [align, rest @ ..] if rest.iter().all(|a| a == align) => {
+                if *align != "center" {
+                    write!(f, "align: #{align}, ")?;
+                }
+            }
+            // mixed alignments by the alignment points in the cells
+            _ => self.cell_aligns = aligns,
+        }
+        if !(vlines.is_empty() && hlines.is_empty()) {
+            let lines = |name: &str, lines: &[usize]| match lines {
+                [] => None,
+                [x] => Some(format!("{name}: {x}")),
+                lines => {
+                    let lines = lines.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+                    Some(format!("{name}: ({})", lines.join(", ")))
+                }
+            };
+            let augment = [lines("hline", &hlines), lines("vline", &vlines)];
+            let augment = augment.into_iter().flatten().collect::<Vec<_>>();
+            write!(f, "augment: #({}), ", augment.join(", "))?;
+        }
+        self.cell_start = self.written.get();
+        self.write_cell_begin(f)?;
+
+        for child in elem.children_with_tokens() {
             // skip \begin and \end commands
             if matches!(
                 child.kind(),
                 LatexSyntaxKind::ItemBegin | LatexSyntaxKind::ItemEnd
-            ) || matches!(child.kind(), LatexSyntaxKind::ItemCmd) && is_named_arg(&child)
-            {
+            ) {
                 continue;
             }
             self.convert(f, child, spec)?;
         }
-        f.write_str("];")?;
-        self.exit_env(prev);
+        self.write_cell_end(f)?;
+        f.write_char(')')?;
 
+        self.cell_aligns = prev_aligns;
+        self.exit_columns(prev_columns);
+        self.exit_env(prev);
+        self.exit_mode(prev_mode);
+        if with_dollar {
+            f.write_str(" $")?;
+        }
         Ok(())
     }
 
-    /// Convert tabular environment
-    fn convert_env_table(
+    /// Convert tabular environments like `tabular` and `longtable` into
+    /// `table`. The rows of a `longtable` before `\endhead` are its header,
+    /// and the ones before `\endfoot` its footer.
+    pub(crate) fn convert_env_table(
         &mut self,
         f: &mut fmt::Formatter<'_>,
-        elem: LatexSyntaxElem,
+        env: &EnvItem,
         spec: &CommandSpec,
         env_kind: LaTeXEnv,
         typst_name: &str,
-    ) -> Result<(), ConvertError> {
-        let env = EnvItem::cast(elem.as_node().unwrap().clone()).unwrap();
-        let arg = env
-            .arguments()
-            .next()
-            .expect("tabular environment must have one argument");
-        let arg = arg.text().to_string();
-        // remove { and } then trim
-        let arg = &arg[1..(arg.len() - 1)];
-        let arg = arg.trim();
-        // split arg to get alignments and vertical lines
-        let mut alignments: Vec<char> = vec![];
-        let mut vlines: Vec<usize> = vec![];
-        let mut index = 0;
-        for ch in arg.chars() {
-            match ch {
-                'l' | 'c' | 'r' => {
-                    alignments.push(ch);
-                    index += 1;
-                }
-                '|' => {
-                    vlines.push(index);
-                }
-                _ => {
-                    // unknown character
-                    Err(format!("unknown alignment: {}", ch))?;
+    ) -> Result<(), Error> {
+        // the column specification is the last argument, in braces
+        let arg = env.arguments().last();
+        let text = arg.as_ref().map(|arg| arg.text().to_string());
+        let columns = text
+            .as_deref()
+            .and_then(|text| text.strip_prefix('{')?.strip_suffix('}'));
+        let (Some(arg), Some(columns)) = (arg, columns) else {
+            let name = env.name_tok().unwrap();
+            Err(ConvertError::with_name(
+                ConvertErrorKind::MissingArgument,
+                tree_range(env.syntax().text_range()),
+                name.text().trim(),
+            ))?
+        };
+        let arg_offset = usize::from(arg.text_range().start()) + 1;
+        let Columns { columns, vlines } = parse_columns(columns, arg_offset)?;
+
+        // split the rows into the header, the body and the footer
+        let mut header = None;
+        let mut footer = None;
+        let mut last_footer = vec![];
+        let mut first_head = false;
+        let mut caption = None;
+        let mut body = vec![];
+        for item in table_items(env) {
+            match item {
+                TableItem::Rule(cmd) => match cmd.name_tok().unwrap().text() {
+                    "\\endfirsthead" => {
+                        header = Some(std::mem::take(&mut body));
+                        first_head = true;
+                    }
+                    // the header continued on the following pages
+                    "\\endhead" if first_head => body.clear(),
+                    "\\endhead" => header = Some(std::mem::take(&mut body)),
+                    "\\endfoot" => footer = Some(std::mem::take(&mut body)),
+                    "\\endlastfoot" => last_footer = std::mem::take(&mut body),
+                    _ => body.push(TableItem::Rule(cmd)),
+                },
+                TableItem::Row(cells) => {
+                    match cells.as_slice() {
+                        // the caption of a longtable
+                        [cell] if find_cmd(cell, "\\caption").is_some() => {
+                            caption = find_cmd(cell, "\\caption");
+                            let key = find_cmd(cell, "\\label").map(|cmd| label_key(&cmd));
+                            if let Some(key) = key.filter(|key| !key.is_empty()) {
+                                self.label = Some(self.label_name(&key));
+                            }
+                        }
+                        _ => body.push(TableItem::Row(cells)),
+                    }
                 }
             }
         }
-        // convert to #figure
+        if footer.is_none() {
+            body.append(&mut last_footer);
+        }
+
         let prev = self.enter_env(env_kind);
+        if let Some(caption) = &caption {
+            // a long table may break across pages
+            f.write_str(
+                "#[#show figure: set block(breakable: true); #figure(kind: table, caption: [",
+            )?;
+            if let Some(arg) = caption.arguments().last() {
+                self.convert(f, arg.into(), spec)?;
+            }
+            f.write_str("],)[")?;
+        }
         f.write_char('#')?;
         f.write_str(typst_name)?;
         f.write_char('(')?;
         // stroke: none,
         f.write_str("stroke: none,\n")?;
-        // columns: 2,
-        f.write_str(format!("columns: {},\n", alignments.len()).as_str())?;
+        // columns: 2, or columns: (auto, 3cm, ),
+        if columns.iter().any(|col| col.width.is_some()) {
+            f.write_str("columns: (")?;
+            for col in columns.iter() {
+                write!(f, "{}, ", col.width.as_deref().unwrap_or("auto"))?;
+            }
+            f.write_str("),\n")?;
+        } else {
+            writeln!(f, "columns: {},", columns.len())?;
+        }
         // align: (left, center, right, ),
         f.write_str("align: (")?;
-        for align in alignments {
-            match align {
-                'l' => f.write_str("left, ")?,
-                'c' => f.write_str("center, ")?,
-                'r' => f.write_str("right, ")?,
-                _ => {}
-            }
+        for col in columns.iter() {
+            write!(f, "{}, ", col.align)?;
         }
         f.write_str("),\n")?;
         // table.vline(x: 1),
@@ -1111,98 +3129,217 @@ impl Converter {
             f.write_str(format!("table.vline(stroke: .5pt, x: {}), ", vline).as_str())?;
         }
         f.write_str("\n")?;
-        let mut exterior = true;
-        for child in elem.as_node().unwrap().children_with_tokens() {
-            // skip \begin and \end commands
-            if matches!(
-                child.kind(),
-                LatexSyntaxKind::ItemBegin | LatexSyntaxKind::ItemEnd
-            ) {
-                continue;
+        // the rows still taken by a `\multirow` above, for each column
+        let mut occupied = vec![0; columns.len()];
+        if let Some(header) = header {
+            f.write_str("table.header(\n")?;
+            self.write_table_items(f, &header, &mut occupied, spec)?;
+            f.write_str("),\n")?;
+        }
+        self.write_table_items(f, &body, &mut occupied, spec)?;
+        if let Some(footer) = footer {
+            f.write_str("table.footer(\n")?;
+            self.write_table_items(f, &footer, &mut occupied, spec)?;
+            f.write_str("),\n")?;
+        }
+        f.write_str(");")?;
+        if caption.is_some() {
+            f.write_str("];")?;
+            if let Some(label) = self.label.take() {
+                write_label(f, &label)?;
             }
-            // preprocess if exterior
-            if exterior {
-                match child.kind() {
-                    LatexSyntaxKind::TokenWhiteSpace | LatexSyntaxKind::TokenLineBreak => {}
-                    LatexSyntaxKind::TokenAmpersand => {
-                        // write `[],` for empty exterior when encountering &
-                        f.write_str("[], ")?;
-                    }
-                    LatexSyntaxKind::ItemNewLine => {
-                        // write `[],\n` for empty exterior when encountering //
-                        f.write_str("[],\n")?;
-                    }
-                    LatexSyntaxKind::ItemCmd => {
-                        let cmd_name = CmdItem::cast(child.as_node().unwrap().clone())
-                            .unwrap()
-                            .name_tok()
-                            .unwrap();
-                        let cmd_name = &cmd_name.text()[1..];
-                        match cmd_name {
-                            "hline" => {
-                                f.write_str("table.hline(stroke: .5pt),\n")?;
-                            }
-                            "toprule" => {
-                                f.write_str("table.hline(stroke: 1pt),\n")?;
-                            }
-                            "midrule" => {
-                                f.write_str("table.hline(stroke: .5pt),\n")?;
-                            }
-                            "bottomrule" => {
-                                f.write_str("table.hline(stroke: 1pt),\n")?;
-                            }
-                            _ => {
-                                // enter interior for normal commands
-                                exterior = false;
-                                f.write_char('[')?;
+            f.write_char(']')?;
+        }
+        self.exit_env(prev);
+        Ok(())
+    }
+
+    /// Write the rows and rules of a table
+    fn write_table_items(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        items: &[TableItem],
+        occupied: &mut [usize],
+        spec: &CommandSpec,
+    ) -> Result<(), Error> {
+        for item in items {
+            let cells = match item {
+                TableItem::Rule(cmd) => {
+                    let name = cmd.name_tok().unwrap();
+                    let stroke = match name.text() {
+                        "\\toprule" | "\\bottomrule" => "1pt",
+                        _ => ".5pt",
+                    };
+                    match name.text() {
+                        "\\cline" | "\\cmidrule" => {
+                            if let Some((start, end)) = rule_columns(cmd) {
+                                writeln!(
+                                    f,
+                                    "table.hline(start: {start}, end: {end}, stroke: {stroke}),"
+                                )?;
                             }
                         }
+                        _ => writeln!(f, "table.hline(stroke: {stroke}),")?,
                     }
-                    _ => {
-                        // enter interior for normal text
-                        exterior = false;
-                        f.write_char('[')?;
-                    }
+                    continue;
                 }
+                TableItem::Row(cells) => cells,
+            };
+
+            let mut spans = vec![0; occupied.len()];
+            let mut col = 0;
+            let mut first = true;
+            for elems in cells {
+                let cell = TableCell::new(elems);
+                if occupied.get(col).is_some_and(|&rows| rows > 0)
+                    && cell.content.iter().all(is_blank)
+                {
+                    // the place of a `\multirow` above
+                    col += cell.colspan;
+                    continue;
+                }
+                if !std::mem::take(&mut first) {
+                    f.write_str(", ")?;
+                }
+                let mut args = vec![];
+                if cell.colspan > 1 {
+                    args.push(format!("colspan: {}", cell.colspan));
+                }
+                if cell.rowspan > 1 {
+                    args.push(format!("rowspan: {}", cell.rowspan));
+                }
+                if let Some(align) = &cell.align {
+                    args.push(format!("align: {align}"));
+                }
+                if !args.is_empty() {
+                    write!(f, "table.cell({})", args.join(", "))?;
+                }
+                f.write_char('[')?;
+                for child in cell.content {
+                    self.convert(f, child, spec)?;
+                }
+                f.write_char(']')?;
+                let end = (col + cell.colspan).min(spans.len());
+                for span in spans.iter_mut().take(end).skip(col) {
+                    *span = cell.rowspan - 1;
+                }
+                col += cell.colspan;
             }
-            // process if interior
-            if !exterior {
-                match child.kind() {
-                    LatexSyntaxKind::TokenAmpersand => {
-                        // write `[],` when encountering &
-                        f.write_str("], ")?;
-                        exterior = true;
-                    }
-                    LatexSyntaxKind::ItemNewLine => {
-                        // write `[],\n` when encountering //
-                        f.write_str("],\n")?;
-                        exterior = true;
-                    }
-                    _ => {
-                        self.convert(f, child, spec)?;
+            // fill up a short row
+            while col < occupied.len() {
+                if occupied[col] == 0 {
+                    if !std::mem::take(&mut first) {
+                        f.write_str(", ")?;
                     }
+                    f.write_str("[]")?;
                 }
+                col += 1;
+            }
+            if !first {
+                f.write_str(",\n")?;
+            }
+            for (rows, span) in occupied.iter_mut().zip(spans) {
+                *rows = rows.saturating_sub(1).max(span);
             }
         }
-        f.write_str(");")?;
-        self.exit_env(prev);
         Ok(())
     }
 }
 
-struct TypstRepr {
+struct TypstRepr<'a> {
+    input: &'a str,
+    spans: &'a SpanMap,
     elem: LatexSyntaxElem,
-    mode: LaTeXMode,
     spec: CommandSpec,
-    error: Rc<RefCell<String>>,
+    options: &'a ConvertOptions,
+    // recovered errors, followed by the fatal error if any
+    diagnostics: Rc<RefCell<Vec<ConvertError>>>,
+    // output written so far
+    output: &'a RefCell<String>,
+    // length of the output written so far
+    written: &'a Cell<usize>,
+    // whether the output is at the start of a line or of a content block
+    line_start: &'a Cell<bool>,
+    // source mappings, if wanted
+    mappings: Option<Rc<RefCell<Vec<SourceMapping>>>>,
+    // names written by the output, of the MiTeX scope or native Typst
+    aliases: Rc<RefCell<BTreeSet<String>>>,
 }
 
-impl fmt::Display for TypstRepr {
+impl fmt::Display for TypstRepr<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut ctx = Converter::new(self.mode);
-        if let Err(e) = ctx.convert(f, self.elem.clone(), &self.spec) {
-            self.error.borrow_mut().push_str(&e.to_string());
-            return Err(fmt::Error);
+        let mut ctx = Converter::new(
+            self.input,
+            self.spans,
+            self.output,
+            self.written,
+            self.line_start,
+            self.options,
+        );
+        // delimiters of a formula starting in math mode
+        let (open, close) = match (self.options.mode, self.options.math_style) {
+            (LaTeXMode::Math, MathStyle::Inline) => ("$", "$"),
+            (LaTeXMode::Math, MathStyle::Display) => ("$ ", " $"),
+            _ => ("", ""),
+        };
+        f.write_str(open)?;
+        if let Some(root) = self.elem.as_node() {
+            ctx.collect_labels(root);
+        }
+        let res = ctx.convert(f, self.elem.clone(), &self.spec);
+        if let (Some(mappings), Some(mut found)) = (&self.mappings, ctx.mappings.take()) {
+            mappings.borrow_mut().append(&mut found);
+        }
+        self.aliases.borrow_mut().append(&mut ctx.aliases);
+        let mut diagnostics = self.diagnostics.borrow_mut();
+        diagnostics.append(&mut ctx.diagnostics);
+        match res {
+            Ok(()) => f.write_str(close),
+            Err(Error::Fmt(e)) => Err(e),
+            Err(Error::Convert(e)) => {
+                diagnostics.push(e);
+                Err(fmt::Error)
+            }
+        }
+    }
+}
+
+/// The result of a conversion, along with its diagnostics and source map.
+#[derive(Debug, Clone, Default)]
+pub struct Converted {
+    /// The converted Typst code
+    pub output: String,
+    /// Errors that were recovered from, in the order of occurrence
+    pub diagnostics: Vec<ConvertError>,
+    /// The mapping between the output and the input, if requested
+    pub source_map: Option<SourceMap>,
+    /// Names from the scope of the MiTeX package used by the output, sorted,
+    /// e.g. `mitexsqrt` for `\sqrt`
+    ///
+    /// A document holding the output binds them from `mitex-scope`.
+    pub aliases: Vec<String>,
+    /// Definitions of the helpers used by the output, to be put before it
+    /// in a document, for [`Target::Standalone`]
+    pub prelude: String,
+}
+
+/// A writer counting the bytes written into a string
+struct CountingWriter<'a> {
+    output: &'a RefCell<String>,
+    written: &'a Cell<usize>,
+    line_start: &'a Cell<bool>,
+}
+
+impl fmt::Write for CountingWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut output = self.output.borrow_mut();
+        output.push_str(s);
+        self.written.set(output.len());
+        // escapes like `\[` are written at once
+        let s = s.trim_end_matches([' ', '\t']);
+        if let Some(last) = s.chars().last() {
+            let open = last == '[' && !s.ends_with("\\[");
+            self.line_start.set(last == '\n' || open);
         }
         Ok(())
     }
@@ -1211,22 +3348,88 @@ impl fmt::Display for TypstRepr {
 #[inline(always)]
 pub fn convert_inner(
     input: &str,
-    mode: LaTeXMode,
-    spec: Option<CommandSpec>,
-    do_parse: fn(input: &str, spec: CommandSpec) -> SyntaxNode,
-) -> Result<String, String> {
-    let node = do_parse(input, spec.unwrap_or_else(|| DEFAULT_SPEC.clone()));
+    options: &ConvertOptions,
+    do_parse: fn(input: &str, spec: CommandSpec) -> (SyntaxNode, SpanMap),
+) -> Result<Converted, ConvertError> {
+    let spec = options.spec.clone().unwrap_or_else(|| DEFAULT_SPEC.clone());
+    let (node, spans) = do_parse(input, spec.clone());
     // println!("{:#?}", node);
     // println!("{:#?}", node.text());
-    let mut output = String::new();
-    let err = String::new();
-    let err = Rc::new(RefCell::new(err));
+    let output = RefCell::new(String::new());
+    let diagnostics = Rc::new(RefCell::new(vec![]));
+    let mappings = options.source_map.then(|| Rc::new(RefCell::new(vec![])));
+    let written = Cell::new(0);
+    let line_start = Cell::new(true);
+    let aliases = Rc::new(RefCell::new(BTreeSet::new()));
     let repr = TypstRepr {
+        input,
+        spans: &spans,
         elem: LatexSyntaxElem::Node(node),
-        mode,
-        spec: DEFAULT_SPEC.clone(),
-        error: err.clone(),
+        spec,
+        options,
+        diagnostics: diagnostics.clone(),
+        output: &output,
+        written: &written,
+        line_start: &line_start,
+        mappings: mappings.clone(),
+        aliases: aliases.clone(),
+    };
+    let mut writer = CountingWriter {
+        output: &output,
+        written: &written,
+        line_start: &line_start,
+    };
+    let res = core::fmt::write(&mut writer, format_args!("{}", repr));
+    let mut output = output.take();
+
+    // map the ranges in the syntax tree back to the input
+    let mut diagnostics = diagnostics.take();
+    let to_size = |offset: usize| TextSize::try_from(offset).unwrap_or_default();
+    for err in diagnostics.iter_mut() {
+        err.range = spans.to_input_range(TextRange::new(
+            to_size(err.range.start),
+            to_size(err.range.end),
+        ));
+    }
+
+    if res.is_err() {
+        return Err(diagnostics
+            .pop()
+            .expect("a Display implementation returned an error unexpectedly"));
+    }
+    let mut mappings = mappings.map(|m| m.take());
+    if options.readable {
+        let root = match (options.mode, options.math_style) {
+            (LaTeXMode::Math, MathStyle::Bare) => Context::Math,
+            _ => Context::Markup,
+        };
+        let tidied = tidy(&output, root);
+        if let Some(mappings) = mappings.as_mut() {
+            mappings.retain_mut(|m| match tidied.map_range(m.output.clone()) {
+                Some(range) => {
+                    m.output = range;
+                    true
+                }
+                None => false,
+            });
+        }
+        output = tidied.output;
+    }
+    // the names missing in the MiTeX scope are native Typst
+    let aliases = aliases
+        .take()
+        .into_iter()
+        .filter(|name| scope_item(name).is_some())
+        .collect::<Vec<_>>();
+    let prelude = match options.target {
+        Target::Standalone => write_prelude(&aliases),
+        Target::MitexScope => String::new(),
     };
-    core::fmt::write(&mut output, format_args!("{}", repr)).map_err(|_| err.borrow().to_owned())?;
-    Ok(output)
+    Ok(Converted {
+        output,
+        diagnostics,
+        source_map: mappings.map(SourceMap::new),
+        aliases,
+        prelude,
+    })
 }
@@ -0,0 +1,113 @@
+//! Errors reported by the converter.
+
+use core::fmt;
+use std::ops::Range;
+
+/// Kind of a [`ConvertError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConvertErrorKind {
+    /// The parser produced an error node, e.g. an unmatched `}`
+    UnexpectedToken,
+    /// A command that is not defined in the command specification
+    UnknownCommand,
+    /// An environment that is not defined in the command specification
+    UnknownEnvironment,
+    /// A formula whose delimiters don't match, e.g. `\[x\)`
+    InvalidFormula,
+    /// An `\item` command outside of `itemize` or `enumerate`
+    ItemOutsideList,
+    /// An unsupported column in the alignment of a `tabular` environment
+    BadTabularAlignment,
+    /// A column specification of a `tabular` environment with more columns
+    /// than a table can have, e.g. `*{99999}{c}`
+    TooManyColumns,
+    /// A command or environment without a required argument, e.g. `tabular`
+    /// without its column specification
+    MissingArgument,
+    /// A command name that is not attached to a command
+    CommandNameOutsideCommand,
+    /// A `\begin` or `\end` clause that is not attached to an environment
+    ClauseOutsideEnvironment,
+}
+
+/// An error that stops the conversion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConvertError {
+    /// Kind of the error
+    pub kind: ConvertErrorKind,
+    /// Byte range of the offending syntax in the input
+    pub range: Range<usize>,
+    /// Name of the offending command or environment without the leading
+    /// backslash, or the offending text for other kinds of errors
+    pub name: Option<String>,
+}
+
+impl ConvertError {
+    /// Create an error without a name
+    pub fn new(kind: ConvertErrorKind, range: Range<usize>) -> Self {
+        Self {
+            kind,
+            range,
+            name: None,
+        }
+    }
+
+    /// Create an error with the name of the offending command or environment
+    pub fn with_name(kind: ConvertErrorKind, range: Range<usize>, name: impl Into<String>) -> Self {
+        Self {
+            kind,
+            range,
+            name: Some(name.into()),
+        }
+    }
+
+    /// Get the 1-based line and column of the start of the error in `input`.
+    ///
+    /// The column is counted in chars. `input` must be the text passed to the
+    /// converter.
+    pub fn line_column(&self, input: &str) -> (usize, usize) {
+        let offset = self.range.start.min(input.len());
+        let offset = (0..=offset)
+            .rev()
+            .find(|&i| input.is_char_boundary(i))
+            .unwrap_or(0);
+        let before = &input[..offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let column = before[line_start..].chars().count() + 1;
+        (line, column)
+    }
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.name.as_deref().unwrap_or_default();
+        f.write_str("error: ")?;
+        match self.kind {
+            ConvertErrorKind::UnexpectedToken if name.is_empty() => f.write_str("unexpected token"),
+            ConvertErrorKind::UnexpectedToken => write!(f, "unexpected token: {name}"),
+            ConvertErrorKind::UnknownCommand => write!(f, "unknown command: \\{name}"),
+            ConvertErrorKind::UnknownEnvironment => write!(f, "unknown environment: {name}"),
+            ConvertErrorKind::InvalidFormula => f.write_str("formula is not valid"),
+            ConvertErrorKind::ItemOutsideList => {
+                f.write_str("item command outside of itemize or enumerate")
+            }
+            ConvertErrorKind::BadTabularAlignment => write!(f, "unknown alignment: {name}"),
+            ConvertErrorKind::TooManyColumns => write!(
+                f,
+                "too many columns in alignment, at most {} are supported",
+                crate::tabular::MAX_COLUMNS
+            ),
+            ConvertErrorKind::MissingArgument => write!(f, "missing argument: {name}"),
+            ConvertErrorKind::CommandNameOutsideCommand => {
+                f.write_str("command name outside of command")
+            }
+            ConvertErrorKind::ClauseOutsideEnvironment => {
+                f.write_str("clauses outside of environment")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConvertError {}
@@ -0,0 +1,160 @@
+//! Escaping text for the places it reaches in the Typst code.
+
+use core::fmt::{self, Write};
+
+/// Write text into Typst markup, e.g. a paragraph or the content block
+/// argument of a function, escaping the characters that would change the
+/// structure of the document.
+///
+/// Markers of headings, lists and enumerations only take effect at the start
+/// of a line or of a content block, which is given by `line_start`.
+pub(crate) fn write_markup(
+    f: &mut fmt::Formatter<'_>,
+    text: &str,
+    line_start: bool,
+) -> fmt::Result {
+    for (i, c) in text.char_indices() {
+        let rest = &text[i + c.len_utf8()..];
+        // a marker followed by a space or the end of the text
+        let marker = line_start && !rest.starts_with(|c: char| !c.is_whitespace());
+        let escape = match c {
+            '\\' | '*' | '_' | '`' | '$' | '#' | '@' | '<' | '>' | '[' | ']' | '~' | '"' => true,
+            // comments
+            '/' if rest.starts_with(['/', '*']) => true,
+            // soft hyphens
+            '-' if rest.starts_with('?') => true,
+            // lists and terms
+            '-' | '+' | '/' => marker && i == 0,
+            // headings
+            '=' => {
+                let level = text.len() - text.trim_start_matches('=').len();
+                line_start && i == 0 && !text[level..].starts_with(|c: char| !c.is_whitespace())
+            }
+            // enumerations like `1.`
+            '.' => marker && i > 0 && text[..i].chars().all(|c| c.is_ascii_digit()),
+            _ => false,
+        };
+        if escape {
+            f.write_char('\\')?;
+        }
+        f.write_char(c)?;
+    }
+    Ok(())
+}
+
+/// Write the content of a Typst string literal, without the quotes
+pub(crate) fn write_string(f: &mut fmt::Formatter<'_>, text: &str) -> fmt::Result {
+    for c in text.chars() {
+        match c {
+            '\\' => f.write_str("\\\\")?,
+            '"' => f.write_str("\\\"")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            _ => f.write_char(c)?,
+        }
+    }
+    Ok(())
+}
+
+/// Write raw text, as `` `a_b` `` or a fenced block with its language in
+/// markup, and as a call to `raw` in math or when the backticks cannot
+/// enclose the text
+pub(crate) fn write_raw(
+    f: &mut fmt::Formatter<'_>,
+    text: &str,
+    lang: Option<&str>,
+    block: bool,
+    markup: bool,
+) -> fmt::Result {
+    // the longest run of backticks in the text
+    let ticks = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    if markup && block {
+        let fence = "`".repeat(ticks.max(2) + 1);
+        return write!(f, "{fence}{}\n{text}\n{fence}", lang.unwrap_or_default());
+    }
+    if markup && ticks == 0 && !text.is_empty() && !text.contains(['\n', '\r']) {
+        return write!(f, "`{text}`");
+    }
+    f.write_str("#raw(")?;
+    if block {
+        f.write_str("block: true, ")?;
+    }
+    if let Some(lang) = lang {
+        write!(f, "lang: \"{lang}\", ")?;
+    }
+    f.write_char('"')?;
+    write_string(f, text)?;
+    f.write_str("\")")
+}
+
+/// Whether a label name can be written in the label syntax, e.g. `<fig:a_b>`
+pub(crate) fn is_plain_label(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(is_label_char)
+}
+
+/// Whether a character is allowed in the label syntax
+pub(crate) fn is_label_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':')
+}
+
+/// Clean a LaTeX label key into a name of the label syntax, replacing each
+/// run of other characters like spaces and `/` by `-`, e.g. `fig-a-b` for
+/// `fig a/b`
+pub(crate) fn clean_label(key: &str) -> String {
+    let mut name = String::new();
+    for c in key.trim().chars() {
+        if is_label_char(c) {
+            name.push(c);
+        } else if !name.ends_with('-') {
+            name.push('-');
+        }
+    }
+    name
+}
+
+/// Write a Typst label into markup, as `<name>` if the name is allowed by
+/// the label syntax, or as `#label("name")` otherwise
+pub(crate) fn write_label(f: &mut fmt::Formatter<'_>, name: &str) -> fmt::Result {
+    if !is_plain_label(name) {
+        f.write_char('#')?;
+    }
+    write_label_value(f, name)
+}
+
+/// Write a Typst label into code, as `<name>` or `label("name")`
+pub(crate) fn write_label_value(f: &mut fmt::Formatter<'_>, name: &str) -> fmt::Result {
+    if is_plain_label(name) {
+        write!(f, "<{name}>")
+    } else {
+        f.write_str("label(\"")?;
+        write_string(f, name)?;
+        f.write_str("\")")
+    }
+}
+
+/// The markup of a LaTeX control symbol in text mode, e.g. `\#` for `\#`
+pub(crate) fn control_symbol_markup(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "#" => "\\#",
+        "$" => "\\$",
+        "%" => "%",
+        "&" => "&",
+        "_" => "\\_",
+        "{" => "{",
+        "}" => "}",
+        "|" => "‖",
+        "-" => "-?",
+        "*" => "",
+        "" | " " => " ",
+        "," => "#h(3em / 18);",
+        ":" | ">" => "#h(4em / 18);",
+        ";" => "#h(5em / 18);",
+        "!" => "#h(-3em / 18);",
+        _ => return None,
+    })
+}
@@ -0,0 +1,248 @@
+//! The front matter of a document, e.g. `\title` and `\author`, written as
+//! its metadata and by `\maketitle`.
+
+use core::fmt;
+use std::fmt::Write;
+
+use mitex_parser::spec::*;
+use mitex_parser::syntax::{CmdItem, SyntaxElement as LatexSyntaxElem};
+use mitex_parser::syntax::{SyntaxKind as LatexSyntaxKind, SyntaxNode};
+use rowan::ast::AstNode;
+
+use crate::converter::{accent_base, argument_elems, is_blank, Converter, Error};
+use crate::escape::write_string;
+use crate::unicode::{accent_mark, compose, ligatures, text_symbol};
+
+/// The front matter of a document, given by `\title`, `\author` and the like
+/// in its preamble or body
+#[derive(Debug, Default)]
+pub(crate) struct FrontMatter {
+    title: Option<SyntaxNode>,
+    // the authors of `\author`, split at `\and`
+    authors: Vec<Vec<LatexSyntaxElem>>,
+    // the affiliations of `\affil` from `authblk`
+    affils: Vec<SyntaxNode>,
+    // the date is today if there is no `\date`, as in LaTeX
+    date: Option<SyntaxNode>,
+    keywords: Vec<String>,
+}
+
+impl FrontMatter {
+    /// Collect the front matter from the commands among some nodes
+    pub(crate) fn collect(&mut self, nodes: impl Iterator<Item = SyntaxNode>) {
+        for cmd in nodes.filter_map(CmdItem::cast) {
+            let Some(arg) = cmd.arguments().last() else {
+                continue;
+            };
+            match cmd.name_tok().unwrap().text() {
+                "\\title" => self.title = Some(arg),
+                "\\author" => self.authors.extend(split_authors(&arg)),
+                "\\affil" => self.affils.push(arg),
+                "\\date" => self.date = Some(arg),
+                "\\keywords" => {
+                    let text = plain_text(argument_elems(&arg));
+                    let keywords = text.split([',', ';']).map(str::trim);
+                    let keywords = keywords.filter(|k| !k.is_empty()).map(String::from);
+                    self.keywords.extend(keywords);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.title.is_none() && self.authors.is_empty() && self.keywords.is_empty()
+    }
+}
+
+/// The format of `\today` in Typst, e.g. `January 2, 2006`
+pub(crate) const TODAY: &str = "[month repr:long] [day padding:none], [year]";
+
+/// Split the argument of `\author` into the authors separated by `\and`
+fn split_authors(arg: &SyntaxNode) -> Vec<Vec<LatexSyntaxElem>> {
+    let mut authors = vec![vec![]];
+    for elem in argument_elems(arg) {
+        let is_and = elem
+            .as_node()
+            .cloned()
+            .and_then(CmdItem::cast)
+            .and_then(|cmd| cmd.name_tok())
+            .is_some_and(|name| name.text() == "\\and");
+        if is_and {
+            authors.push(vec![]);
+        } else {
+            authors.last_mut().unwrap().push(elem);
+        }
+    }
+    authors.retain(|author| !author.iter().all(is_blank));
+    authors
+}
+
+/// Get the plain text of some elements for the metadata of a document, e.g.
+/// `Erdős` for `Erd\H{o}s`, dropping footnotes and formulas
+pub(crate) fn plain_text(elems: impl IntoIterator<Item = LatexSyntaxElem>) -> String {
+    fn visit(elem: LatexSyntaxElem, text: &mut String) {
+        match elem {
+            LatexSyntaxElem::Token(token) => match token.kind() {
+                LatexSyntaxKind::TokenWord => text.push_str(&ligatures(token.text())),
+                LatexSyntaxKind::TokenWhiteSpace
+                | LatexSyntaxKind::TokenLineBreak
+                | LatexSyntaxKind::TokenTilde => visit_space(text),
+                LatexSyntaxKind::TokenApostrophe => text.push('’'),
+                LatexSyntaxKind::TokenComma
+                | LatexSyntaxKind::TokenSemicolon
+                | LatexSyntaxKind::TokenSlash
+                | LatexSyntaxKind::TokenLParen
+                | LatexSyntaxKind::TokenRParen
+                | LatexSyntaxKind::TokenLBracket
+                | LatexSyntaxKind::TokenRBracket
+                | LatexSyntaxKind::TokenAsterisk
+                | LatexSyntaxKind::TokenAtSign
+                | LatexSyntaxKind::TokenDitto => text.push_str(token.text()),
+                _ => {}
+            },
+            LatexSyntaxElem::Node(node) => match node.kind() {
+                LatexSyntaxKind::ItemCmd => {
+                    let cmd = CmdItem::cast(node).unwrap();
+                    let name = cmd.name_tok().unwrap();
+                    let name = &name.text()[1..];
+                    let arg = cmd.arguments().next();
+                    if let Some(mark) = accent_mark(name) {
+                        let base = arg.as_ref().and_then(accent_base).unwrap_or_default();
+                        text.push_str(&compose(&base, mark));
+                    } else if let Some(symbol) = text_symbol(name) {
+                        text.push_str(symbol);
+                    } else if name.len() == 1 && !name.starts_with(char::is_alphabetic) {
+                        // characters like `\&`
+                        text.push_str(name);
+                    } else if !matches!(name, "thanks" | "footnote" | "label") {
+                        for arg in cmd.arguments() {
+                            visit(LatexSyntaxElem::Node(arg), text);
+                        }
+                    }
+                }
+                LatexSyntaxKind::ItemNewLine => visit_space(text),
+                LatexSyntaxKind::ItemFormula | LatexSyntaxKind::ItemTypstCode => {}
+                _ => {
+                    for child in node.children_with_tokens() {
+                        visit(child, text);
+                    }
+                }
+            },
+        }
+    }
+
+    fn visit_space(text: &mut String) {
+        if !text.is_empty() && !text.ends_with(' ') {
+            text.push(' ');
+        }
+    }
+
+    let mut text = String::new();
+    for elem in elems {
+        visit(elem, &mut text);
+    }
+    text.trim().to_owned()
+}
+
+impl Converter<'_> {
+    /// Write `#set document(..)` with the title, the authors and the keywords
+    /// of the document
+    pub(crate) fn write_document_metadata(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> Result<(), Error> {
+        let mut args = vec![];
+        if let Some(title) = &self.front.title {
+            args.push(("title", vec![plain_text(argument_elems(title))]));
+        }
+        if !self.front.authors.is_empty() {
+            let names = self.front.authors.iter().map(|author| {
+                let name = author
+                    .iter()
+                    .take_while(|e| e.kind() != LatexSyntaxKind::ItemNewLine);
+                plain_text(name.cloned())
+            });
+            args.push(("author", names.collect()));
+        }
+        if !self.front.keywords.is_empty() {
+            args.push(("keywords", self.front.keywords.clone()));
+        }
+
+        f.write_str("#set document(")?;
+        for (i, (name, values)) in args.into_iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{name}: ")?;
+            let array = name != "title";
+            if array {
+                f.write_char('(')?;
+            }
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                f.write_char('"')?;
+                write_string(f, value)?;
+                f.write_char('"')?;
+            }
+            if array {
+                f.write_str(if values.len() == 1 { ",)" } else { ")" })?;
+            }
+        }
+        f.write_str(")\n\n")?;
+        Ok(())
+    }
+
+    /// Convert `\maketitle` into a centered block of the title, the authors
+    /// and the date
+    pub(crate) fn convert_maketitle(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        spec: &CommandSpec,
+    ) -> Result<(), Error> {
+        let front = std::mem::take(&mut self.front);
+        f.write_str("#align(center)[")?;
+        if let Some(title) = &front.title {
+            f.write_str("#block(text(size: 1.7em)[")?;
+            self.convert_elems(f, argument_elems(title), spec)?;
+            f.write_str("])")?;
+        }
+        // authors side by side, at most three in a row
+        match front.authors.len() {
+            0 => {}
+            1 => {
+                f.write_str("#block[")?;
+                self.convert_elems(f, front.authors[0].clone(), spec)?;
+                f.write_char(']')?;
+            }
+            n => {
+                write!(f, "#grid(columns: {}, column-gutter: 2em", n.min(3))?;
+                for author in &front.authors {
+                    f.write_str(", [")?;
+                    self.convert_elems(f, author.clone(), spec)?;
+                    f.write_char(']')?;
+                }
+                f.write_char(')')?;
+            }
+        }
+        for affil in &front.affils {
+            f.write_str("#block[")?;
+            self.convert_elems(f, argument_elems(affil), spec)?;
+            f.write_char(']')?;
+        }
+        match &front.date {
+            Some(date) if argument_elems(date).iter().all(is_blank) => {}
+            Some(date) => {
+                f.write_str("#block[")?;
+                self.convert_elems(f, argument_elems(date), spec)?;
+                f.write_char(']')?;
+            }
+            None => write!(f, "#block(datetime.today().display({TODAY:?}))")?,
+        }
+        f.write_char(']')?;
+        self.front = front;
+        Ok(())
+    }
+}
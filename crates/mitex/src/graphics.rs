@@ -0,0 +1,138 @@
+//! Converting the options of `\includegraphics`, e.g. `width=0.5\textwidth`,
+//! and looking up the files of its images.
+
+use std::path::Path;
+
+use mitex_lexer::group_span;
+
+use crate::length::typst_length;
+
+/// Extensions tried in order for an image named without one, as by
+/// `\DeclareGraphicsExtensions`
+const EXTENSIONS: &[&str] = &["png", "jpg", "svg", "pdf"];
+
+/// The options of `\includegraphics` that Typst can show
+#[derive(Debug, Default)]
+pub(crate) struct GraphicsOptions {
+    pub width: Option<String>,
+    pub height: Option<String>,
+    // the percentage of `scale`
+    pub scale: Option<f64>,
+    // counterclockwise in degrees, as in LaTeX
+    pub angle: Option<f64>,
+    pub keep_aspect_ratio: bool,
+    // the left, bottom, right and top of `trim`
+    pub trim: Option<[String; 4]>,
+    pub clip: bool,
+    pub page: Option<usize>,
+}
+
+impl GraphicsOptions {
+    /// Parse the options like `width=3cm, angle=90, clip`, dropping the
+    /// unknown keys and values
+    pub fn parse(text: &str) -> Self {
+        let mut options = Self::default();
+        for option in text.split(',') {
+            let (key, value) = option.split_once('=').unwrap_or((option, ""));
+            let value = value.trim().trim_start_matches('{').trim_end_matches('}');
+            let flag = matches!(value.trim(), "" | "true");
+            match key.trim() {
+                "width" => options.width = typst_length(value),
+                "height" | "totalheight" => options.height = typst_length(value),
+                "scale" => {
+                    let scale = value.trim().parse::<f64>().ok();
+                    options.scale = scale.map(|scale| (scale * 1e6).round() / 1e4);
+                }
+                "angle" => options.angle = value.trim().parse().ok(),
+                "keepaspectratio" => options.keep_aspect_ratio = flag,
+                "clip" => options.clip = flag,
+                "page" => options.page = value.trim().parse().ok(),
+                "trim" => {
+                    // lengths without units are in big points
+                    let lengths = value
+                        .split_whitespace()
+                        .map(|length| match length.parse::<f64>() {
+                            Ok(_) => typst_length(&format!("{length}bp")),
+                            Err(_) => typst_length(length),
+                        })
+                        .collect::<Option<Vec<_>>>();
+                    options.trim = lengths.and_then(|lengths| lengths.try_into().ok());
+                }
+                _ => {}
+            }
+        }
+        options
+    }
+
+    /// The `fit` of an image given both a width and a height, which is
+    /// stretched unless `keepaspectratio` is set
+    pub fn fit(&self) -> Option<&'static str> {
+        if self.width.is_none() || self.height.is_none() {
+            return None;
+        }
+        Some(if self.keep_aspect_ratio {
+            "contain"
+        } else {
+            "stretch"
+        })
+    }
+}
+
+/// The directories of `\graphicspath`, given the text of its argument like
+/// `{figs/}{img/}`
+pub(crate) fn graphics_dirs(text: &str) -> Vec<String> {
+    let mut dirs = vec![];
+    let mut pos = 0;
+    while let Some(span) = group_span(text, pos, '{', '}') {
+        dirs.push(text[span.start + 1..span.end - 1].trim());
+        pos = span.end;
+    }
+    // a single directory without braces
+    if dirs.is_empty() && !text.trim().is_empty() {
+        dirs.push(text.trim());
+    }
+    dirs.into_iter()
+        .map(|dir| {
+            if dir.ends_with('/') {
+                dir.to_owned()
+            } else {
+                format!("{dir}/")
+            }
+        })
+        .collect()
+}
+
+/// Find the file of an image in the directory of the document or else in
+/// the directories of `\graphicspath`, trying the known extensions for a
+/// name without one. The name is kept if there is no `base` directory to
+/// look in or no file is found.
+pub(crate) fn resolve_image(name: &str, dirs: &[String], base: Option<&Path>) -> String {
+    let Some(base) = base else {
+        return name.to_owned();
+    };
+    let known = Path::new(name).extension().is_some_and(|ext| {
+        EXTENSIONS
+            .iter()
+            .any(|known| ext.eq_ignore_ascii_case(known))
+    });
+    let dirs = if Path::new(name).is_absolute() {
+        &[][..]
+    } else {
+        dirs
+    };
+    for dir in std::iter::once("").chain(dirs.iter().map(String::as_str)) {
+        let path = format!("{dir}{name}");
+        let mut candidates = vec![];
+        if !known {
+            candidates.extend(EXTENSIONS.iter().map(|ext| format!("{path}.{ext}")));
+        }
+        candidates.push(path);
+        if let Some(found) = candidates
+            .into_iter()
+            .find(|path| base.join(path).is_file())
+        {
+            return found;
+        }
+    }
+    name.to_owned()
+}
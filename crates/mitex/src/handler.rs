@@ -0,0 +1,727 @@
+//! Handlers converting commands and environments by their names.
+//!
+//! A [`CommandHandler`] or [`EnvHandler`] registered in [`Handlers`] takes
+//! over the conversion of a command or environment, before the command
+//! specification is consulted. The special cases of MiTeX itself, like
+//! `\item` or `figure`, are registered in [`Handlers::default`].
+
+use core::fmt::{self, Write};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use mitex_parser::spec::CommandSpec;
+use mitex_parser::syntax::{CmdItem, EnvItem, SyntaxElement, SyntaxKind};
+use rowan::ast::AstNode;
+
+use crate::bibliography::CiteForm;
+use crate::converter::{Converter, Error, LaTeXEnv, LaTeXMode, RefStyle};
+use crate::error::ConvertError;
+use crate::escape::write_raw;
+use crate::front_matter::TODAY;
+use crate::options::Target;
+use crate::unicode::accent_mark;
+
+/// An error raised while converting a command or environment by a handler.
+///
+/// It is either a [`ConvertError`] or a failure of the writer, and is
+/// converted from both by the `?` operator.
+#[derive(Debug)]
+pub struct HandlerError(pub(crate) Error);
+
+impl From<fmt::Error> for HandlerError {
+    fn from(e: fmt::Error) -> Self {
+        Self(Error::Fmt(e))
+    }
+}
+
+impl From<ConvertError> for HandlerError {
+    fn from(e: ConvertError) -> Self {
+        Self(Error::Convert(e))
+    }
+}
+
+impl From<Error> for HandlerError {
+    fn from(e: Error) -> Self {
+        Self(e)
+    }
+}
+
+/// The state of the converter passed to a handler.
+///
+/// Typst code is written to the output by the [`fmt::Write`] implementation.
+pub struct HandlerContext<'c, 'a, 'f> {
+    pub(crate) conv: &'c mut Converter<'a>,
+    pub(crate) f: &'c mut fmt::Formatter<'f>,
+    pub(crate) spec: &'c CommandSpec,
+}
+
+impl HandlerContext<'_, '_, '_> {
+    /// The current mode of the converter.
+    pub fn mode(&self) -> LaTeXMode {
+        self.conv.mode
+    }
+
+    /// Switch to another mode, returning the previous one.
+    #[must_use]
+    pub fn enter_mode(&mut self, mode: LaTeXMode) -> LaTeXMode {
+        self.conv.enter_mode(mode)
+    }
+
+    /// Restore the mode returned by [`HandlerContext::enter_mode`].
+    pub fn exit_mode(&mut self, prev: LaTeXMode) {
+        self.conv.exit_mode(prev)
+    }
+
+    /// The command specification used by the conversion.
+    pub fn spec(&self) -> &CommandSpec {
+        self.spec
+    }
+
+    /// Convert a syntax element, e.g. an argument of the command, in the
+    /// current mode.
+    pub fn convert(&mut self, elem: SyntaxElement) -> Result<(), HandlerError> {
+        Ok(self.conv.convert(self.f, elem, self.spec)?)
+    }
+}
+
+impl fmt::Write for HandlerContext<'_, '_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.f.write_str(s)
+    }
+}
+
+/// Converts a command, e.g. `\foo{x}`.
+///
+/// Ranges of the errors returned by a handler are offsets in the syntax tree,
+/// e.g. the text range of the command, and are mapped back to the input by
+/// the converter.
+pub trait CommandHandler {
+    /// Write the Typst code for `cmd`.
+    fn convert(&self, ctx: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError>;
+}
+
+impl<F> CommandHandler for F
+where
+    F: Fn(&mut HandlerContext, &CmdItem) -> Result<(), HandlerError>,
+{
+    fn convert(&self, ctx: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError> {
+        self(ctx, cmd)
+    }
+}
+
+/// Converts an environment, e.g. `\begin{foo}x\end{foo}`.
+///
+/// The children of the environment include its `\begin` and `\end` clauses.
+/// See [`CommandHandler`] for the ranges of errors.
+pub trait EnvHandler {
+    /// Write the Typst code for `env`.
+    fn convert(&self, ctx: &mut HandlerContext, env: &EnvItem) -> Result<(), HandlerError>;
+}
+
+impl<F> EnvHandler for F
+where
+    F: Fn(&mut HandlerContext, &EnvItem) -> Result<(), HandlerError>,
+{
+    fn convert(&self, ctx: &mut HandlerContext, env: &EnvItem) -> Result<(), HandlerError> {
+        self(ctx, env)
+    }
+}
+
+type SharedCommandHandler = Arc<dyn CommandHandler + Send + Sync>;
+type SharedEnvHandler = Arc<dyn EnvHandler + Send + Sync>;
+
+/// Handlers for commands and environments, by their names without `\`.
+#[derive(Clone)]
+pub struct Handlers {
+    commands: HashMap<String, SharedCommandHandler>,
+    envs: HashMap<String, SharedEnvHandler>,
+}
+
+impl fmt::Debug for Handlers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Handlers")
+            .field("commands", &self.commands.keys())
+            .field("envs", &self.envs.keys())
+            .finish()
+    }
+}
+
+impl Default for Handlers {
+    /// The built-in handlers of MiTeX.
+    fn default() -> Self {
+        let mut handlers = Self {
+            commands: HashMap::new(),
+            envs: HashMap::new(),
+        };
+        // lists, labels and references
+        handlers
+            .add_command("item", convert_item)
+            .add_command("label", convert_label)
+            .add_command("tag", convert_tag)
+            .add_command("tag*", convert_tag)
+            .add_command("ref", convert_ref)
+            .add_command("eqref", convert_ref)
+            .add_command("autoref", convert_ref)
+            .add_command("cref", convert_ref)
+            .add_command("Cref", convert_ref);
+        // headings and the front matter
+        handlers
+            .add_command("section", convert_heading)
+            .add_command("subsection", convert_heading)
+            .add_command("subsubsection", convert_heading)
+            .add_command("usepackage", convert_front_matter)
+            .add_command("title", convert_front_matter)
+            .add_command("author", convert_front_matter)
+            .add_command("affil", convert_front_matter)
+            .add_command("date", convert_front_matter)
+            .add_command("keywords", convert_front_matter)
+            .add_command("and", convert_front_matter)
+            .add_command("maketitle", convert_maketitle)
+            .add_command("today", convert_today);
+        // links and footnotes
+        handlers
+            .add_command("url", convert_link)
+            .add_command("href", convert_link)
+            .add_command("hyperref", convert_link)
+            .add_command("footnotemark", convert_footnote_mark)
+            .add_command("footnotetext", convert_footnote_mark);
+        // citations and bibliographies
+        for name in [
+            "cite",
+            "citep",
+            "citet",
+            "citeauthor",
+            "citeyear",
+            "citeyearpar",
+            "parencite",
+            "textcite",
+            "autocite",
+            "fullcite",
+        ] {
+            handlers.add_command(name, convert_cite);
+        }
+        handlers
+            .add_command("bibliography", convert_bibliography)
+            .add_command("printbibliography", convert_bibliography)
+            .add_command("bibliographystyle", convert_front_matter)
+            .add_command("addbibresource", convert_front_matter)
+            .add_command("nocite", convert_front_matter)
+            .add_env("thebibliography", convert_thebibliography);
+        // theorems
+        handlers
+            .add_command("newtheorem", convert_front_matter)
+            .add_command("newtheorem*", convert_front_matter)
+            .add_command("theoremstyle", convert_front_matter)
+            .add_env("proof", convert_proof);
+        // text styles, colors and accents
+        handlers
+            .add_command("color", convert_color)
+            .add_command("textcolor", convert_color)
+            .add_command("colorbox", convert_color)
+            .add_command("textbf", convert_textbf)
+            .add_command("textit", convert_textit)
+            .add_command("mathrm", convert_upright_word)
+            .add_command("operatorname", convert_upright_word)
+            .add_command("sqrt", convert_sqrt);
+        for name in [
+            "`", "'", "^", "~", "=", "u", ".", "\"", "r", "H", "v", "d", "c", "k", "b", "t",
+        ] {
+            handlers.add_command(name, convert_accent);
+        }
+        // equations and matrices
+        handlers
+            .add_command("intertext", convert_intertext)
+            .add_command("shortintertext", convert_intertext)
+            .add_env("equation", convert_equation)
+            .add_env("equation*", convert_equation)
+            .add_env("align", convert_equation)
+            .add_env("align*", convert_equation)
+            .add_env("alignat", convert_equation)
+            .add_env("alignat*", convert_equation)
+            .add_env("flalign", convert_equation)
+            .add_env("flalign*", convert_equation)
+            .add_env("gather", convert_equation)
+            .add_env("gather*", convert_equation)
+            .add_env("aligned", convert_equation)
+            .add_env("alignedat", convert_equation);
+        for name in [
+            "matrix", "pmatrix", "bmatrix", "Bmatrix", "vmatrix", "Vmatrix",
+        ] {
+            handlers
+                .add_env(name, convert_matrix)
+                .add_env(format!("{name}*"), convert_matrix);
+        }
+        handlers
+            .add_env("smallmatrix", convert_matrix)
+            .add_env("array", convert_matrix)
+            .add_env("subarray", convert_matrix);
+        // verbatim text
+        handlers
+            .add_command("verb", convert_verb)
+            .add_command("verb*", convert_verb)
+            .add_env("verbatim", convert_verbatim)
+            .add_env("verbatim*", convert_verbatim)
+            .add_env("Verbatim", convert_verbatim)
+            .add_env("lstlisting", convert_verbatim)
+            .add_env("minted", convert_verbatim);
+        // figures and graphics
+        handlers
+            .add_command("includegraphics", convert_includegraphics)
+            .add_command("graphicspath", convert_front_matter)
+            .add_command("subcaptionbox", convert_subcaptionbox)
+            .add_env("figure", convert_figure)
+            .add_env("table", convert_figure)
+            .add_env("figure*", convert_figure)
+            .add_env("table*", convert_figure)
+            .add_env("subfigure", convert_figure)
+            .add_env("subtable", convert_figure)
+            .add_env("wrapfigure", convert_figure)
+            .add_env("wraptable", convert_figure);
+        // tables
+        handlers
+            .add_command("multicolumn", convert_cell)
+            .add_command("multirow", convert_cell)
+            .add_command("cline", convert_rule)
+            .add_command("cmidrule", convert_rule)
+            .add_env("tabular", convert_tabular)
+            .add_env("tabular*", convert_tabular)
+            .add_env("tabularx", convert_tabular)
+            .add_env("longtable", convert_tabular);
+        handlers
+    }
+}
+
+impl Handlers {
+    /// Register a handler for the command `\name`, replacing the previous
+    /// one.
+    pub fn add_command(
+        &mut self,
+        name: impl Into<String>,
+        handler: impl CommandHandler + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.commands.insert(name.into(), Arc::new(handler));
+        self
+    }
+
+    /// Register a handler for the environment `name`, replacing the previous
+    /// one.
+    pub fn add_env(
+        &mut self,
+        name: impl Into<String>,
+        handler: impl EnvHandler + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.envs.insert(name.into(), Arc::new(handler));
+        self
+    }
+
+    /// Remove the handler for the command `\name`, converting it by the
+    /// command specification instead.
+    pub fn remove_command(&mut self, name: &str) -> &mut Self {
+        self.commands.remove(name);
+        self
+    }
+
+    /// Remove the handler for the environment `name`, converting it by the
+    /// command specification instead.
+    pub fn remove_env(&mut self, name: &str) -> &mut Self {
+        self.envs.remove(name);
+        self
+    }
+
+    pub(crate) fn command(&self, name: &str) -> Option<&SharedCommandHandler> {
+        self.commands.get(name)
+    }
+
+    pub(crate) fn env(&self, name: &str) -> Option<&SharedEnvHandler> {
+        self.envs.get(name)
+    }
+}
+
+// Built-in handlers
+
+fn convert_item(ctx: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError> {
+    Ok(ctx.conv.convert_command_item(ctx.f, cmd)?)
+}
+
+fn convert_label(ctx: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError> {
+    Ok(ctx.conv.convert_command_label(ctx.f, cmd)?)
+}
+
+/// `\tag` is written as the numbering of its equation, see
+/// [`convert_equation`]
+fn convert_tag(_ctx: &mut HandlerContext, _cmd: &CmdItem) -> Result<(), HandlerError> {
+    Ok(())
+}
+
+/// `\ref` and `\eqref` show the number only, `\cref` with a lowercase
+/// supplement, and `\autoref` and `\Cref` with the supplement
+fn convert_ref(ctx: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError> {
+    let style = match cmd.name_tok().unwrap().text() {
+        "\\cref" => RefStyle::Lower,
+        "\\autoref" | "\\Cref" => RefStyle::Upper,
+        _ => RefStyle::Number,
+    };
+    Ok(ctx.conv.convert_command_ref(ctx.f, cmd, style)?)
+}
+
+fn convert_heading(ctx: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError> {
+    Ok(ctx.conv.convert_command_heading(ctx.f, cmd, ctx.spec)?)
+}
+
+/// `\title`, `\author` and the like are written by `\maketitle` and in
+/// the metadata of the document, settings like `\newtheorem` are collected
+/// before converting the document, and `\usepackage` is dropped
+fn convert_front_matter(_ctx: &mut HandlerContext, _cmd: &CmdItem) -> Result<(), HandlerError> {
+    Ok(())
+}
+
+fn convert_maketitle(ctx: &mut HandlerContext, _cmd: &CmdItem) -> Result<(), HandlerError> {
+    Ok(ctx.conv.convert_maketitle(ctx.f, ctx.spec)?)
+}
+
+/// `\cite` and `\citep` are citations like `@key`, and `\citet`,
+/// `\citeauthor` and the like are `cite` of the corresponding form
+fn convert_cite(ctx: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError> {
+    let form = match cmd.name_tok().unwrap().text() {
+        "\\citet" | "\\textcite" => CiteForm::Prose,
+        "\\citeauthor" => CiteForm::Author,
+        "\\citeyear" | "\\citeyearpar" => CiteForm::Year,
+        "\\fullcite" => CiteForm::Full,
+        _ => CiteForm::Normal,
+    };
+    Ok(ctx.conv.convert_command_cite(ctx.f, cmd, form, ctx.spec)?)
+}
+
+/// `\bibliography` and `\printbibliography` are `#bibliography` with the
+/// style given by `\bibliographystyle` or the options of `biblatex`
+fn convert_bibliography(ctx: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError> {
+    Ok(ctx.conv.convert_command_bibliography(ctx.f, cmd)?)
+}
+
+/// `\url` and `\href` are links to their URLs, which are raw text, and
+/// `\hyperref` is a link to a label
+fn convert_link(ctx: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError> {
+    Ok(ctx.conv.convert_command_link(ctx.f, cmd, ctx.spec)?)
+}
+
+/// `\footnotemark` and `\footnotetext` are joined into a footnote at the
+/// mark
+fn convert_footnote_mark(ctx: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError> {
+    Ok(ctx
+        .conv
+        .convert_command_footnote_mark(ctx.f, cmd, ctx.spec)?)
+}
+
+fn convert_proof(ctx: &mut HandlerContext, env: &EnvItem) -> Result<(), HandlerError> {
+    Ok(ctx.conv.convert_env_theorem(ctx.f, env, ctx.spec, None)?)
+}
+
+fn convert_thebibliography(ctx: &mut HandlerContext, env: &EnvItem) -> Result<(), HandlerError> {
+    Ok(ctx.conv.convert_env_thebibliography(ctx.f, env, ctx.spec)?)
+}
+
+/// `\today` is the date of compiling the Typst document
+fn convert_today(ctx: &mut HandlerContext, _cmd: &CmdItem) -> Result<(), HandlerError> {
+    write!(ctx.f, "#datetime.today().display({TODAY:?})")?;
+    Ok(())
+}
+
+/// `equation`, `align` and `gather` are numbered display equations in text
+/// mode unless starred, and follow the spec in math mode. The column pairs of
+/// `align` and `flalign` are spaced apart, while `alignat` leaves it to the
+/// document
+fn convert_equation(ctx: &mut HandlerContext, env: &EnvItem) -> Result<(), HandlerError> {
+    let name = env.name_tok().unwrap();
+    let name = name.text().trim();
+    let gap =
+        matches!(name.trim_end_matches('*'), "align" | "flalign" | "aligned").then_some("quad");
+    let prev = ctx.conv.enter_columns(gap);
+    let res = if matches!(ctx.mode(), LaTeXMode::Math) || name.starts_with("aligned") {
+        ctx.conv.convert_normal_env(ctx.f, env, ctx.spec)
+    } else {
+        let numbered = !name.ends_with('*');
+        let by_row = !name.starts_with("equation");
+        ctx.conv
+            .convert_env_equation(ctx.f, env, ctx.spec, numbered, by_row)
+    };
+    ctx.conv.exit_columns(prev);
+    Ok(res?)
+}
+
+/// `\intertext` in math mode is a row of text, see [`convert_equation`] for
+/// text mode
+fn convert_intertext(ctx: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError> {
+    ctx.conv
+        .convert_normal_command(ctx.f, cmd, ctx.spec, None)?;
+    if matches!(ctx.mode(), LaTeXMode::Math) {
+        ctx.write_str("\\ ")?;
+    }
+    Ok(())
+}
+
+fn convert_includegraphics(ctx: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError> {
+    Ok(ctx.conv.convert_command_includegraphics(ctx.f, cmd)?)
+}
+
+fn convert_color(ctx: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError> {
+    Ok(ctx.conv.convert_command_color(ctx.f, cmd, ctx.spec)?)
+}
+
+/// `\textbf` is `#strong` in text mode, and follows the spec in math mode
+fn convert_textbf(ctx: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError> {
+    let typst_name = matches!(ctx.mode(), LaTeXMode::Text).then_some("#strong");
+    Ok(ctx
+        .conv
+        .convert_normal_command(ctx.f, cmd, ctx.spec, typst_name)?)
+}
+
+/// `\textit` is `#emph` in text mode, and follows the spec in math mode
+fn convert_textit(ctx: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError> {
+    let typst_name = matches!(ctx.mode(), LaTeXMode::Text).then_some("#emph");
+    Ok(ctx
+        .conv
+        .convert_normal_command(ctx.f, cmd, ctx.spec, typst_name)?)
+}
+
+/// `\mathrm{max}` is `upright("max")` in the readable output, and follows
+/// the spec otherwise
+fn convert_upright_word(ctx: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError> {
+    let word = ctx
+        .conv
+        .options
+        .readable
+        .then(|| plain_word_argument(cmd))
+        .flatten();
+    match word {
+        Some(word) if matches!(ctx.mode(), LaTeXMode::Math) => {
+            let name = cmd.name_tok().unwrap();
+            let name = &name.text()[1..];
+            let shape = ctx.spec.get_cmd(name);
+            let typst_name = shape.and_then(|s| s.alias.as_deref()).unwrap_or(name);
+            let call_args = ctx.conv.write_call_alias(ctx.f, typst_name)?;
+            let args = call_args
+                .map(|args| format!("{args}, "))
+                .unwrap_or_default();
+            write!(ctx, "({args}\"{word}\")")?;
+            Ok(())
+        }
+        _ => Ok(ctx
+            .conv
+            .convert_normal_command(ctx.f, cmd, ctx.spec, None)?),
+    }
+}
+
+/// Get the letters of the only argument of a command, e.g. `max` in
+/// `\mathrm{max}`
+fn plain_word_argument(cmd: &CmdItem) -> Option<String> {
+    let mut args = cmd
+        .syntax()
+        .children()
+        .filter(|node| node.kind() != SyntaxKind::ClauseCommandName);
+    let arg = args.next().filter(|_| args.next().is_none())?;
+    let text = arg.text().to_string();
+    let word = text.trim().strip_prefix('{')?.strip_suffix('}')?.trim();
+    let is_word = !word.is_empty() && word.chars().all(|c| c.is_ascii_alphabetic());
+    is_word.then(|| word.to_owned())
+}
+
+/// `\sqrt[n]{x}` is `root(n, x)` in standalone Typst, and follows the spec
+/// otherwise
+fn convert_sqrt(ctx: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError> {
+    let standalone = matches!(ctx.conv.options.target, Target::Standalone);
+    if !standalone || !matches!(ctx.mode(), LaTeXMode::Math) {
+        return Ok(ctx
+            .conv
+            .convert_normal_command(ctx.f, cmd, ctx.spec, None)?);
+    }
+    let args = cmd.arguments().collect::<Vec<_>>();
+    match args.as_slice() {
+        [radicand] => {
+            ctx.conv.write_call_alias(ctx.f, "sqrt")?;
+            ctx.write_char('(')?;
+            ctx.convert(radicand.clone().into())?;
+        }
+        [index, radicand] => {
+            ctx.conv.write_call_alias(ctx.f, "root")?;
+            ctx.write_char('(')?;
+            // the index without its brackets
+            for child in index.children_with_tokens() {
+                match child.as_node() {
+                    Some(bracket) if bracket.kind() == SyntaxKind::ItemBracket => {
+                        for elem in bracket.children_with_tokens() {
+                            if !matches!(
+                                elem.kind(),
+                                SyntaxKind::TokenLBracket | SyntaxKind::TokenRBracket
+                            ) {
+                                ctx.convert(elem)?;
+                            }
+                        }
+                    }
+                    _ => ctx.convert(child)?,
+                }
+            }
+            ctx.write_str(", ")?;
+            ctx.convert(radicand.clone().into())?;
+        }
+        _ => {
+            return Ok(ctx
+                .conv
+                .convert_normal_command(ctx.f, cmd, ctx.spec, None)?)
+        }
+    }
+    ctx.write_char(')')?;
+    Ok(())
+}
+
+/// Accents like `\"o` are accented letters in text mode
+fn convert_accent(ctx: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError> {
+    let name = cmd.name_tok().unwrap();
+    let mark = accent_mark(&name.text()[1..]).expect("accent commands must have a mark");
+    Ok(ctx
+        .conv
+        .convert_command_accent(ctx.f, cmd, ctx.spec, mark)?)
+}
+
+/// `\verb|x|` is raw text, with visible spaces if starred
+fn convert_verb(ctx: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError> {
+    let raw = cmd
+        .syntax()
+        .descendants_with_tokens()
+        .find(|elem| elem.kind() == SyntaxKind::TokenVerbatim);
+    // `\verb` without a closing delimiter is followed by plain text
+    let Some(raw) = raw else {
+        for arg in cmd.arguments() {
+            ctx.convert(arg.into())?;
+        }
+        return Ok(());
+    };
+    // remove the delimiters
+    let mut text = raw.as_token().unwrap().text().chars();
+    text.next();
+    text.next_back();
+    let mut text = text.as_str().to_owned();
+    if cmd.name_tok().unwrap().text().ends_with('*') {
+        text = text.replace(' ', "␣");
+    }
+    let markup = matches!(ctx.mode(), LaTeXMode::Text);
+    Ok(write_raw(ctx.f, &text, None, false, markup)?)
+}
+
+/// Verbatim environments are raw blocks, in the language given by
+/// `[language=Python]` of `lstlisting` or the argument of `minted`
+fn convert_verbatim(ctx: &mut HandlerContext, env: &EnvItem) -> Result<(), HandlerError> {
+    let name = env.name_tok().unwrap();
+    let raw = env
+        .syntax()
+        .children_with_tokens()
+        .find(|elem| elem.kind() == SyntaxKind::TokenVerbatim);
+    let text = raw
+        .as_ref()
+        .map_or("", |raw| raw.as_token().unwrap().text());
+    // the line breaks after `\begin` and before `\end` are not part of the
+    // text
+    let text = text
+        .strip_prefix("\r\n")
+        .or_else(|| text.strip_prefix('\n'))
+        .unwrap_or(text);
+    let text = text.trim_end_matches([' ', '\t']);
+    let text = text.strip_suffix('\n').unwrap_or(text);
+    let text = text.strip_suffix('\r').unwrap_or(text);
+    let text = match name.text().trim() {
+        "verbatim*" => text.replace(' ', "␣"),
+        _ => text.to_owned(),
+    };
+    let lang = env.arguments().find_map(|arg| {
+        let arg = arg.text().to_string();
+        let arg = arg.trim();
+        match arg.strip_prefix('[') {
+            Some(options) => listing_language(options.trim_end_matches(']')),
+            None => raw_language(arg.trim_start_matches('{').trim_end_matches('}')),
+        }
+    });
+    let markup = matches!(ctx.mode(), LaTeXMode::Text);
+    Ok(write_raw(ctx.f, &text, lang.as_deref(), true, markup)?)
+}
+
+/// Get the language from the options of `lstlisting`, e.g. `python` from
+/// `language=Python` or `c` from `language={[Sharp]C}`
+fn listing_language(options: &str) -> Option<String> {
+    let language = options.split(',').find_map(|option| {
+        let (key, value) = option.split_once('=')?;
+        (key.trim() == "language").then_some(value)
+    })?;
+    let language = language
+        .trim()
+        .trim_start_matches('{')
+        .trim_end_matches('}');
+    // the dialect of the language
+    let language = match language.trim_start().strip_prefix('[') {
+        Some(rest) => rest.split_once(']').map_or(rest, |(_, language)| language),
+        None => language,
+    };
+    raw_language(language)
+}
+
+/// Get the language tag of Typst raw text from a language name, e.g.
+/// `python` from `Python`
+fn raw_language(name: &str) -> Option<String> {
+    let tag = name
+        .trim()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '#' | '-'))
+        .collect::<String>()
+        .to_ascii_lowercase();
+    (!tag.is_empty()).then_some(tag)
+}
+
+fn convert_figure(ctx: &mut HandlerContext, env: &EnvItem) -> Result<(), HandlerError> {
+    let typst_name = env_alias(ctx.spec, env, "figure");
+    Ok(ctx
+        .conv
+        .convert_env_figure(ctx.f, env, ctx.spec, LaTeXEnv::Figure, &typst_name)?)
+}
+
+fn convert_subcaptionbox(ctx: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError> {
+    Ok(ctx
+        .conv
+        .convert_command_subcaptionbox(ctx.f, cmd, ctx.spec)?)
+}
+
+fn convert_tabular(ctx: &mut HandlerContext, env: &EnvItem) -> Result<(), HandlerError> {
+    let typst_name = env_alias(ctx.spec, env, "table");
+    Ok(ctx
+        .conv
+        .convert_env_table(ctx.f, env, ctx.spec, LaTeXEnv::Table, &typst_name)?)
+}
+
+/// Matrices and `array` are `mat` with the rules of `array` and `\hline`
+/// as its augmentation
+fn convert_matrix(ctx: &mut HandlerContext, env: &EnvItem) -> Result<(), HandlerError> {
+    Ok(ctx.conv.convert_env_matrix(ctx.f, env, ctx.spec)?)
+}
+
+/// `\multicolumn` and `\multirow` span the cells of tables, and are only
+/// their content elsewhere
+fn convert_cell(ctx: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError> {
+    let content = cmd
+        .arguments()
+        .filter(|arg| !arg.text().to_string().trim_start().starts_with('['))
+        .last();
+    if let Some(content) = content {
+        ctx.convert(content.into())?;
+    }
+    Ok(())
+}
+
+/// `\cline` and `\cmidrule` are partial lines of tables, and nothing
+/// elsewhere
+fn convert_rule(_ctx: &mut HandlerContext, _cmd: &CmdItem) -> Result<(), HandlerError> {
+    Ok(())
+}
+
+/// Get the alias of an environment from the spec
+fn env_alias(spec: &CommandSpec, env: &EnvItem, default: &str) -> String {
+    let name = env.name_tok().map(|t| t.text().trim().to_owned());
+    name.and_then(|name| spec.get_env(&name)?.alias.clone())
+        .unwrap_or_else(|| default.to_owned())
+}
@@ -0,0 +1,493 @@
+//! Inlining of the files of a multi-file project, i.e. the files included by
+//! `\input`, `\include`, `\subfile` and `\import`.
+//!
+//! The inlined text is converted as a single document, so that the macros
+//! defined in earlier files are visible to later ones.
+
+use core::fmt;
+use std::io;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+
+use mitex_lexer::group_span;
+
+/// Environments whose content is not scanned for inclusions
+const RAW_ENVS: &[&str] = &[
+    "verbatim",
+    "verbatim*",
+    "Verbatim",
+    "lstlisting",
+    "minted",
+    "comment",
+];
+
+/// An error raised while inlining the files of a project.
+#[derive(Debug)]
+pub enum IncludeError {
+    /// A file that cannot be found
+    Missing {
+        /// Name of the file as written in the including file
+        name: String,
+        /// The including file
+        from: PathBuf,
+        /// 1-based line and column of the inclusion in `from`
+        line_column: (usize, usize),
+    },
+    /// A file that includes itself, directly or through other files
+    Cycle {
+        /// The chain of inclusions, ending with the file included again
+        chain: Vec<PathBuf>,
+    },
+    /// A file that cannot be read
+    Io {
+        /// Path of the file
+        path: PathBuf,
+        /// The error raised by the reader
+        error: io::Error,
+    },
+}
+
+impl fmt::Display for IncludeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing {
+                name,
+                from,
+                line_column: (line, column),
+            } => write!(
+                f,
+                "{}:{line}:{column}: error: file `{name}` not found",
+                from.display()
+            ),
+            Self::Cycle { chain } => {
+                f.write_str("error: cyclic inclusion: ")?;
+                for (i, path) in chain.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" -> ")?;
+                    }
+                    write!(f, "{}", path.display())?;
+                }
+                Ok(())
+            }
+            Self::Io { path, error } => {
+                write!(f, "error: failed to read {}: {error}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for IncludeError {}
+
+/// A project whose included files are inlined into the text of its main
+/// file.
+#[derive(Debug, Clone, Default)]
+pub struct Inlined {
+    /// The text of the project
+    pub text: String,
+    /// The path and the text of each file read
+    files: Vec<(PathBuf, String)>,
+    /// The ranges of `text` copied from the files, in order
+    chunks: Vec<Chunk>,
+}
+
+/// A range of the inlined text copied from a file
+#[derive(Debug, Clone)]
+struct Chunk {
+    /// Start of the chunk in the inlined text
+    start: usize,
+    /// Index of the file in [`Inlined::files`]
+    file: usize,
+    /// Start of the chunk in the file
+    file_start: usize,
+}
+
+impl Inlined {
+    /// Get the path and the text of the file that a byte offset in
+    /// [`Inlined::text`] comes from, and the offset in that file.
+    pub fn source(&self, offset: usize) -> Option<(&Path, &str, usize)> {
+        let idx = self.chunks.partition_point(|c| c.start <= offset);
+        let chunk = &self.chunks[idx.checked_sub(1)?];
+        let (path, text) = &self.files[chunk.file];
+        let offset = (chunk.file_start + offset - chunk.start).min(text.len());
+        Some((path, text, offset))
+    }
+}
+
+/// Inline the files included by the file at `main` and by the files it
+/// includes, reading them by `read`.
+///
+/// As in LaTeX, each included name is looked up relative to the directory of
+/// the main file, and then relative to `workspace`, with a `.tex` extension
+/// if it has none. The names in a `\subfile` are relative to its directory
+/// instead, and only its document body is inlined. The directory given by
+/// `\import` is looked up in the same way, and the one given by `\subimport`
+/// is relative to the directory that the names of the including file are
+/// looked up in.
+pub fn inline_includes(
+    main: &Path,
+    workspace: &Path,
+    read: &mut dyn FnMut(&Path) -> io::Result<String>,
+) -> Result<Inlined, IncludeError> {
+    let main = normalize(main);
+    let text = read(&main).map_err(|error| IncludeError::Io {
+        path: main.clone(),
+        error,
+    })?;
+    let base = main.parent().map(Path::to_owned).unwrap_or_default();
+    let mut inliner = Inliner {
+        root: base.clone(),
+        workspace: normalize(workspace),
+        read,
+        stack: vec![],
+        res: Inlined::default(),
+    };
+    inliner.inline(main, text, 0..usize::MAX, base)?;
+    Ok(inliner.res)
+}
+
+/// A command including a file
+struct Inclusion {
+    /// Range of the command in the including file
+    range: Range<usize>,
+    /// Name of the file
+    name: String,
+    /// The directory given by `\import` and its relative variants
+    dir: Option<(String, bool)>,
+    /// Whether only the document body of the file is inlined
+    subfile: bool,
+}
+
+struct Inliner<'a> {
+    /// The directory of the main file
+    root: PathBuf,
+    workspace: PathBuf,
+    read: &'a mut dyn FnMut(&Path) -> io::Result<String>,
+    /// The files being inlined
+    stack: Vec<PathBuf>,
+    res: Inlined,
+}
+
+impl Inliner<'_> {
+    /// Inline a range of a file, resolving the names it includes in `base`
+    fn inline(
+        &mut self,
+        path: PathBuf,
+        text: String,
+        range: Range<usize>,
+        base: PathBuf,
+    ) -> Result<(), IncludeError> {
+        self.stack.push(path.clone());
+        let file = self.res.files.len();
+        self.res.files.push((path.clone(), text));
+
+        let range = range.start..range.end.min(self.res.files[file].1.len());
+        let mut pos = range.start;
+        let mut scanner = Scanner {
+            text: &self.res.files[file].1[..range.end],
+            pos,
+        };
+        let mut inclusions = vec![];
+        while let Some(inclusion) = scanner.next_inclusion() {
+            inclusions.push(inclusion);
+        }
+        let end = scanner.pos;
+
+        for inclusion in inclusions {
+            self.copy(file, pos..inclusion.range.start);
+            pos = inclusion.range.end;
+
+            let dirs = match &inclusion.dir {
+                Some((dir, true)) => vec![normalize(&base.join(dir))],
+                Some((dir, false)) => vec![
+                    normalize(&self.root.join(dir)),
+                    normalize(&self.workspace.join(dir)),
+                ],
+                None => vec![base.clone(), self.workspace.clone()],
+            };
+            let Some((dir, found, text)) = self.find(&dirs, &inclusion.name)? else {
+                let (path, text) = &self.res.files[file];
+                return Err(IncludeError::Missing {
+                    name: inclusion.name,
+                    from: path.clone(),
+                    line_column: line_column(text, inclusion.range.start),
+                });
+            };
+            if self.stack.contains(&found) {
+                let mut chain = self.stack.clone();
+                chain.push(found);
+                return Err(IncludeError::Cycle { chain });
+            }
+
+            let (range, base) = if inclusion.subfile {
+                let base = found.parent().map(Path::to_owned).unwrap_or_default();
+                (document_body(&text), base)
+            } else if inclusion.dir.is_some() {
+                (0..text.len(), dir)
+            } else {
+                (0..text.len(), base.clone())
+            };
+            self.inline(found, text, range, base)?;
+        }
+        self.copy(file, pos..end);
+
+        self.stack.pop();
+        Ok(())
+    }
+
+    /// Copy a range of a file into the inlined text
+    fn copy(&mut self, file: usize, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        self.res.chunks.push(Chunk {
+            start: self.res.text.len(),
+            file,
+            file_start: range.start,
+        });
+        let text = &self.res.files[file].1[range];
+        self.res.text.push_str(text);
+    }
+
+    /// Find and read the file of a name in the first directory having it,
+    /// returning the directory, the path and the text
+    fn find(
+        &mut self,
+        dirs: &[PathBuf],
+        name: &str,
+    ) -> Result<Option<(PathBuf, PathBuf, String)>, IncludeError> {
+        let mut names = vec![];
+        if !name.ends_with(".tex") {
+            names.push(format!("{name}.tex"));
+        }
+        names.push(name.to_owned());
+        for dir in dirs {
+            for name in &names {
+                let path = normalize(&dir.join(name));
+                match (self.read)(&path) {
+                    Ok(text) => return Ok(Some((dir.clone(), path, text))),
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                    Err(error) => return Err(IncludeError::Io { path, error }),
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// A scanner for the commands including files in a LaTeX file, skipping
+/// comments and raw text
+struct Scanner<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    /// Find the next inclusion, stopping at the end of the text or at
+    /// `\endinput`
+    fn next_inclusion(&mut self) -> Option<Inclusion> {
+        while let Some(c) = self.text[self.pos..].chars().next() {
+            let start = self.pos;
+            self.pos += c.len_utf8();
+            match c {
+                '%' => self.skip_line(),
+                '\\' => {
+                    let name = self.command_name();
+                    match name {
+                        "endinput" => {
+                            self.pos = start;
+                            self.text = &self.text[..start];
+                            return None;
+                        }
+                        "verb" => self.skip_verb(),
+                        // URLs may hold `%`
+                        "url" | "href" => {
+                            self.group();
+                        }
+                        "begin" => self.skip_raw_env(),
+                        _ => {
+                            if let Some(mut inclusion) = self.inclusion(name) {
+                                inclusion.range.start = start;
+                                return Some(inclusion);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Parse the arguments of an inclusion command
+    fn inclusion(&mut self, name: &str) -> Option<Inclusion> {
+        let pos = self.pos;
+        let res = match name {
+            "input" | "include" | "subfile" => {
+                let file = match self.group() {
+                    Some(file) => file,
+                    // the primitive form `\input file`
+                    None if name == "input" => self.bare_name()?,
+                    None => return None,
+                };
+                Some((None, file))
+            }
+            "import" | "subimport" | "inputfrom" | "subinputfrom" | "includefrom"
+            | "subincludefrom" => {
+                self.eat('*');
+                let dir = self.group()?;
+                let file = self.group()?;
+                Some((Some((dir, name.starts_with("sub"))), file))
+            }
+            _ => None,
+        };
+        match res {
+            // names computed by macros are left to the converter
+            Some((dir, file)) if !file.contains(['#', '\\']) => Some(Inclusion {
+                range: 0..self.pos,
+                name: file,
+                dir,
+                subfile: name == "subfile",
+            }),
+            _ => {
+                self.pos = pos;
+                None
+            }
+        }
+    }
+
+    /// Get the name of a command after its backslash
+    fn command_name(&mut self) -> &'a str {
+        let rest = &self.text[self.pos..];
+        let len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let len = match (len, rest.chars().next()) {
+            (0, Some(c)) => c.len_utf8(),
+            _ => len,
+        };
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.text[self.pos..].starts_with(c);
+        if found {
+            self.pos += c.len_utf8();
+        }
+        found
+    }
+
+    fn skip_spaces(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start_matches([' ', '\t']).len();
+        if self.eat('\n') {
+            let rest = &self.text[self.pos..];
+            self.pos += rest.len() - rest.trim_start_matches([' ', '\t']).len();
+        }
+    }
+
+    fn skip_line(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.find('\n').unwrap_or(rest.len());
+    }
+
+    /// Get the trimmed content of a braced group
+    fn group(&mut self) -> Option<String> {
+        let pos = self.pos;
+        self.skip_spaces();
+        let Some(span) = group_span(self.text, self.pos, '{', '}') else {
+            self.pos = pos;
+            return None;
+        };
+        self.pos = span.end;
+        Some(self.text[span.start + 1..span.end - 1].trim().to_owned())
+    }
+
+    /// Get a file name ended by a space, e.g. `chapter` in `\input chapter`
+    fn bare_name(&mut self) -> Option<String> {
+        self.skip_spaces();
+        let rest = &self.text[self.pos..];
+        let len = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '%' | '{' | '}' | '\\'))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        (len > 0).then(|| rest[..len].to_owned())
+    }
+
+    /// Skip the argument of `\verb|x|`
+    fn skip_verb(&mut self) {
+        self.eat('*');
+        let rest = &self.text[self.pos..];
+        // as lexed, there is no argument after a letter or a space
+        let Some(delim) = rest.chars().next() else {
+            return;
+        };
+        if delim.is_alphabetic() || delim.is_whitespace() {
+            return;
+        }
+        let line = &rest[..rest.find(['\r', '\n']).unwrap_or(rest.len())];
+        let end = line
+            .get(delim.len_utf8()..)
+            .and_then(|line| line.find(delim));
+        if let Some(end) = end {
+            self.pos += end + 2 * delim.len_utf8();
+        }
+    }
+
+    /// Skip the content of a raw environment like `verbatim`
+    fn skip_raw_env(&mut self) {
+        let pos = self.pos;
+        let Some(name) = self.group() else {
+            return;
+        };
+        if !RAW_ENVS.contains(&name.as_str()) {
+            self.pos = pos;
+            return;
+        }
+        let end = format!("\\end{{{name}}}");
+        let rest = &self.text[self.pos..];
+        self.pos += rest.find(&end).map_or(rest.len(), |i| i + end.len());
+    }
+}
+
+/// Get the range of the document body of a file, or the whole file if it has
+/// no `document` environment
+fn document_body(text: &str) -> Range<usize> {
+    const BEGIN: &str = "\\begin{document}";
+    match text.find(BEGIN) {
+        Some(start) => {
+            let start = start + BEGIN.len();
+            let end = text[start..]
+                .find("\\end{document}")
+                .map_or(text.len(), |end| start + end);
+            start..end
+        }
+        None => 0..text.len(),
+    }
+}
+
+/// Get the 1-based line and column of a byte offset in a text
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+/// Normalize a path without accessing the file system, e.g. `a/c` for
+/// `./a/b/../c`
+fn normalize(path: &Path) -> PathBuf {
+    let mut res = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(res.components().next_back(), Some(Component::Normal(_))) =>
+            {
+                res.pop();
+            }
+            component => res.push(component),
+        }
+    }
+    res
+}
@@ -0,0 +1,36 @@
+//! Converting LaTeX lengths, e.g. `4pt` or `1.5ex`, into Typst lengths.
+
+/// Convert a LaTeX length like `4pt`, `-1.5ex` or `0.5\textwidth` into a
+/// Typst length, or `None` if the unit is unknown, e.g. a register like
+/// `\baselineskip`
+pub(crate) fn typst_length(text: &str) -> Option<String> {
+    let text = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '-' | '+')))
+        .unwrap_or(text.len());
+    let (value, unit) = text.split_at(split);
+    let value: f64 = match value {
+        "" | "+" => 1.0,
+        "-" => -1.0,
+        value => value.parse().ok()?,
+    };
+    let (scale, unit) = match unit {
+        // TeX points are 1/72.27 inch, close enough to the 1/72 of Typst
+        "pt" | "bp" => (1.0, "pt"),
+        "mm" | "cm" | "in" | "em" => (1.0, unit),
+        "ex" => (0.5, "em"),
+        "pc" => (12.0, "pt"),
+        "dd" => (1.07, "pt"),
+        "cc" => (12.84, "pt"),
+        "mu" => (1.0 / 18.0, "em"),
+        "\\jot" => (3.0, "pt"),
+        "\\textwidth" | "\\linewidth" | "\\columnwidth" | "\\hsize" => (100.0, "%"),
+        "\\textheight" | "\\vsize" => (100.0, "%"),
+        _ => return None,
+    };
+    let value = (value * scale * 1e4).round() / 1e4;
+    Some(format!("{value}{unit}"))
+}
@@ -1,22 +1,138 @@
+mod bibliography;
 mod converter;
+mod error;
+mod escape;
+mod front_matter;
+mod graphics;
+mod handler;
+mod include;
+mod length;
+mod options;
+mod qualified;
+mod source_map;
+mod standalone;
+mod tabular;
+mod theorem;
+mod tidy;
+mod unicode;
 
 pub use mitex_parser::command_preludes;
-use mitex_parser::parse;
-use mitex_parser::parse_without_macro;
+use mitex_parser::parse_with_span_map;
+use mitex_parser::parse_without_macro_with_span_map;
 pub use mitex_parser::spec::*;
+pub use mitex_parser::syntax;
+pub use rowan;
 
 use converter::convert_inner;
-use converter::LaTeXMode;
+pub use converter::Converted;
+pub use converter::LaTeXMode;
+pub use error::{ConvertError, ConvertErrorKind};
+pub use handler::{CommandHandler, EnvHandler, HandlerContext, HandlerError, Handlers};
+pub use include::{inline_includes, IncludeError, Inlined};
+pub use options::{ConvertOptions, ErrorPolicy, LabelPolicy, MathStyle, Target};
+pub use source_map::{SourceMap, SourceMapping};
 
-pub fn convert_text(input: &str, spec: Option<CommandSpec>) -> Result<String, String> {
-    convert_inner(input, LaTeXMode::Text, spec, parse)
+/// Converts LaTeX code into Typst code as configured by `options`.
+pub fn convert(input: &str, options: &ConvertOptions) -> Result<Converted, ConvertError> {
+    convert_inner(input, options, parse_with_span_map)
 }
 
-pub fn convert_math(input: &str, spec: Option<CommandSpec>) -> Result<String, String> {
-    convert_inner(input, LaTeXMode::Math, spec, parse)
+/// Options for `convert_*` functions taking an optional spec
+fn options_with_spec(mode: LaTeXMode, spec: Option<CommandSpec>) -> ConvertOptions {
+    let options = ConvertOptions::default().mode(mode);
+    match spec {
+        Some(spec) => options.spec(spec),
+        None => options,
+    }
+}
+
+pub fn convert_text(input: &str, spec: Option<CommandSpec>) -> Result<String, ConvertError> {
+    convert(input, &options_with_spec(LaTeXMode::Text, spec)).map(|c| c.output)
+}
+
+pub fn convert_math(input: &str, spec: Option<CommandSpec>) -> Result<String, ConvertError> {
+    convert(input, &options_with_spec(LaTeXMode::Math, spec)).map(|c| c.output)
+}
+
+/// Converts LaTeX code like [`convert_text`], converting the commands and
+/// environments registered in `handlers` by them.
+pub fn convert_text_with_handlers(
+    input: &str,
+    spec: Option<CommandSpec>,
+    handlers: &Handlers,
+) -> Result<String, ConvertError> {
+    let options = options_with_spec(LaTeXMode::Text, spec).handlers(handlers.clone());
+    convert(input, &options).map(|c| c.output)
+}
+
+/// Converts a LaTeX equation like [`convert_math`], converting the commands
+/// and environments registered in `handlers` by them.
+pub fn convert_math_with_handlers(
+    input: &str,
+    spec: Option<CommandSpec>,
+    handlers: &Handlers,
+) -> Result<String, ConvertError> {
+    let options = options_with_spec(LaTeXMode::Math, spec).handlers(handlers.clone());
+    convert(input, &options).map(|c| c.output)
+}
+
+/// Converts LaTeX code like [`convert_text`], and also returns a
+/// [`SourceMap`] between the output and the input in
+/// [`Converted::source_map`].
+pub fn convert_text_with_source_map(
+    input: &str,
+    spec: Option<CommandSpec>,
+) -> Result<Converted, ConvertError> {
+    convert(
+        input,
+        &options_with_spec(LaTeXMode::Text, spec).source_map(true),
+    )
+}
+
+/// Converts a LaTeX equation like [`convert_math`], and also returns a
+/// [`SourceMap`] between the output and the input in
+/// [`Converted::source_map`].
+pub fn convert_math_with_source_map(
+    input: &str,
+    spec: Option<CommandSpec>,
+) -> Result<Converted, ConvertError> {
+    convert(
+        input,
+        &options_with_spec(LaTeXMode::Math, spec).source_map(true),
+    )
+}
+
+/// Converts LaTeX code like [`convert_text`], but keeps converting on errors.
+///
+/// Each failing node is replaced by a red raw box showing its original LaTeX,
+/// and the error is collected in [`Converted::diagnostics`].
+pub fn convert_text_recovering(input: &str, spec: Option<CommandSpec>) -> Converted {
+    let options = options_with_spec(LaTeXMode::Text, spec).error_policy(ErrorPolicy::Recover);
+    convert(input, &options).unwrap_or_else(|err| Converted {
+        output: String::new(),
+        diagnostics: vec![err],
+        ..Default::default()
+    })
+}
+
+/// Converts a LaTeX equation like [`convert_math`], but keeps converting on
+/// errors.
+///
+/// See [`convert_text_recovering`] for details.
+pub fn convert_math_recovering(input: &str, spec: Option<CommandSpec>) -> Converted {
+    let options = options_with_spec(LaTeXMode::Math, spec).error_policy(ErrorPolicy::Recover);
+    convert(input, &options).unwrap_or_else(|err| Converted {
+        output: String::new(),
+        diagnostics: vec![err],
+        ..Default::default()
+    })
 }
 
 /// For internal testing
-pub fn convert_math_no_macro(input: &str, spec: Option<CommandSpec>) -> Result<String, String> {
-    convert_inner(input, LaTeXMode::Math, spec, parse_without_macro)
+pub fn convert_math_no_macro(
+    input: &str,
+    spec: Option<CommandSpec>,
+) -> Result<String, ConvertError> {
+    let options = options_with_spec(LaTeXMode::Math, spec);
+    convert_inner(input, &options, parse_without_macro_with_span_map).map(|c| c.output)
 }
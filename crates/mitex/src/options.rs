@@ -0,0 +1,221 @@
+//! Options of a conversion.
+
+use std::path::{Path, PathBuf};
+
+use mitex_parser::spec::CommandSpec;
+
+use crate::converter::LaTeXMode;
+use crate::handler::Handlers;
+
+/// How a conversion starting in math mode writes its formula.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MathStyle {
+    /// Math code without delimiters, e.g. `x `, to be put in a Typst equation
+    /// by the caller
+    #[default]
+    Bare,
+    /// An inline equation, e.g. `$x $`
+    Inline,
+    /// A display equation, e.g. `$ x  $`
+    Display,
+}
+
+/// How `\label` commands are converted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LabelPolicy {
+    /// Attach a Typst label to the content, e.g. `<name>`
+    #[default]
+    Attach,
+    /// Drop the labels
+    Drop,
+}
+
+/// What to do when a part of the input cannot be converted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Stop at the first error
+    #[default]
+    Abort,
+    /// Replace each failing node by a red raw box showing its original LaTeX,
+    /// and collect the error in [`Converted::diagnostics`]
+    ///
+    /// [`Converted::diagnostics`]: crate::Converted::diagnostics
+    Recover,
+}
+
+/// The Typst environment the output is written for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Target {
+    /// Typst evaluated with the scope of the MiTeX package, e.g. by `mitex`
+    /// or after `#import "@preview/mitex:0.2.7": *`
+    #[default]
+    MitexScope,
+    /// Plain Typst without the MiTeX package
+    ///
+    /// Native Typst is written where possible, e.g. `mat(delim: "(", ..)` for
+    /// `pmatrix`, and the helpers still needed are defined in
+    /// [`Converted::prelude`](crate::Converted::prelude).
+    Standalone,
+}
+
+/// Options of [`convert`](crate::convert), built by chaining its methods.
+///
+/// ```
+/// use mitex::{ConvertOptions, ErrorPolicy, LaTeXMode};
+///
+/// let options = ConvertOptions::default()
+///     .mode(LaTeXMode::Text)
+///     .wrap_inline_equations(false)
+///     .error_policy(ErrorPolicy::Recover);
+/// let res = mitex::convert(r#"a $x$ \unknown"#, &options).unwrap();
+/// assert_eq!(res.output, r#"a $x $ #text(fill: red, raw("\\unknown"));"#);
+/// assert_eq!(res.diagnostics.len(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConvertOptions {
+    pub(crate) mode: LaTeXMode,
+    pub(crate) math_style: MathStyle,
+    pub(crate) labels: LabelPolicy,
+    pub(crate) wrap_inline_equations: bool,
+    pub(crate) list_indent: usize,
+    pub(crate) readable: bool,
+    pub(crate) target: Target,
+    pub(crate) qualified: bool,
+    pub(crate) error_policy: ErrorPolicy,
+    pub(crate) source_map: bool,
+    pub(crate) base_dir: Option<PathBuf>,
+    pub(crate) spec: Option<CommandSpec>,
+    pub(crate) handlers: Handlers,
+}
+
+impl Default for ConvertOptions {
+    fn default() -> Self {
+        Self {
+            mode: LaTeXMode::Text,
+            math_style: MathStyle::Bare,
+            labels: LabelPolicy::Attach,
+            wrap_inline_equations: true,
+            list_indent: 2,
+            readable: false,
+            target: Target::MitexScope,
+            qualified: false,
+            error_policy: ErrorPolicy::Abort,
+            source_map: false,
+            base_dir: None,
+            spec: None,
+            handlers: Handlers::default(),
+        }
+    }
+}
+
+impl ConvertOptions {
+    /// Set the mode to start the conversion in, [`LaTeXMode::Text`] by
+    /// default.
+    pub fn mode(mut self, mode: LaTeXMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set how a conversion starting in math mode writes its formula,
+    /// [`MathStyle::Bare`] by default.
+    pub fn math_style(mut self, style: MathStyle) -> Self {
+        self.math_style = style;
+        self
+    }
+
+    /// Set how `\label` commands are converted, [`LabelPolicy::Attach`] by
+    /// default.
+    pub fn labels(mut self, labels: LabelPolicy) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Set whether inline formulas in text mode are wrapped in
+    /// `#math.equation(block: false, ..);` instead of written as `$..$`,
+    /// `true` by default.
+    pub fn wrap_inline_equations(mut self, wrap: bool) -> Self {
+        self.wrap_inline_equations = wrap;
+        self
+    }
+
+    /// Set the number of spaces to indent the items of nested lists, `2` by
+    /// default.
+    pub fn list_indent(mut self, width: usize) -> Self {
+        self.list_indent = width;
+        self
+    }
+
+    /// Set whether to write idiomatic Typst meant to be read and edited by
+    /// people, `false` by default.
+    ///
+    /// The readable output keeps numbers like `1.5` intact, writes upright
+    /// words like `\mathrm{max}` as strings, e.g. `upright("max")`, and
+    /// normalizes the spacing, line breaks and indentation of the code. It
+    /// renders the same as the default output, which is written for
+    /// machines and follows the structure of the input closely.
+    pub fn readable(mut self, readable: bool) -> Self {
+        self.readable = readable;
+        self
+    }
+
+    /// Set the Typst environment the output is written for,
+    /// [`Target::MitexScope`] by default.
+    pub fn target(mut self, target: Target) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Set whether to write fully qualified names in math, `false` by
+    /// default.
+    ///
+    /// Typst builtins are written as `math.frac` or `sym.alpha` instead of
+    /// `frac` or `alpha`, and the handlers of the MiTeX scope as
+    /// `mitex.mitexsqrt`, so that bindings of the document like
+    /// `#let alpha = ..` do not change the formulas. The output expects
+    /// `mitex` to be bound to `mitex-scope`, unless the target is
+    /// [`Target::Standalone`].
+    pub fn qualified(mut self, qualified: bool) -> Self {
+        self.qualified = qualified;
+        self
+    }
+
+    /// Set what to do when a part of the input cannot be converted,
+    /// [`ErrorPolicy::Abort`] by default.
+    pub fn error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.error_policy = policy;
+        self
+    }
+
+    /// Set whether to return a [`SourceMap`](crate::SourceMap) in
+    /// [`Converted::source_map`](crate::Converted::source_map), `false` by
+    /// default.
+    pub fn source_map(mut self, source_map: bool) -> Self {
+        self.source_map = source_map;
+        self
+    }
+
+    /// Set the directory of the document that the images of
+    /// `\includegraphics` are looked up in, none by default.
+    ///
+    /// The images are searched for in it and in the directories of
+    /// `\graphicspath`, and a name without an extension is given the one of
+    /// the file found, e.g. `fig` becomes `figs/fig.png`. Without the
+    /// directory, the names are written as they are.
+    pub fn base_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.base_dir = Some(dir.as_ref().to_owned());
+        self
+    }
+
+    /// Set the command specification, `DEFAULT_SPEC` by default.
+    pub fn spec(mut self, spec: CommandSpec) -> Self {
+        self.spec = Some(spec);
+        self
+    }
+
+    /// Set the handlers for commands and environments, the built-in ones by
+    /// default.
+    pub fn handlers(mut self, handlers: Handlers) -> Self {
+        self.handlers = handlers;
+        self
+    }
+}
@@ -0,0 +1,130 @@
+//! Fully qualified names of Typst builtins, which cannot be shadowed by the
+//! bindings of a document.
+
+/// Names of the `math` module that are not symbols, sorted.
+const MATH: &[&str] = &[
+    "Pr",
+    "abs",
+    "accent",
+    "arccos",
+    "arcsin",
+    "arctan",
+    "arg",
+    "attach",
+    "bb",
+    "binom",
+    "bold",
+    "cal",
+    "cancel",
+    "cases",
+    "ceil",
+    "class",
+    "cos",
+    "cosh",
+    "cot",
+    "coth",
+    "csc",
+    "csch",
+    "ctg",
+    "deg",
+    "det",
+    "dim",
+    "display",
+    "exp",
+    "floor",
+    "frac",
+    "frak",
+    "gcd",
+    "hom",
+    "id",
+    "im",
+    "inf",
+    "inline",
+    "italic",
+    "ker",
+    "lg",
+    "lim",
+    "liminf",
+    "limits",
+    "limsup",
+    "ln",
+    "log",
+    "lr",
+    "mat",
+    "max",
+    "med",
+    "mid",
+    "min",
+    "mod",
+    "mono",
+    "norm",
+    "op",
+    "overbrace",
+    "overbracket",
+    "overline",
+    "primes",
+    "quad",
+    "root",
+    "round",
+    "sans",
+    "script",
+    "scripts",
+    "sec",
+    "sech",
+    "serif",
+    "sin",
+    "sinc",
+    "sinh",
+    "sqrt",
+    "sscript",
+    "sup",
+    "tan",
+    "tanh",
+    "tg",
+    "thick",
+    "thin",
+    "underbrace",
+    "underbracket",
+    "underline",
+    "upright",
+    "vec",
+    "wide",
+];
+
+/// Accent functions of the `math` module, sorted. Most of them are also the
+/// names of symbols, e.g. `tilde` or `dot.c`, so they are functions only
+/// when called.
+const ACCENTS: &[&str] = &[
+    "acute", "arrow", "breve", "caron", "circle", "diaer", "dot", "grave", "harpoon", "hat",
+    "macron", "tilde",
+];
+
+/// The module holding a Typst name written in math, e.g. `math.` for `frac`
+/// and `sym.` for `alpha` or `arrow.r`, or `None` if the name is a
+/// shorthand like `->` or already qualified. An accent like `hat` or
+/// `dot.double` is in `math.` if it is `called` with arguments.
+pub(crate) fn math_module(name: &str, called: bool) -> Option<&'static str> {
+    let head = name.split(['.', '(']).next().unwrap_or(name);
+    let is_ident = head.starts_with(|c: char| c.is_ascii_alphabetic())
+        && head.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    if !is_ident || matches!(head, "math" | "sym" | "mitex") {
+        return None;
+    }
+    let accent = called && ACCENTS.binary_search(&head).is_ok();
+    Some(match MATH.binary_search(&head) {
+        Ok(_) => "math.",
+        Err(_) if accent => "math.",
+        Err(_) => "sym.",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorted() {
+        assert!(MATH.windows(2).all(|w| w[0] < w[1]));
+        assert!(ACCENTS.windows(2).all(|w| w[0] < w[1]));
+    }
+}
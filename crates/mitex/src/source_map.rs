@@ -0,0 +1,64 @@
+//! Mapping between the generated Typst code and the LaTeX code.
+
+use std::cmp::Reverse;
+use std::ops::Range;
+
+/// A range of the generated Typst code and the LaTeX code producing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceMapping {
+    /// Byte range in the output
+    pub output: Range<usize>,
+    /// Byte range in the input
+    pub input: Range<usize>,
+}
+
+/// A list of [`SourceMapping`]s, one for each syntax node that produced some
+/// output.
+///
+/// Mappings of nested nodes are nested, so an offset may be covered by several
+/// mappings. Lookups return the innermost one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    /// Sorted by output start, outer mappings first
+    mappings: Vec<SourceMapping>,
+}
+
+impl SourceMap {
+    pub(crate) fn new(mut mappings: Vec<SourceMapping>) -> Self {
+        mappings.sort_by_key(|m| {
+            (
+                m.output.start,
+                Reverse(m.output.end),
+                Reverse(m.input.len()),
+            )
+        });
+        // nested nodes like `{x}` may produce the same mapping twice
+        mappings.dedup();
+        Self { mappings }
+    }
+
+    /// All mappings, sorted by their start in the output.
+    pub fn mappings(&self) -> &[SourceMapping] {
+        &self.mappings
+    }
+
+    /// Find the input range producing the output at `output_offset`, e.g. to
+    /// translate a Typst compile error back to the LaTeX source.
+    pub fn to_input(&self, output_offset: usize) -> Option<Range<usize>> {
+        self.mappings
+            .iter()
+            .take_while(|m| m.output.start <= output_offset)
+            .filter(|m| output_offset < m.output.end)
+            .last()
+            .map(|m| m.input.clone())
+    }
+
+    /// Find the output range produced by the input at `input_offset`.
+    pub fn to_output(&self, input_offset: usize) -> Option<Range<usize>> {
+        self.mappings
+            .iter()
+            .filter(|m| m.input.contains(&input_offset))
+            .min_by_key(|m| (m.input.len(), m.output.len()))
+            .map(|m| m.output.clone())
+    }
+}
@@ -0,0 +1,834 @@
+//! Native Typst for the items of the MiTeX scope.
+//!
+//! The output of the converter refers to helpers of `mitex-scope`, defined in
+//! `packages/mitex/specs/latex/standard.typ`, e.g. `mitexsqrt` for `\sqrt`.
+//! For [`Target::Standalone`](crate::Target::Standalone), they are replaced by
+//! native Typst where possible, and otherwise defined by a prelude holding a
+//! copy of the helpers used. The table below must be kept in sync with the
+//! scope of the standard package, which the tests check.
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use ScopeItem::*;
+
+/// How an item of the MiTeX scope is written in standalone Typst.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ScopeItem {
+    /// The native Typst function of the same name, e.g. `frac`
+    Native,
+    /// Another native Typst function, e.g. `cancel` for `xcancel`
+    Rename(&'static str),
+    /// A native Typst function called with leading arguments, e.g. `mat`
+    /// with `delim: "("` for `pmatrix`
+    Call(&'static str, &'static str),
+    /// Typst math replacing a symbol, e.g. `#h((1/2) * 1em)` for `enspace`
+    Symbol(&'static str),
+    /// A helper defined in the prelude, and the helpers it uses
+    Helper(&'static str, &'static [&'static str]),
+}
+
+/// Get an item of the MiTeX scope by its name, returning the name as stored
+/// in the table.
+pub(crate) fn scope_item(name: &str) -> Option<(&'static str, ScopeItem)> {
+    let index = SCOPE.binary_search_by_key(&name, |(name, _)| name).ok()?;
+    Some(SCOPE[index])
+}
+
+/// Write the definitions of the helpers used by a standalone output,
+/// including the ones they depend on.
+pub(crate) fn write_prelude(used: &[String]) -> String {
+    fn visit(name: &str, seen: &mut BTreeSet<&'static str>, prelude: &mut String) {
+        let Some((name, Helper(definition, deps))) = scope_item(name) else {
+            return;
+        };
+        if !seen.insert(name) {
+            return;
+        }
+        for dep in deps {
+            visit(dep, seen, prelude);
+        }
+        let _ = writeln!(prelude, "#let {name} = {definition}");
+    }
+
+    let mut seen = BTreeSet::new();
+    let mut prelude = String::new();
+    for name in used {
+        visit(name, &mut seen, &mut prelude);
+    }
+    prelude
+}
+
+/// The items of the MiTeX scope, sorted by name
+const SCOPE: &[(&str, ScopeItem)] = &[
+    ("Big", Helper("it => math.lr(size: 1.8em, it)", &[])),
+    ("Bigg", Helper("it => math.lr(size: 3em, it)", &[])),
+    ("Bmatrix", Call("mat", r#"delim: "{""#)),
+    ("Bra", Helper("it => $math.lr(sym.chevron.l it|)$", &[])),
+    (
+        "Braket",
+        Helper("it => $math.lr(sym.chevron.l it sym.chevron.r)$", &[]),
+    ),
+    ("KaTeX", Symbol("#math.upright($K A T E X$)")),
+    ("Ket", Helper("it => $math.lr(|it sym.chevron.r)$", &[])),
+    ("LaTeX", Symbol("#math.upright($L A T E X$)")),
+    ("Set", Helper(r#"it => $math.lr(\{it\})$"#, &[])),
+    ("TeX", Symbol("#math.upright($T E X$)")),
+    ("Vmatrix", Call("mat", r#"delim: "||""#)),
+    (
+        "aligned",
+        Helper(
+            r#"(..args) => if args.pos().len() > 0 {
+  pad(y: 0.2em, block(math.op(math.display(..args))))
+} else {
+  math.zws
+}"#,
+            &[],
+        ),
+    ),
+    (
+        "alignedat",
+        Helper("(arg0: none, it) => pad(y: 0.2em, block(math.op(it)))", &[]),
+    ),
+    ("arcctg", Symbol(r#"#math.op("arcctg")"#)),
+    ("arctg", Symbol(r#"#math.op("arctg")"#)),
+    (
+        "atop",
+        Helper("(a, b) => $math.mat(delim: #none, #a; #b)$", &[]),
+    ),
+    ("bcancel", Call("cancel", "inverted: #true")),
+    ("big", Helper("it => math.lr(size: 1.2em, it)", &[])),
+    ("bigg", Helper("it => math.lr(size: 2.4em, it)", &[])),
+    ("binom", Native),
+    ("bmatrix", Call("mat", r#"delim: "[""#)),
+    (
+        "boxed",
+        Helper("it => box(stroke: 0.5pt, inset: 6pt, $it$)", &[]),
+    ),
+    ("bra", Helper("it => $sym.chevron.l it|$", &[])),
+    (
+        "brace",
+        Helper(r#"(n, k) => $math.mat(delim: "{", #n;; #k)$"#, &[]),
+    ),
+    (
+        "brack",
+        Helper(r#"(n, k) => $math.mat(delim: "[", #n;; #k)$"#, &[]),
+    ),
+    (
+        "braket",
+        Helper("it => $sym.chevron.l it sym.chevron.r$", &[]),
+    ),
+    (
+        "cfrac",
+        Helper("(num, den) => $math.display((num)/(den))$", &[]),
+    ),
+    ("ch", Symbol(r#"#math.op("ch")"#)),
+    (
+        "colortext",
+        Helper(
+            r#"(model, texcolor, body) => {
+  let color = get-tex-color(model, texcolor)
+  if color != none {
+    text(fill: color, body)
+  } else {
+    body
+  }
+}"#,
+            &["get-tex-color"],
+        ),
+    ),
+    ("cosec", Symbol(r#"#math.op("cosec")"#)),
+    ("cotg", Symbol(r#"#math.op("cotg")"#)),
+    ("cth", Symbol(r#"#math.op("cth")"#)),
+    (
+        "dbinom",
+        Helper("(n, k) => $math.display(math.binom(#n, #k))$", &[]),
+    ),
+    (
+        "dfrac",
+        Helper("(num, den) => $math.display((num)/(den))$", &[]),
+    ),
+    ("enspace", Symbol("#h((1/2) * 1em)")),
+    ("fbox", Helper("it => box(stroke: 0.5pt, $it$)", &[])),
+    ("frac", Native),
+    (
+        "get-tex-color",
+        Helper(
+            r##"(model, spec) => {
+  let model = if type(model) == content and model.has("text") {
+    model.text
+  } else if type(model) == str {
+    model
+  } else {
+    model
+  }
+
+  let s = if type(spec) == str {
+    spec
+  } else if type(spec) == content and spec.has("text") {
+    spec.text
+  } else if (
+    type(spec) == content and spec.has("children")
+  ) {
+    spec.children.map(it => if it.has("text") { it.text } else { "" }).join("")
+  } else {
+    ""
+  }
+
+  if model == none {
+    mitex-color-map.at(lower(s), default: none)
+  } else if model == "gray" {
+    luma(float(s) * 100%)
+  } else if model == "rgb" {
+    rgb(..s.split(",").map(x => float(x) * 100%))
+  } else if model == "RGB" {
+    rgb(..s.split(",").map(x => int(x)))
+  } else if model == "HTML" {
+    rgb("#" + s)
+  } else if model == "cmyk" {
+    cmyk(..s.split(",").map(x => float(x) * 100%))
+  } else {
+    none
+  }
+}"##,
+            &["mitex-color-map"],
+        ),
+    ),
+    (
+        "get-tex-str",
+        Helper(
+            r#"tex => if tex.has("children") {
+  get-tex-str-from-arr(tex.children)
+} else {
+  tex.text
+}"#,
+            &["get-tex-str-from-arr"],
+        ),
+    ),
+    (
+        "get-tex-str-from-arr",
+        Helper(
+            "arr => arr.filter(it => it != [ ] and it != [#math.zws]).map(it => it.text).sum()",
+            &[],
+        ),
+    ),
+    ("hbox", Helper("it => it", &[])),
+    ("hphantom", Helper("it => box(height: 0pt, hide(it))", &[])),
+    (
+        "hspace",
+        Helper("it => h(eval(get-tex-str(it)))", &["get-tex-str"]),
+    ),
+    (
+        "injlim",
+        Symbol(r#"#math.op("inj\u{2009}lim", limits: true)"#),
+    ),
+    ("ket", Helper("it => $|it sym.chevron.r$", &[])),
+    ("lVert", Symbol(r#"#math.class("opening", "||")"#)),
+    ("lvert", Symbol(r#"#math.class("opening", "|")"#)),
+    ("mathbin", Helper(r#"it => math.class("binary", it)"#, &[])),
+    ("mathclap", Helper("it => box(width: 0pt, $it$)", &[])),
+    (
+        "mathclose",
+        Helper(r#"it => math.class("closing", it)"#, &[]),
+    ),
+    ("mathinner", Helper(r#"it => math.class("fence", it)"#, &[])),
+    ("mathop", Helper(r#"it => math.class("unary", it)"#, &[])),
+    (
+        "mathopen",
+        Helper(r#"it => math.class("opening", it)"#, &[]),
+    ),
+    ("mathord", Helper(r#"it => math.class("normal", it)"#, &[])),
+    (
+        "mathpunct",
+        Helper(r#"it => math.class("punctuation", it)"#, &[]),
+    ),
+    (
+        "mathrel",
+        Helper(r#"it => math.class("relation", it)"#, &[]),
+    ),
+    ("mathring", Helper("it => math.circle(it)", &[])),
+    (
+        "mathscr",
+        Helper(
+            r#"it => {
+  let s = get-tex-str(it)
+  s.clusters().map(x => $scr(#x)$).join()
+}"#,
+            &["get-tex-str"],
+        ),
+    ),
+    ("matrix", Call("mat", "delim: #none")),
+    ("middle", Helper("it => math.mid(it)", &[])),
+    (
+        "mitex-color-map",
+        Helper(
+            r#"(
+  "red": rgb(255, 0, 0),
+  "green": rgb(0, 255, 0),
+  "blue": rgb(0, 0, 255),
+  "cyan": rgb(0, 255, 255),
+  "magenta": rgb(255, 0, 255),
+  "yellow": rgb(255, 255, 0),
+  "black": rgb(0, 0, 0),
+  "white": rgb(255, 255, 255),
+  "gray": rgb(128, 128, 128),
+  "lightgray": rgb(192, 192, 192),
+  "darkgray": rgb(64, 64, 64),
+  "brown": rgb(165, 42, 42),
+  "orange": rgb(255, 165, 0),
+  "pink": rgb(255, 182, 193),
+  "purple": rgb(128, 0, 128),
+  "teal": rgb(0, 128, 128),
+  "olive": rgb(128, 128, 0),
+)"#,
+            &[],
+        ),
+    ),
+    (
+        "mitexarray",
+        Helper(
+            r#"(arg0: ("l",), ..args) => {
+  if args.pos().len() == 0 {
+    return
+  }
+  if type(arg0) != str {
+    if arg0.has("children") {
+      arg0 = arg0.children.filter(it => it != [ ] and it != [#math.zws])
+        .map(it => it.text)
+        .filter(it => it == "l" or it == "c" or it == "r")
+    } else {
+      arg0 = (arg0.text,)
+    }
+  }
+  let matrix = args.pos().map(row => if type(row) == array { row } else { (row,) } )
+  let n = matrix.len()
+  let m = calc.max(..matrix.map(row => row.len()))
+  matrix = matrix.map(row => row + (m - row.len()) * (none,))
+  let array-at(arr, pos) = {
+    arr.at(calc.min(pos, arr.len() - 1))
+  }
+  let align-map = ("l": left, "c": center, "r": right)
+  set align(align-map.at(array-at(arg0, 0)))
+  pad(y: 0.2em, grid(
+    columns: m,
+    column-gutter: 0.5em,
+    row-gutter: 0.5em,
+    ..matrix.flatten().map(it => $it$)
+  ))
+}"#,
+            &[],
+        ),
+    ),
+    (
+        "mitexbold",
+        Helper("(..args) => math.bold(math.upright(args.pos().sum()))", &[]),
+    ),
+    (
+        "mitexcal",
+        Helper("(..args) => math.cal(args.pos().sum())", &[]),
+    ),
+    ("mitexcaption", Helper("(..args) => none", &[])),
+    (
+        "mitexcite",
+        Helper("it => cite(label(get-tex-str(it)))", &["get-tex-str"]),
+    ),
+    (
+        "mitexcolor",
+        Helper(
+            r#"(model, texcolor, ..args) => {
+  let color = get-tex-color(model, texcolor)
+  if color != none {
+    text(fill: color, args.pos().sum())
+  } else {
+    args.pos().sum()
+  }
+}"#,
+            &["get-tex-color"],
+        ),
+    ),
+    (
+        "mitexcolorbox",
+        Helper(
+            r#"(model, texcolor, body) => {
+  let color = get-tex-color(model, texcolor)
+  if color != none {
+    box(fill: color, inset: (x: 3pt), outset: (y: 3pt), radius: 2pt, body)
+  } else {
+    body
+  }
+}"#,
+            &["get-tex-color"],
+        ),
+    ),
+    (
+        "mitexdisplay",
+        Helper("(..args) => math.display(args.pos().sum())", &[]),
+    ),
+    (
+        "mitexfrak",
+        Helper("(..args) => math.frak(args.pos().sum())", &[]),
+    ),
+    ("miteximage", Helper("(..args) => none", &[])),
+    (
+        "mitexinline",
+        Helper("(..args) => math.inline(args.pos().sum())", &[]),
+    ),
+    (
+        "mitexitalic",
+        Helper("(..args) => math.italic(args.pos().sum())", &[]),
+    ),
+    ("mitexlabel", Helper("(..args) => none", &[])),
+    (
+        "mitexmathbf",
+        Helper("it => math.bold(math.upright(it))", &[]),
+    ),
+    (
+        "mitexmono",
+        Helper("(..args) => math.mono(args.pos().sum())", &[]),
+    ),
+    (
+        "mitexnot",
+        Helper("it => math.cancel(angle: 20deg, it)", &[]),
+    ),
+    (
+        "mitexoverbrace",
+        Helper("it => math.limits(math.overbrace(it))", &[]),
+    ),
+    (
+        "mitexoverbracket",
+        Helper("it => math.limits(math.overbracket(it))", &[]),
+    ),
+    (
+        "mitexref",
+        Helper("it => ref(label(get-tex-str(it)))", &["get-tex-str"]),
+    ),
+    (
+        "mitexsans",
+        Helper("(..args) => math.sans(args.pos().sum())", &[]),
+    ),
+    (
+        "mitexscript",
+        Helper("(..args) => math.script(args.pos().sum())", &[]),
+    ),
+    (
+        "mitexsqrt",
+        Helper(
+            r#"(..args) => {
+  if args.pos().len() == 1 {
+    $sqrt(#args.pos().at(0))$
+  } else if args.pos().len() == 2 {
+    $root(
+      #args.pos().at(0).children.filter(it => it != [\[] and it != [\]]).sum(),
+      #args.pos().at(1)
+    )$
+  } else {
+    panic("unexpected args in sqrt")
+  }
+}"#,
+            &[],
+        ),
+    ),
+    (
+        "mitexsscript",
+        Helper("(..args) => math.sscript(args.pos().sum())", &[]),
+    ),
+    (
+        "mitexunderbrace",
+        Helper("it => math.limits(math.underbrace(it))", &[]),
+    ),
+    (
+        "mitexunderbracket",
+        Helper("it => math.limits(math.underbracket(it))", &[]),
+    ),
+    (
+        "mitexupright",
+        Helper("(..args) => math.upright(args.pos().sum())", &[]),
+    ),
+    ("negmedspace", Symbol("#h(-(4/18) * 1em)")),
+    ("negthickspace", Symbol("#h(-(5/18) * 1em)")),
+    ("negthinmedspace", Symbol("#h(-(3/18) * 1em)")),
+    ("negthinspace", Symbol("#h(-(3/18) * 1em)")),
+    ("ngeqq", Symbol(r#"#math.cancel(angle: 20deg, "\u{2267}")"#)),
+    ("nleqq", Symbol(r#"#math.cancel(angle: 20deg, "\u{2266}")"#)),
+    (
+        "nleqslant",
+        Symbol("#math.cancel(angle: 20deg, length: 1em, math.lt.eq.slant)"),
+    ),
+    (
+        "nsubseteqq",
+        Symbol(r#"#math.cancel(angle: 20deg, length: 1em, "\u{2AC5}")"#),
+    ),
+    (
+        "nsupseteqq",
+        Symbol(r#"#math.cancel(angle: 20deg, length: 1em, "\u{2AC6}")"#),
+    ),
+    (
+        "operatorname",
+        Helper("it => math.op(math.upright(it))", &[]),
+    ),
+    (
+        "operatornamewithlimits",
+        Helper("it => math.op(limits: true, math.upright(it))", &[]),
+    ),
+    (
+        "overgroup",
+        Helper(r#"it => $math.accent(it, \u{0311})$"#, &[]),
+    ),
+    (
+        "overleftharpoon",
+        Helper(r#"it => $math.accent(it, \u{20d0})$"#, &[]),
+    ),
+    (
+        "overleftrightarrow",
+        Helper(r#"it => $math.accent(it, \u{20e1})$"#, &[]),
+    ),
+    (
+        "overlinesegment",
+        Helper(r#"it => $math.accent(it, \u{20e9})$"#, &[]),
+    ),
+    (
+        "overrightharpoon",
+        Helper(r#"it => $math.accent(it, \u{20d1})$"#, &[]),
+    ),
+    (
+        "overset",
+        Helper("(sup, base) => $math.limits(base)^(sup)$", &[]),
+    ),
+    ("phantom", Helper("it => hide(it)", &[])),
+    ("plim", Symbol(r#"#math.op("plim", limits: true)"#)),
+    ("pmatrix", Call("mat", r#"delim: "(""#)),
+    (
+        "pmod",
+        Helper("it => $math.quad (math.mod math.thick it)$", &[]),
+    ),
+    ("pod", Helper("it => $math.quad (it)$", &[])),
+    (
+        "projlim",
+        Symbol(r#"#math.op("proj\u{2009}lim", limits: true)"#),
+    ),
+    ("rVert", Symbol(r#"#math.class("closing", "||")"#)),
+    (
+        "raisebox",
+        Helper(
+            "(sp, it) => text(baseline: -eval(get-tex-str(sp)), it)",
+            &["get-tex-str"],
+        ),
+    ),
+    ("rcases", Call("cases", "reverse: #true")),
+    ("rvert", Symbol(r#"#math.class("closing", "|")"#)),
+    ("set", Helper(r#"it => $\{it\}$"#, &[])),
+    ("sh", Symbol(r#"#math.op("sh")"#)),
+    ("smallint", Symbol("inline(integral)")),
+    ("smallmatrix", Call("mat", "delim: #none")),
+    (
+        "smash",
+        Helper("it => box(height: 0pt, align(bottom, $#it$))", &[]),
+    ),
+    ("sout", Call("cancel", "angle: #90deg")),
+    (
+        "stackrel",
+        Helper("(sup, base) => $math.limits(base)^(sup)$", &[]),
+    ),
+    ("substack", Helper("it => it", &[])),
+    (
+        "tbinom",
+        Helper("(n, k) => $math.inline(math.binom(#n, #k))$", &[]),
+    ),
+    ("textbf", Rename("math.bold")),
+    ("textit", Rename("math.italic")),
+    ("textmath", Helper("it => it", &[])),
+    ("textmd", Helper("it => it", &[])),
+    ("textnormal", Helper("it => it", &[])),
+    ("textrm", Rename("math.upright")),
+    ("textsf", Rename("math.sans")),
+    ("texttt", Rename("math.mono")),
+    ("textup", Rename("math.upright")),
+    (
+        "tfrac",
+        Helper("(num, den) => $math.inline((num)/(den))$", &[]),
+    ),
+    ("th", Symbol(r#"#math.op("th")"#)),
+    ("thickapprox", Symbol("bold(approx)")),
+    ("thicksim", Symbol("bold(tilde)")),
+    ("underbar", Helper("it => $math.underline(it)$", &[])),
+    (
+        "undergroup",
+        Helper(r#"it => $math.accent(it, \u{032e})$"#, &[]),
+    ),
+    (
+        "underset",
+        Helper("(sub, base) => $math.limits(base)_(sub)$", &[]),
+    ),
+    ("varDelta", Symbol("italic(Delta)")),
+    ("varGamma", Symbol("italic(Gamma)")),
+    ("varLambda", Symbol("italic(Lambda)")),
+    ("varOmega", Symbol("italic(Omega)")),
+    ("varPhi", Symbol("italic(Phi)")),
+    ("varPi", Symbol("italic(Pi)")),
+    ("varPsi", Symbol("italic(Psi)")),
+    ("varSigma", Symbol("italic(Sigma)")),
+    ("varTheta", Symbol("italic(Theta)")),
+    ("varUpsilon", Symbol("italic(Upsilon)")),
+    ("varXi", Symbol("italic(Xi)")),
+    ("vmatrix", Call("mat", r#"delim: "|""#)),
+    ("vphantom", Helper("it => box(width: 0pt, hide(it))", &[])),
+    (
+        "vspace",
+        Helper("it => v(eval(get-tex-str(it)))", &["get-tex-str"]),
+    ),
+    (
+        "xLeftarrow",
+        Helper(
+            "it => $math.limits(math.stretch(#math.arrow.l.double)^#it)$",
+            &[],
+        ),
+    ),
+    (
+        "xLeftrightarrow",
+        Helper(
+            "it => $math.limits(math.stretch(#math.arrow.l.r.double)^#it)$",
+            &[],
+        ),
+    ),
+    (
+        "xRightarrow",
+        Helper(
+            "it => $math.limits(math.stretch(#math.arrow.r.double)^#it)$",
+            &[],
+        ),
+    ),
+    ("xcancel", Rename("cancel")),
+    (
+        "xhookleftarrow",
+        Helper(
+            "it => $math.limits(math.stretch(#math.arrow.l.hook)^#it)$",
+            &[],
+        ),
+    ),
+    (
+        "xhookrightarrow",
+        Helper(
+            "it => $math.limits(math.stretch(#math.arrow.r.hook)^#it)$",
+            &[],
+        ),
+    ),
+    (
+        "xleftarrow",
+        Helper("it => $math.limits(math.stretch(#math.arrow.l)^#it)$", &[]),
+    ),
+    (
+        "xleftharpoondown",
+        Helper(
+            "it => $math.limits(math.stretch(#math.harpoon.lb)^#it)$",
+            &[],
+        ),
+    ),
+    (
+        "xleftharpoonup",
+        Helper(
+            "it => $math.limits(math.stretch(#math.harpoon.lt)^#it)$",
+            &[],
+        ),
+    ),
+    (
+        "xleftrightarrow",
+        Helper(
+            "it => $math.limits(math.stretch(#math.arrow.l.r)^#it)$",
+            &[],
+        ),
+    ),
+    (
+        "xleftrightharpoons",
+        Helper(
+            "it => $math.limits(math.stretch(#math.harpoons.ltrb)^#it)$",
+            &[],
+        ),
+    ),
+    (
+        "xlongequal",
+        Helper("it => $math.limits(math.stretch(#math.eq)^#it)$", &[]),
+    ),
+    (
+        "xmapsto",
+        Helper(
+            "it => $math.limits(math.stretch(#math.arrow.r.bar)^#it)$",
+            &[],
+        ),
+    ),
+    (
+        "xrightarrow",
+        Helper("it => $math.limits(math.stretch(#math.arrow.r)^#it)$", &[]),
+    ),
+    (
+        "xrightharpoondown",
+        Helper(
+            "it => $math.limits(math.stretch(#math.harpoon.rb)^#it)$",
+            &[],
+        ),
+    ),
+    (
+        "xrightharpoonup",
+        Helper(
+            "it => $math.limits(math.stretch(#math.harpoon.rt)^#it)$",
+            &[],
+        ),
+    ),
+    (
+        "xrightleftharpoons",
+        Helper(
+            "it => $math.limits(math.stretch(#math.harpoons.rtlb)^#it)$",
+            &[],
+        ),
+    ),
+    (
+        "xtofrom",
+        Helper(
+            "it => $math.limits(math.stretch(#math.arrows.rl)^#it)$",
+            &[],
+        ),
+    ),
+    (
+        "xtwoheadleftarrow",
+        Helper(
+            "it => $math.limits(math.stretch(#math.arrow.l.twohead)^#it)$",
+            &[],
+        ),
+    ),
+    (
+        "xtwoheadrightarrow",
+        Helper(
+            "it => $math.limits(math.stretch(#math.arrow.r.twohead)^#it)$",
+            &[],
+        ),
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConvertOptions, ErrorPolicy, LaTeXMode};
+
+    /// The standard package, whose definitions with a `handle` make up the
+    /// MiTeX scope
+    const STANDARD: &str = include_str!("../../../packages/mitex/specs/latex/standard.typ");
+
+    /// The definitions of the standard package with a scope item, along with
+    /// the name `process-spec` of `packages/mitex/specs/prelude.typ` keys it
+    /// by, e.g. `("define-cmd", "sqrt", "mitexsqrt")`
+    fn standard_definitions() -> Vec<(String, String, String)> {
+        let (_, definitions) = STANDARD.split_once("= process-spec((\n").unwrap();
+        let (definitions, _) = definitions.split_once("\n))").unwrap();
+
+        // each definition starts at the indentation of the dictionary
+        let mut entries: Vec<(String, String)> = vec![];
+        for line in definitions.lines() {
+            let key = line
+                .strip_prefix("  ")
+                .filter(|line| !line.starts_with([' ', '/']))
+                .and_then(|line| line.split_once(": "));
+            match key {
+                Some((key, body)) => entries.push((key.trim_matches('"').into(), body.into())),
+                None => {
+                    if let Some((_, body)) = entries.last_mut() {
+                        body.push_str(line);
+                    }
+                }
+            }
+        }
+
+        let strings = |body: &str| -> Vec<String> {
+            body.split('"')
+                .skip(1)
+                .step_by(2)
+                .map(str::to_owned)
+                .collect()
+        };
+        let mut definitions = vec![];
+        for (key, body) in entries {
+            let (func, args) = body.split_once('(').unwrap_or((&body, ""));
+            let handled = args.contains("handle:");
+            let name = match func {
+                "define-sym" if args.contains("sym:") => strings(args).swap_remove(0),
+                "of-sym" | "arrow-handle" | "matrix-handle" => key.clone(),
+                "greedy-handle" | "limits-handle" => strings(args).swap_remove(0),
+                "define-greedy-cmd" | "define-infix-cmd" if handled => strings(args).swap_remove(0),
+                "define-glob-cmd" if handled => strings(args).swap_remove(1),
+                "define-cmd" | "define-env" | "define-glob-env" if handled => {
+                    match args.split_once("alias: \"") {
+                        Some((_, alias)) => alias.split('"').next().unwrap().into(),
+                        None => key.clone(),
+                    }
+                }
+                _ => continue,
+            };
+            let name = name.trim_start_matches('#').to_owned();
+            definitions.push((func.to_owned(), key, name));
+        }
+        definitions
+    }
+
+    fn standard_scope() -> Vec<String> {
+        standard_definitions()
+            .into_iter()
+            .map(|(_, _, name)| name)
+            .collect()
+    }
+
+    #[test]
+    fn sorted() {
+        assert!(SCOPE.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn synced() {
+        let scope = standard_scope();
+        assert!(scope.len() > 100);
+        let missing = scope
+            .iter()
+            .filter(|name| scope_item(name).is_none())
+            .collect::<Vec<_>>();
+        assert!(missing.is_empty(), "missing in SCOPE: {missing:?}");
+    }
+
+    #[test]
+    fn emitted() {
+        let scope = standard_scope();
+        for (func, key, _) in standard_definitions() {
+            let input = match func.as_str() {
+                "define-env" | "define-glob-env" | "matrix-handle" => {
+                    format!("\\begin{{{key}}}a\\end{{{key}}}")
+                }
+                _ => format!("\\{key}{{a}}{{b}}"),
+            };
+            for mode in [LaTeXMode::Math, LaTeXMode::Text] {
+                let options = ConvertOptions::default()
+                    .spec(mitex_spec_gen::DEFAULT_SPEC.clone())
+                    .mode(mode)
+                    .error_policy(ErrorPolicy::Recover);
+                let converted = crate::convert(&input, &options).unwrap();
+                // the names of the scope written by the output are bound by
+                // the prelude, so they are to be recorded with their items,
+                // while in text mode only the names after `#` are evaluated
+                let output = converted.output.as_str();
+                let words = match mode {
+                    LaTeXMode::Math => output,
+                    LaTeXMode::Text => output.split_once('#').map_or("", |(_, code)| code),
+                };
+                let written = words
+                    .split(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+                    .filter(|word| scope.iter().any(|name| name == word))
+                    .collect::<BTreeSet<_>>();
+                for name in written {
+                    assert!(
+                        converted.aliases.iter().any(|alias| alias == name),
+                        "{name} written for {input} is not recorded: {}",
+                        converted.output
+                    );
+                }
+                for alias in &converted.aliases {
+                    assert!(scope_item(alias).is_some(), "{alias} has no item");
+                }
+            }
+        }
+    }
+}
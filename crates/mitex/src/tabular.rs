@@ -0,0 +1,143 @@
+//! Column specifications of tabular environments, e.g. `|l|p{3cm}|`.
+
+use mitex_lexer::group_span;
+
+use crate::error::{ConvertError, ConvertErrorKind};
+use crate::length::typst_length;
+
+/// A column of a table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Column {
+    /// The Typst alignment of its cells, e.g. `left` or `left + top`
+    pub align: String,
+    /// The Typst width of the column, e.g. `3cm` or `1fr`, or `None` for
+    /// `auto`
+    pub width: Option<String>,
+}
+
+/// The columns of a table and the vertical lines between them
+#[derive(Debug, Default)]
+pub(crate) struct Columns {
+    pub columns: Vec<Column>,
+    /// Positions of the vertical lines, where `0` is before the first column
+    pub vlines: Vec<usize>,
+}
+
+/// The most columns a table can have, bounding the work of repetitions like
+/// `*{99999}{c}` in untrusted input
+pub(crate) const MAX_COLUMNS: usize = 500;
+
+/// Parse a column specification like `|l|p{3cm}|`, without its braces,
+/// which starts at `offset` in the syntax tree.
+///
+/// `@{..}` and `!{..}` separators are dropped, and only `\centering`,
+/// `\raggedleft` and `\raggedright` of the `>{..}` declarations are kept.
+/// A specification of more than [`MAX_COLUMNS`] columns is an error.
+pub(crate) fn parse_columns(spec: &str, offset: usize) -> Result<Columns, ConvertError> {
+    let mut columns = Columns::default();
+    parse_into(&mut columns, spec, offset)?;
+    Ok(columns)
+}
+
+fn parse_into(cols: &mut Columns, spec: &str, offset: usize) -> Result<(), ConvertError> {
+    let bad = |pos: usize, ch: char| {
+        let start = offset + pos;
+        ConvertError::with_name(
+            ConvertErrorKind::BadTabularAlignment,
+            start..start + ch.len_utf8(),
+            ch,
+        )
+    };
+    // the horizontal alignment set by `>{..}` for the next column
+    let mut align = None;
+    let mut pos = 0;
+    while let Some(ch) = spec[pos..].chars().next() {
+        if cols.columns.len() > MAX_COLUMNS {
+            let range = offset + pos..offset + spec.len();
+            Err(ConvertError::new(ConvertErrorKind::TooManyColumns, range))?
+        }
+        let next = pos + ch.len_utf8();
+        pos = match ch {
+            'l' | 'c' | 'r' => {
+                let default = match ch {
+                    'l' => "left",
+                    'c' => "center",
+                    _ => "right",
+                };
+                cols.columns.push(Column {
+                    align: align.take().unwrap_or(default).to_owned(),
+                    width: None,
+                });
+                next
+            }
+            'p' | 'm' | 'b' => {
+                let (width, _, end) = group(spec, next).ok_or_else(|| bad(pos, ch))?;
+                let vertical = match ch {
+                    'p' => "top",
+                    'm' => "horizon",
+                    _ => "bottom",
+                };
+                let horizontal = align.take().unwrap_or("left");
+                cols.columns.push(Column {
+                    align: format!("{horizontal} + {vertical}"),
+                    width: typst_length(width),
+                });
+                end
+            }
+            'X' => {
+                cols.columns.push(Column {
+                    align: align.take().unwrap_or("left").to_owned(),
+                    width: Some("1fr".to_owned()),
+                });
+                next
+            }
+            '|' => {
+                let x = cols.columns.len();
+                if cols.vlines.last() != Some(&x) {
+                    cols.vlines.push(x);
+                }
+                next
+            }
+            '>' => {
+                let (decl, _, end) = group(spec, next).ok_or_else(|| bad(pos, ch))?;
+                if decl.contains("\\centering") {
+                    align = Some("center");
+                } else if decl.contains("\\raggedleft") {
+                    align = Some("right");
+                } else if decl.contains("\\raggedright") {
+                    align = Some("left");
+                }
+                end
+            }
+            '<' | '@' | '!' => group(spec, next).ok_or_else(|| bad(pos, ch))?.2,
+            '*' => {
+                let (count, _, end) = group(spec, next).ok_or_else(|| bad(pos, ch))?;
+                let count = count.trim().parse::<usize>().map_err(|_| bad(pos, ch))?;
+                let (body, body_start, end) = group(spec, end).ok_or_else(|| bad(pos, ch))?;
+                for _ in 0..count {
+                    let len = cols.columns.len();
+                    parse_into(cols, body, offset + body_start)?;
+                    // a body without columns, like `|`, is the same repeated
+                    if cols.columns.len() == len {
+                        break;
+                    }
+                }
+                end
+            }
+            _ if ch.is_whitespace() => next,
+            _ => return Err(bad(pos, ch)),
+        };
+    }
+    Ok(())
+}
+
+/// Get the content of the braced group starting at `pos` after optional
+/// spaces, along with the start of the content and the end of the group
+fn group(spec: &str, pos: usize) -> Option<(&str, usize, usize)> {
+    let span = group_span(spec, pos, '{', '}')?;
+    Some((
+        &spec[span.start + 1..span.end - 1],
+        span.start + 1,
+        span.end,
+    ))
+}
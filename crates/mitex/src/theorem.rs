@@ -0,0 +1,254 @@
+//! Theorem environments defined by `\newtheorem`.
+
+use core::fmt;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use mitex_parser::spec::*;
+use mitex_parser::syntax::{CmdItem, EnvItem, SyntaxKind as LatexSyntaxKind, SyntaxNode};
+use rowan::ast::AstNode;
+
+use crate::converter::{
+    argument_elems, argument_text, is_blank, only_cmd, Converter, Error, LaTeXEnv,
+};
+use crate::escape::write_string;
+use crate::front_matter::plain_text;
+
+/// A theorem environment defined by `\newtheorem`
+#[derive(Debug, Clone)]
+pub(crate) struct Theorem {
+    // the environment whose counter numbers the theorem, also the kind of its
+    // figure
+    kind: String,
+    supplement: String,
+    // `plain`, `definition` or `remark` of `\theoremstyle`
+    style: &'static str,
+    // the level of the headings resetting the counter, or 0
+    within: usize,
+    numbered: bool,
+}
+
+/// The theorem environments of a document, in the order of definition
+#[derive(Debug, Default)]
+pub(crate) struct Theorems {
+    envs: Vec<(String, Theorem)>,
+    // the current `\theoremstyle`
+    style: Option<&'static str>,
+}
+
+impl Theorems {
+    /// Collect `\newtheorem` and `\theoremstyle` among some nodes
+    pub(crate) fn collect(&mut self, nodes: impl Iterator<Item = SyntaxNode>) {
+        for cmd in nodes.filter_map(CmdItem::cast) {
+            let name = cmd.name_tok().unwrap();
+            let numbered = match name.text() {
+                "\\newtheorem" => true,
+                "\\newtheorem*" => false,
+                "\\theoremstyle" => {
+                    let style = cmd.arguments().last().map(|arg| argument_text(&arg));
+                    self.style = match style.as_deref() {
+                        Some("definition") => Some("definition"),
+                        Some("remark") => Some("remark"),
+                        _ => None,
+                    };
+                    continue;
+                }
+                _ => continue,
+            };
+            // `{env}[counter]{supplement}[within]`
+            let (mut braced, mut counter, mut within) = (vec![], None, None);
+            for arg in cmd.arguments() {
+                let text = argument_text(&arg);
+                let option = text.strip_prefix('[').and_then(|t| t.strip_suffix(']'));
+                match (option, braced.len()) {
+                    (Some(option), 1) => counter = Some(option.trim().to_owned()),
+                    (Some(option), _) => within = Some(option.trim().to_owned()),
+                    (None, _) => braced.push(arg),
+                }
+            }
+            let [env, supplement] = braced.as_slice() else {
+                continue;
+            };
+            let env = argument_text(env);
+            let shared = counter.and_then(|counter| self.get(&counter).cloned());
+            let within = match within.as_deref() {
+                Some("chapter" | "section") => 1,
+                Some("subsection") => 2,
+                Some("subsubsection") => 3,
+                _ => 0,
+            };
+            let theorem = Theorem {
+                kind: shared
+                    .as_ref()
+                    .map_or_else(|| env.clone(), |t| t.kind.clone()),
+                supplement: plain_text(argument_elems(supplement)),
+                style: self.style.unwrap_or("plain"),
+                within: shared.map_or(within, |t| t.within),
+                numbered,
+            };
+            self.envs.retain(|(name, _)| *name != env);
+            self.envs.push((env, theorem));
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.envs.is_empty()
+    }
+
+    pub(crate) fn get(&self, env: &str) -> Option<&Theorem> {
+        self.envs
+            .iter()
+            .find_map(|(name, theorem)| (name == env).then_some(theorem))
+    }
+}
+
+/// Typst function of the theorem environments, which are figures of the kind
+/// of their counters laid out by their styles
+const THEOREM_FUNC: &str = r#"#let mitex-theorem(name, title: none, body) = {
+  let (kind, supplement, style, within, numbered) = mitex-theorems.at(name)
+  let pattern = if within > 0 {
+    (..n) => {
+      let levels = (counter(heading).get() + (0,) * within).slice(0, within)
+      numbering("1.1", ..levels, ..n)
+    }
+  } else { "1" }
+  let number = if numbered [ #context counter(figure.where(kind: kind)).display(pattern)]
+  let head = [#supplement#number#if title != none [ (#title)].]
+  let body = if style == "plain" {
+    [#strong(head) #emph(body)]
+  } else if style == "definition" {
+    [#strong(head) #body]
+  } else {
+    [#emph(head) #body]
+  }
+  figure(
+    kind: kind, supplement: supplement, numbering: if numbered { pattern }, outlined: false,
+    block(width: 100%, align(left, body)),
+  )
+}
+"#;
+
+impl Converter<'_> {
+    /// Write the settings of the theorem environments, the function calling
+    /// them and the rules laying out and resetting their counters
+    pub(crate) fn write_theorem_definitions(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> Result<(), Error> {
+        f.write_str("#let mitex-theorems = (\n")?;
+        for (name, theorem) in &self.theorems.envs {
+            f.write_str("  \"")?;
+            write_string(f, name)?;
+            f.write_str("\": (kind: \"")?;
+            write_string(f, &theorem.kind)?;
+            f.write_str("\", supplement: \"")?;
+            write_string(f, &theorem.supplement)?;
+            writeln!(
+                f,
+                "\", style: \"{}\", within: {}, numbered: {}),",
+                theorem.style, theorem.within, theorem.numbered
+            )?;
+        }
+        f.write_str(")\n")?;
+        f.write_str(THEOREM_FUNC)?;
+        let mut kinds = BTreeMap::new();
+        for (_, theorem) in &self.theorems.envs {
+            let within = kinds.entry(theorem.kind.as_str()).or_insert(0);
+            *within = theorem.within.max(*within);
+        }
+        for (kind, within) in kinds {
+            f.write_str("#show figure.where(kind: \"")?;
+            write_string(f, kind)?;
+            f.write_str("\"): it => it.body\n")?;
+            if within > 0 {
+                write!(f, "#show heading: it => {{ if it.level <= {within} {{ ")?;
+                f.write_str("counter(figure.where(kind: \"")?;
+                write_string(f, kind)?;
+                f.write_str("\")).update(0) }; it }\n")?;
+            }
+        }
+        f.write_char('\n')?;
+        Ok(())
+    }
+
+    /// Convert theorem environments like `\begin{thm}[Fermat]` into
+    /// `#mitex-theorem("thm", title: [Fermat])[..]`, or `proof` without a
+    /// name into a block ended by a QED square
+    pub(crate) fn convert_env_theorem(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        env: &EnvItem,
+        spec: &CommandSpec,
+        name: Option<&str>,
+    ) -> Result<(), Error> {
+        // the title is an optional argument like `[Fermat]`, and the text is
+        // split into tokens to trim the spaces around the body
+        let mut children = env
+            .syntax()
+            .children_with_tokens()
+            .flat_map(|child| match child.kind() {
+                LatexSyntaxKind::ItemText => {
+                    child.as_node().unwrap().children_with_tokens().collect()
+                }
+                LatexSyntaxKind::ItemBegin | LatexSyntaxKind::ItemEnd => vec![],
+                _ => vec![child],
+            })
+            .peekable();
+        let mut title = None;
+        if children
+            .next_if(|child| child.kind() == LatexSyntaxKind::TokenLBracket)
+            .is_some()
+        {
+            let mut elems = vec![];
+            let mut depth = 0;
+            for child in children.by_ref() {
+                match child.kind() {
+                    LatexSyntaxKind::TokenLBracket => depth += 1,
+                    LatexSyntaxKind::TokenRBracket if depth == 0 => break,
+                    LatexSyntaxKind::TokenRBracket => depth -= 1,
+                    _ => {}
+                }
+                elems.push(child);
+            }
+            title = Some(elems);
+        }
+
+        let prev = self.enter_env(LaTeXEnv::Theorem);
+        // labels before the body are kept for the theorem
+        while let Some(child) = children.next_if(|child| {
+            is_blank(child) || only_cmd(std::slice::from_ref(child), "\\label").is_some()
+        }) {
+            if !is_blank(&child) {
+                self.convert(f, child, spec)?;
+            }
+        }
+        let body = children.collect();
+        match name {
+            Some(name) => {
+                f.write_str("#mitex-theorem(\"")?;
+                write_string(f, name)?;
+                f.write_char('"')?;
+                if let Some(title) = title {
+                    f.write_str(", title: [")?;
+                    self.convert_elems(f, title, spec)?;
+                    f.write_char(']')?;
+                }
+                f.write_str(")[")?;
+                self.convert_elems(f, body, spec)?;
+                f.write_str("];")?;
+            }
+            None => {
+                f.write_str("#block(width: 100%)[#emph[")?;
+                match title {
+                    Some(title) => self.convert_elems(f, title, spec)?,
+                    None => f.write_str("Proof")?,
+                }
+                f.write_str(".] ")?;
+                self.convert_elems(f, body, spec)?;
+                f.write_str(" #h(1fr) $square$];")?;
+            }
+        }
+        self.exit_env(prev);
+        Ok(())
+    }
+}
@@ -0,0 +1,294 @@
+//! Normalizing the generated Typst code for the readable output.
+//!
+//! The converter writes a space after most tokens and keeps the line breaks
+//! of the input. This pass collapses the spaces, drops the ones Typst does
+//! not need, limits blank lines and indents the lines inside brackets, while
+//! keeping strings, raw text and escapes untouched. It tracks the offsets it
+//! moves, so that a source map can be carried over to the tidied code.
+
+/// The syntax of the Typst code at some position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Context {
+    Markup,
+    Code,
+    Math,
+}
+
+/// An open bracket or equation
+struct Frame {
+    ctx: Context,
+    closer: char,
+    // a display equation like `$ x $`, whose inner spaces are kept
+    display: bool,
+    // a bracket of a function call like `#f(..)[..]`, which may be followed
+    // by more arguments
+    call: bool,
+}
+
+/// The tidied code and the offsets it moved.
+pub(crate) struct Tidied {
+    pub output: String,
+    // the output offset of each input byte, and the end of its output
+    starts: Vec<usize>,
+    ends: Vec<usize>,
+}
+
+impl Tidied {
+    /// Map a range of the code before tidying, returning `None` if nothing
+    /// of it is left
+    pub fn map_range(&self, range: std::ops::Range<usize>) -> Option<std::ops::Range<usize>> {
+        let start = self.starts[range.start].min(self.output.len());
+        let end = match range.end {
+            0 => 0,
+            end => self.ends[end - 1].min(self.output.len()),
+        };
+        (start < end).then_some(start..end)
+    }
+}
+
+struct Tidier<'a> {
+    input: &'a str,
+    pos: usize,
+    out: String,
+    starts: Vec<usize>,
+    ends: Vec<usize>,
+    stack: Vec<Frame>,
+    at_line_start: bool,
+    // leading spaces of a markup line, which may indent a list item
+    leading: usize,
+    pending_space: bool,
+    // just after `#ident` or the brackets of a call
+    in_call: bool,
+}
+
+/// Tidy Typst code starting in `root` context.
+pub(crate) fn tidy(input: &str, root: Context) -> Tidied {
+    let mut t = Tidier {
+        input,
+        pos: 0,
+        out: String::with_capacity(input.len()),
+        starts: vec![0; input.len() + 1],
+        ends: vec![0; input.len()],
+        stack: vec![Frame {
+            ctx: root,
+            closer: '\0',
+            display: true,
+            call: false,
+        }],
+        at_line_start: true,
+        leading: 0,
+        pending_space: false,
+        in_call: false,
+    };
+    while let Some(c) = t.peek(0) {
+        t.step(c);
+    }
+    let trimmed = t.out.trim_end().len();
+    t.out.truncate(trimmed);
+    t.starts[input.len()] = t.out.len();
+    Tidied {
+        output: t.out,
+        starts: t.starts,
+        ends: t.ends,
+    }
+}
+
+impl Tidier<'_> {
+    fn peek(&self, n: usize) -> Option<char> {
+        self.input[self.pos..].chars().nth(n)
+    }
+
+    fn top(&self) -> &Frame {
+        self.stack.last().unwrap()
+    }
+
+    /// Copy the next character to the output
+    fn copy(&mut self) {
+        let c = self.peek(0).unwrap();
+        let start = self.out.len();
+        self.out.push(c);
+        self.record(c.len_utf8(), start);
+    }
+
+    /// Drop the next character
+    fn skip(&mut self) {
+        let c = self.peek(0).unwrap();
+        let at = self.out.len();
+        self.record(c.len_utf8(), at);
+    }
+
+    fn record(&mut self, len: usize, start: usize) {
+        let end = self.out.len();
+        for i in self.pos..self.pos + len {
+            self.starts[i] = start;
+            self.ends[i] = end;
+        }
+        self.pos += len;
+    }
+
+    fn step(&mut self, c: char) {
+        match c {
+            ' ' | '\t' | '\r' => {
+                if self.at_line_start {
+                    self.leading += 1;
+                } else {
+                    self.pending_space = true;
+                }
+                self.in_call = false;
+                self.skip();
+            }
+            '\n' => {
+                self.pending_space = false;
+                self.in_call = false;
+                if self.out.is_empty() || self.out.ends_with("\n\n") {
+                    self.skip();
+                } else {
+                    self.copy();
+                }
+                self.at_line_start = true;
+                self.leading = 0;
+            }
+            _ => {
+                self.flush_space(c);
+                self.token(c);
+            }
+        }
+    }
+
+    /// Write the indentation or the space before the next token
+    fn flush_space(&mut self, c: char) {
+        let ctx = self.top().ctx;
+        if self.at_line_start {
+            let mut depth = self.stack.len() - 1;
+            if c == self.top().closer {
+                depth -= 1;
+            }
+            let mut width = depth * 2;
+            if ctx == Context::Markup {
+                width += self.leading;
+            }
+            self.out.push_str(&" ".repeat(width));
+            self.at_line_start = false;
+            self.pending_space = false;
+            return;
+        }
+        if !std::mem::take(&mut self.pending_space) {
+            return;
+        }
+        // spaces between words are collapsed into one
+        if ctx == Context::Markup {
+            self.out.push(' ');
+            return;
+        }
+        let prev = self.out.chars().last();
+        let closes = match c {
+            ')' | ',' | ';' => true,
+            ']' | '}' => ctx == Context::Code,
+            '^' | '_' => ctx == Context::Math,
+            '$' => ctx == Context::Math && !self.top().display,
+            '\\' => matches!(self.peek(1), Some(')' | ']' | ',' | ';')),
+            _ => false,
+        };
+        let opens = match prev {
+            Some('(') => true,
+            Some('[' | '{') => ctx == Context::Code,
+            Some('$') => ctx == Context::Math && !self.top().display,
+            _ => false,
+        };
+        if !closes && !opens {
+            self.out.push(' ');
+        }
+    }
+
+    fn token(&mut self, c: char) {
+        let ctx = self.top().ctx;
+        let in_call = std::mem::take(&mut self.in_call);
+        match c {
+            '\\' => {
+                self.copy();
+                if self.peek(0).is_some_and(|c| c != '\n') {
+                    self.copy();
+                }
+                return;
+            }
+            '"' if ctx != Context::Markup => {
+                self.copy();
+                while let Some(c) = self.peek(0) {
+                    self.copy();
+                    match c {
+                        '\\' if self.peek(0).is_some() => self.copy(),
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+                return;
+            }
+            '`' if ctx == Context::Markup => {
+                let ticks = self.input[self.pos..]
+                    .chars()
+                    .take_while(|&c| c == '`')
+                    .count();
+                let fence = "`".repeat(ticks);
+                let len = match ticks {
+                    // an empty raw text
+                    2 => 2,
+                    _ => {
+                        let body = &self.input[self.pos + ticks..];
+                        body.find(&fence)
+                            .map_or(self.input.len() - self.pos, |end| ticks + end + ticks)
+                    }
+                };
+                let start = self.out.len();
+                self.out.push_str(&self.input[self.pos..self.pos + len]);
+                self.record(len, start);
+                return;
+            }
+            '#' if ctx != Context::Code => {
+                self.copy();
+                while let Some(c) = self.peek(0) {
+                    if !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.')) {
+                        break;
+                    }
+                    self.copy();
+                }
+                // a trailing `.` ends the sentence instead
+                self.in_call = !self.out.ends_with('.');
+                return;
+            }
+            '$' if ctx != Context::Math => {
+                let display = self.peek(1).is_some_and(char::is_whitespace);
+                self.push(Context::Math, '$', display, false);
+            }
+            '(' if ctx == Context::Code || in_call => {
+                self.push(Context::Code, ')', false, in_call);
+            }
+            '[' if ctx == Context::Code || in_call => {
+                self.push(Context::Markup, ']', false, in_call);
+            }
+            '{' if ctx == Context::Code => {
+                self.push(Context::Code, '}', false, false);
+            }
+            '(' if ctx == Context::Math => {
+                self.push(Context::Math, ')', false, false);
+            }
+            _ if c == self.top().closer && self.stack.len() > 1 => {
+                let frame = self.stack.pop().unwrap();
+                self.in_call = frame.call;
+            }
+            _ => {}
+        }
+        self.copy();
+        if matches!(c, ',' | ';') && ctx != Context::Markup {
+            self.pending_space = true;
+        }
+    }
+
+    fn push(&mut self, ctx: Context, closer: char, display: bool, call: bool) {
+        self.stack.push(Frame {
+            ctx,
+            closer,
+            display,
+            call,
+        });
+    }
+}
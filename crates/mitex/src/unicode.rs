@@ -0,0 +1,184 @@
+//! Unicode text of LaTeX accents, special letters and ligatures in text
+//! mode, e.g. `ö` for `\"o` and `–` for `--`.
+
+use std::borrow::Cow;
+
+/// The combining mark of an accent command, e.g. U+0308 for `\"`
+pub(crate) fn accent_mark(name: &str) -> Option<char> {
+    Some(match name {
+        "`" => '\u{300}',
+        "'" => '\u{301}',
+        "^" => '\u{302}',
+        "~" => '\u{303}',
+        "=" => '\u{304}',
+        "u" => '\u{306}',
+        "." => '\u{307}',
+        "\"" => '\u{308}',
+        "r" => '\u{30A}',
+        "H" => '\u{30B}',
+        "v" => '\u{30C}',
+        "d" => '\u{323}',
+        "c" => '\u{327}',
+        "k" => '\u{328}',
+        "b" => '\u{331}',
+        "t" => '\u{361}',
+        _ => return None,
+    })
+}
+
+/// Letters with an accent that have a precomposed form, by the combining
+/// mark, as pairs of the base letter and the accented one
+const COMPOSED: &[(char, &str)] = &[
+    ('\u{300}', "aàeèiìoòuùnǹwẁyỳAÀEÈIÌOÒUÙNǸWẀYỲ"),
+    (
+        '\u{301}',
+        "aáeéiíoóuúyýcćgǵlĺnńrŕsśwẃzźAÁEÉIÍOÓUÚYÝCĆGǴLĹNŃRŔSŚWẂZŹ",
+    ),
+    (
+        '\u{302}',
+        "aâeêiîoôuûcĉgĝhĥjĵsŝwŵyŷAÂEÊIÎOÔUÛCĈGĜHĤJĴSŜWŴYŶ",
+    ),
+    ('\u{303}', "aãeẽiĩnñoõuũyỹAÃEẼIĨNÑOÕUŨYỸ"),
+    ('\u{304}', "aāeēiīoōuūyȳAĀEĒIĪOŌUŪYȲ"),
+    ('\u{306}', "aăeĕgğiĭoŏuŭAĂEĔGĞIĬOŎUŬ"),
+    ('\u{307}', "cċeėgġzżCĊEĖGĠIİZŻ"),
+    ('\u{308}', "aäeëiïoöuüyÿwẅAÄEËIÏOÖUÜYŸWẄ"),
+    ('\u{30A}', "aåuůAÅUŮ"),
+    ('\u{30B}', "oőuűOŐUŰ"),
+    ('\u{30C}', "cčdďeěnňrřsštťzžCČDĎEĚNŇRŘSŠTŤZŽ"),
+    ('\u{327}', "cçgģkķlļnņrŗsştţCÇGĢKĶLĻNŅRŖSŞTŢ"),
+    ('\u{328}', "aąeęiįuųAĄEĘIĮUŲ"),
+];
+
+/// Put an accent on the first letter of a text, e.g. `ö` for `o` and U+0308,
+/// using the precomposed letter if there is one.
+///
+/// The tie of `\t{oo}` joins the first two letters.
+pub(crate) fn compose(base: &str, mark: char) -> String {
+    let mut chars = base.chars();
+    let Some(first) = chars.next() else {
+        return spacing_accent(mark).to_string();
+    };
+    let composed = COMPOSED
+        .iter()
+        .find(|(m, _)| *m == mark)
+        .and_then(|(_, pairs)| {
+            let mut pairs = pairs.chars();
+            std::iter::from_fn(|| Some((pairs.next()?, pairs.next()?)))
+                .find_map(|(base, composed)| (base == first).then_some(composed))
+        });
+    match composed {
+        Some(composed) => format!("{composed}{}", chars.as_str()),
+        None => format!("{first}{mark}{}", chars.as_str()),
+    }
+}
+
+/// The accent without a letter, e.g. `´` for `\'{}`
+fn spacing_accent(mark: char) -> char {
+    match mark {
+        '\u{300}' => '`',
+        '\u{301}' => '´',
+        '\u{302}' => '^',
+        '\u{303}' => '~',
+        '\u{304}' => '¯',
+        '\u{306}' => '˘',
+        '\u{307}' => '˙',
+        '\u{308}' => '¨',
+        '\u{30A}' => '˚',
+        '\u{30B}' => '˝',
+        '\u{30C}' => 'ˇ',
+        '\u{327}' => '¸',
+        '\u{328}' => '˛',
+        mark => mark,
+    }
+}
+
+/// The text of a command for a special letter or a symbol in text mode, e.g.
+/// `ß` for `\ss` and `~` for `\textasciitilde`
+pub(crate) fn text_symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        // special letters
+        "ss" => "ß",
+        "SS" => "SS",
+        "o" => "ø",
+        "O" => "Ø",
+        "ae" => "æ",
+        "AE" => "Æ",
+        "oe" => "œ",
+        "OE" => "Œ",
+        "aa" => "å",
+        "AA" => "Å",
+        "l" => "ł",
+        "L" => "Ł",
+        "i" => "ı",
+        "j" => "ȷ",
+        "dh" => "ð",
+        "DH" => "Ð",
+        "th" => "þ",
+        "TH" => "Þ",
+        "ng" => "ŋ",
+        "NG" => "Ŋ",
+        "dj" => "đ",
+        "DJ" => "Đ",
+        // symbols
+        "textasciitilde" => "~",
+        "textasciicircum" => "^",
+        "textbackslash" => "\\",
+        "textbar" => "|",
+        "textbardbl" => "‖",
+        "textbraceleft" => "{",
+        "textbraceright" => "}",
+        "textdollar" | "textdollarsign" => "$",
+        "textunderscore" => "_",
+        "textless" => "<",
+        "textgreater" => ">",
+        "textendash" => "–",
+        "textemdash" => "—",
+        "textquoteleft" => "‘",
+        "textquoteright" => "’",
+        "textquotedblleft" => "“",
+        "textquotedblright" => "”",
+        "textquotesingle" => "'",
+        "textquotedbl" => "\"",
+        "quotesinglbase" => "‚",
+        "quotedblbase" => "„",
+        "guillemotleft" | "guillemetleft" => "«",
+        "guillemotright" | "guillemetright" => "»",
+        "guilsinglleft" => "‹",
+        "guilsinglright" => "›",
+        "textexclamdown" => "¡",
+        "textquestiondown" => "¿",
+        "textellipsis" | "dots" | "ldots" => "…",
+        "textbullet" => "•",
+        "textperiodcentered" => "·",
+        "textdagger" | "dag" => "†",
+        "textdaggerdbl" | "ddag" => "‡",
+        "textsection" | "S" => "§",
+        "textparagraph" | "P" => "¶",
+        "textcopyright" | "copyright" => "©",
+        "textregistered" => "®",
+        "texttrademark" => "™",
+        "textdegree" => "°",
+        "textperthousand" => "‰",
+        "textsterling" | "pounds" => "£",
+        "texteuro" => "€",
+        "textyen" => "¥",
+        "textcent" => "¢",
+        "textvisiblespace" => "␣",
+        _ => return None,
+    })
+}
+
+/// Replace the dashes and quotes of TeX in a word of text, i.e. `--`, `---`,
+/// `` ` `` and ``` `` ```
+pub(crate) fn ligatures(text: &str) -> Cow<'_, str> {
+    if !text.contains(['-', '`']) {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(
+        text.replace("---", "—")
+            .replace("--", "–")
+            .replace("``", "“")
+            .replace('`', "‘"),
+    )
+}
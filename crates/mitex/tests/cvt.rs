@@ -3,15 +3,32 @@ mod cvt {
         pub use insta::assert_snapshot;
         pub use mitex::convert_math as mitex_convert_math;
         pub use mitex::convert_text as mitex_convert_text;
+        pub use mitex::{ConvertError, ConvertErrorKind};
         pub use mitex_spec_gen::DEFAULT_SPEC;
 
-        pub fn convert_text(input: &str) -> Result<String, String> {
+        pub fn convert_text(input: &str) -> Result<String, ConvertError> {
             mitex_convert_text(input, Some(DEFAULT_SPEC.clone()))
         }
 
-        pub fn convert_math(input: &str) -> Result<String, String> {
+        pub fn convert_math(input: &str) -> Result<String, ConvertError> {
             mitex_convert_math(input, Some(DEFAULT_SPEC.clone()))
         }
+
+        pub fn convert_text_recovering(input: &str) -> mitex::Converted {
+            mitex::convert_text_recovering(input, Some(DEFAULT_SPEC.clone()))
+        }
+
+        pub fn convert_text_with_source_map(input: &str) -> mitex::Converted {
+            mitex::convert_text_with_source_map(input, Some(DEFAULT_SPEC.clone())).unwrap()
+        }
+
+        pub fn convert_math_with_source_map(input: &str) -> mitex::Converted {
+            mitex::convert_math_with_source_map(input, Some(DEFAULT_SPEC.clone())).unwrap()
+        }
+
+        pub fn convert_math_recovering(input: &str) -> mitex::Converted {
+            mitex::convert_math_recovering(input, Some(DEFAULT_SPEC.clone()))
+        }
     }
 
     use prelude::*;
@@ -60,6 +77,73 @@ mod cvt {
 
     #[cfg(test)]
     mod misc;
+
+    #[cfg(test)]
+    mod error;
+
+    #[cfg(test)]
+    mod recover;
+
+    #[cfg(test)]
+    mod source_map;
+
+    #[cfg(test)]
+    mod handler;
+
+    #[cfg(test)]
+    mod spec;
+
+    #[cfg(test)]
+    mod options;
+
+    #[cfg(test)]
+    mod readable;
+
+    #[cfg(test)]
+    mod standalone;
+
+    #[cfg(test)]
+    mod aliases;
+
+    #[cfg(test)]
+    mod qualified;
+
+    #[cfg(test)]
+    mod escape;
+
+    #[cfg(test)]
+    mod accent;
+
+    #[cfg(test)]
+    mod alignment;
+
+    #[cfg(test)]
+    mod bibliography;
+
+    #[cfg(test)]
+    mod document;
+
+    #[cfg(test)]
+    mod graphics;
+
+    #[cfg(test)]
+    mod include;
+
+    #[cfg(test)]
+    mod link;
+
+    #[cfg(test)]
+    mod numbering;
+
+    #[cfg(test)]
+    mod reference;
+
+    #[cfg(test)]
+    mod theorem;
+
+    #[cfg(test)]
+    mod verbatim;
+
     /// Convenient function to launch/debug a test case
     #[test]
     fn bug_playground() {}
@@ -0,0 +1,40 @@
+use super::prelude::*;
+
+#[test]
+fn accents() {
+    assert_snapshot!(convert_text(r#"Erd\H{o}s Schr\"odinger na\"ive"#).unwrap(), @"Erdős Schrödinger naïve");
+    assert_snapshot!(convert_text(r#"\'{e}t\'e \c{c}a \v s \~n \t{oo}"#).unwrap(), @"été ça š ñ o͡o");
+    assert_snapshot!(convert_text(r#"\"{\i} \^{\j} \'{} \={}"#).unwrap(), @"ï ĵ ´ ¯");
+    assert_snapshot!(convert_text(r#"\"{x}\"{y}\u{g} \d{s}"#).unwrap(), @"ẍÿğ ṣ");
+    assert_snapshot!(convert_text(r#"\'{\textbf{e}}"#).unwrap(), @"#strong[e];́");
+    assert_snapshot!(convert_text(r#"\^o \^{a}\^ e \d sa \b{b}ar"#).unwrap(), @"ô âê ṣa ḇar");
+}
+
+#[test]
+fn accents_in_math() {
+    assert_snapshot!(convert_math(r#"\"a + \^{x} + \'x + \~n"#).unwrap(), @"dot.double(a ) +  hat x  +  acute(x ) +  tilde(n )");
+    // `\^` is the `hat` accent on its own, while `\d` and `\b` are only
+    // accents of text
+    assert_snapshot!(convert_math(r#"\^ x"#).unwrap(), @"hat  x");
+    assert_snapshot!(convert_math(r#"\d x"#).unwrap_err(), @r#"error: unknown command: \d"#);
+    assert_snapshot!(convert_math(r#"\b"#).unwrap_err(), @r#"error: unknown command: \b"#);
+}
+
+#[test]
+fn special_letters() {
+    assert_snapshot!(convert_text(r#"Stra\ss e \o{} \O \ae\ \AE \oe \aa \AA ngstr\"om"#).unwrap(), @"Straße ø Øæ  ÆœåÅngström");
+    assert_snapshot!(convert_text(r#"\L\'od\'z \i \l \dh \th"#).unwrap(), @"Łódź ıłðþ");
+}
+
+#[test]
+fn quotes_and_dashes() {
+    assert_snapshot!(convert_text(r#"``quoted'' and `single' it's"#).unwrap(), @"“quoted” and ‘single’ it’s");
+    assert_snapshot!(convert_text(r#"pages 1--2 --- or -1"#).unwrap(), @"pages 1–2 — or -1");
+}
+
+#[test]
+fn text_symbols() {
+    assert_snapshot!(convert_text(r#"\textasciitilde{} \textbackslash{} \textasciicircum"#).unwrap(), @r#"\~ \\ ^"#);
+    assert_snapshot!(convert_text(r#"\textquotedblleft a\textquotedblright{} \guillemotleft b\guillemotright"#).unwrap(), @"“a” «b»");
+    assert_snapshot!(convert_text(r#"\S 2 \copyright\ \pounds 3 \dots"#).unwrap(), @"§2 ©  £3 …");
+}
@@ -0,0 +1,39 @@
+use super::prelude::*;
+use mitex::{ConvertOptions, LaTeXMode};
+
+fn aliases(input: &str, mode: LaTeXMode) -> Vec<String> {
+    let options = ConvertOptions::default()
+        .spec(DEFAULT_SPEC.clone())
+        .mode(mode);
+    mitex::convert(input, &options).unwrap().aliases
+}
+
+#[test]
+fn math() {
+    assert_eq!(
+        aliases(r#"\frac{\sqrt{x}}{2} + \sqrt[3]{y}"#, LaTeXMode::Math),
+        vec!["frac", "mitexsqrt"]
+    );
+    // native Typst does not need mitex-scope
+    assert!(aliases(r#"\alpha + \hat{x} + \mathrm{d}"#, LaTeXMode::Math).is_empty());
+    assert_eq!(
+        aliases(
+            r#"\begin{pmatrix} \lvert x \rvert \end{pmatrix}"#,
+            LaTeXMode::Math
+        ),
+        vec!["lvert", "pmatrix", "rvert"]
+    );
+}
+
+#[test]
+fn text() {
+    assert_eq!(
+        aliases(
+            r#"\textcolor{red}{a} $\mathscr{A}$ $\dfrac{1}{2}$"#,
+            LaTeXMode::Text
+        ),
+        vec!["colortext", "dfrac", "mathscr"]
+    );
+    // names written as plain text are not bound
+    assert!(aliases(r#"\section{A} \emph{b} \textbf{c}"#, LaTeXMode::Text).is_empty());
+}
@@ -0,0 +1,40 @@
+use super::prelude::*;
+
+#[test]
+fn column_pairs() {
+    assert_snapshot!(convert_math(r#"\begin{aligned}a&=b&c&=d\\e&=f&&g\end{aligned}"#).unwrap(), @r#"aligned(a &= b quad &c &= d \ e &= f quad &&g )"#);
+    assert_snapshot!(convert_math(r#"\begin{alignedat}{2}a&=b&\quad c&=d\end{alignedat}"#).unwrap(), @"alignedat(arg0: 2 ,a &= b &quad  c &= d )");
+    assert_snapshot!(convert_text(r#"\begin{alignat*}{2}a&=b&\quad c&=d\end{alignat*}"#).unwrap(), @"#[#set math.equation(numbering: none); $ a &= b &quad  c &= d  $]");
+    assert_snapshot!(convert_text(r#"\begin{flalign*}a&=b&c&=d\end{flalign*}"#).unwrap(), @"#[#set math.equation(numbering: none); $ a &= b quad &c &= d  $]");
+}
+
+#[test]
+fn row_gaps() {
+    assert_snapshot!(convert_math(r#"\begin{aligned}a\\[4pt]b\\*[1.5ex]c\\[-2pt]d\end{aligned}"#).unwrap(), @r#"aligned(a \ #box(height: 0.7em + 4pt) b \ #box(height: 0.7em + 0.75em) c \ d )"#);
+    assert_snapshot!(convert_math(r#"\begin{pmatrix}a\\[2mm]b\end{pmatrix}"#).unwrap(), @"pmatrix(a zws ;#box(height: 0.7em + 2mm) b )");
+    assert_snapshot!(convert_math(r#"\begin{aligned}a\\ [x]\end{aligned}"#).unwrap(), @r#"aligned(a \  \[x \])"#);
+    assert_snapshot!(convert_text(r#"\begin{align*}a&=b\\[3pt]c&=d\\[1em]\end{align*}"#).unwrap(), @r#"#[#set math.equation(numbering: none); $ a &= b \ #box(height: 0.7em + 3pt) c &= d  $]"#);
+    assert_snapshot!(convert_text(r#"\begin{gather}a\\[2\jot]b\end{gather}"#).unwrap(), @r###"
+    #[#set math.equation(numbering: "(1)"); $ a  $ #v(6pt);
+    $ b  $]
+    "###);
+    assert_snapshot!(convert_text(r#"a\\[2pt]b"#).unwrap(), @r#"a\ b"#);
+}
+
+#[test]
+fn intertext() {
+    assert_snapshot!(convert_text(r#"\begin{align*}a&=b\\\intertext{so that}c&=d\end{align*}"#).unwrap(), @r###"
+    #[#set math.equation(numbering: none); $ a &= b  $
+    so that
+    $ c &= d  $]
+    "###);
+    assert_snapshot!(convert_text(r#"\begin{align}a&=b\label{eq:a}\\\shortintertext{and}c&=d\\e&=f\end{align}"#).unwrap(), @r###"
+    #[#set math.equation(numbering: "(1)"); $ a &= b  $<eq:a>
+    and
+    #[#show math.equation: set align(left); #grid(columns: (1fr, auto, auto, 1fr), align: (horizon, right + horizon, left + horizon, horizon), row-gutter: (0.65em,),
+      [], $display(c )$, grid.cell(colspan: 2)[$ zws = d  $],
+      [], $display(e )$, grid.cell(colspan: 2)[$ zws = f  $],
+    )]]
+    "###);
+    assert_snapshot!(convert_math(r#"\begin{aligned}a&=b\\\intertext{so}c&=d\end{aligned}"#).unwrap(), @r#"aligned(a &= b \ #textmath[so];\ c &= d )"#);
+}
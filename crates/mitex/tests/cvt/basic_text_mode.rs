@@ -19,10 +19,8 @@ fn test_convert_text_mode() {
 
     A #strong[strong]; text\, a #emph[emph]; text and inline equation #math.equation(block: false, $x  +  y $);.
 
-    Also block #mitexref[eq:pythagoras];.
+    Also block @eq:pythagoras[].
 
-    $ aligned(
-    a ^(2 ) +  b ^(2 ) =  c ^(2 ) 
-    ) $<eq:pythagoras>
+    #[#set math.equation(numbering: "(1)"); $ a ^(2 ) +  b ^(2 ) =  c ^(2 )  $<eq:pythagoras>]
     "###);
 }
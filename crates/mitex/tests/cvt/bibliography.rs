@@ -0,0 +1,51 @@
+use super::prelude::*;
+
+#[test]
+fn cite() {
+    assert_snapshot!(convert_text(r#"See \cite{a}, \cite[p.~5]{b} and \cite{a, b}."#).unwrap(), @r#"See @a\, @b[p.~5] and @a @b."#);
+    assert_snapshot!(convert_text(r#"\citep[see][ch.~2]{a} \citep[][p.~1]{b}"#).unwrap(), @"see @a[ch.~2] @b[p.~1]");
+    // `~` is a tie in the notes
+    assert_snapshot!(convert_text(r#"\citep[see~also][]{a} \citet[p.~3]{x}"#).unwrap(), @r#"see~also @a #cite(<x>, form: "prose", supplement: [p.~3]);"#);
+    assert_snapshot!(convert_text(r#"\citet{knuth} \citeauthor{x} \citeyear{x} \fullcite{x}"#).unwrap(), @r#"#cite(<knuth>, form: "prose"); #cite(<x>, form: "author"); #cite(<x>, form: "year"); #cite(<x>, form: "full");"#);
+    // keys that are not plain labels, or run on the text after them
+    assert_snapshot!(convert_text(r#"\cite{key:with.dot} \cite{a.}\cite{b}c"#).unwrap(), @"@key:with.dot #cite(<a.>);#cite(<b>);c");
+    assert_snapshot!(convert_math(r#"x \cite{a}"#).unwrap(), @"x  #cite(<a>)");
+}
+
+#[test]
+fn bibliography() {
+    assert_snapshot!(convert_text(r#"\bibliographystyle{plainnat}
+\bibliography{refs}"#).unwrap(), @r###"
+
+    #bibliography("refs.bib", style: "chicago-author-date")
+    "###);
+    assert_snapshot!(convert_text(r#"\bibliography{a,b.bib}
+\bibliographystyle{unknown}"#).unwrap(), @r#"#bibliography(("a.bib", "b.bib"))"#);
+    assert_snapshot!(convert_text(r#"\documentclass{article}
+\usepackage[backend=biber,style=alphabetic]{biblatex}
+\addbibresource{refs.bib}
+\begin{document}
+\nocite{*}
+\printbibliography
+\end{document}"#).unwrap(), @r###"
+
+    #bibliography("refs.bib", full: true, style: "alphanumeric")
+    "###);
+}
+
+#[test]
+fn thebibliography() {
+    assert_snapshot!(convert_text(r#"As in \cite{lamport}.
+\begin{thebibliography}{9}
+\bibitem{lamport} Leslie Lamport, \emph{A document preparation system}, 1994.
+\bibitem[Knu84]{knuth} Donald Knuth, The book.
+\end{thebibliography}"#).unwrap(), @r###"
+    As in @lamport.
+    #heading(numbering: none)[References]
+    #[
+    #show figure.where(kind: "thebibliography"): it => grid(columns: (auto, 1fr), column-gutter: 0.6em, it.counter.display(it.numbering), align(left, it.body))
+    #figure(kind: "thebibliography", supplement: none, numbering: "[1]")[Leslie Lamport\, #emph[A document preparation system];\, 1994.] <lamport>
+    #figure(kind: "thebibliography", supplement: none, numbering: _ => [\[Knu84\]])[Donald Knuth\, The book.] <knuth>
+    ]
+    "###);
+}
@@ -0,0 +1,64 @@
+use super::prelude::*;
+
+#[test]
+fn preamble() {
+    assert_snapshot!(convert_text(r#"\documentclass[11pt]{article}
+\usepackage[utf8]{inputenc}
+\usepackage{amsmath}
+\newcommand{\R}{\mathbb{R}}
+\begin{document}
+Text $\R$.
+\end{document}
+after"#).unwrap(), @"Text #math.equation(block: false, $bb(R )$);.");
+    assert_snapshot!(convert_text(r#"\documentclass{article}
+\iftypst
+#import "template.typ": *
+\fi
+\begin{document}
+Text
+\end{document}"#).unwrap(), @r###"
+
+    #import "template.typ": *
+    Text
+    "###);
+}
+
+#[test]
+fn front_matter() {
+    assert_snapshot!(convert_text(r#"\documentclass{article}
+\title{On Schr\"odinger's \emph{Cat}}
+\author{Paul Erd\H{o}s\thanks{Supported by X.} \\ Budapest \and Jane Doe \\ MIT}
+\date{January 2006}
+\keywords{cats, quantum; boxes}
+\begin{document}
+\maketitle
+\begin{abstract}
+We study cats.
+\end{abstract}
+\end{document}"#).unwrap(), @r###"
+    #set document(title: "On Schrödinger’s Cat", author: ("Paul Erdős", "Jane Doe"), keywords: ("cats", "quantum", "boxes"))
+
+    #align(center)[#block(text(size: 1.7em)[On Schrödinger’s #emph[Cat];])#grid(columns: 2, column-gutter: 2em, [Paul Erdős#footnote[Supported by X.]; \  Budapest ], [Jane Doe \  MIT])#block[January 2006]]
+    #quote(block: true)[
+    We study cats.
+    ];
+    "###);
+    assert_snapshot!(convert_text(r#"\documentclass{article}
+\title{T}
+\author{A}
+\affil{University of X}
+\date{}
+\begin{document}
+\maketitle
+\end{document}"#).unwrap(), @r###"
+    #set document(title: "T", author: ("A",))
+
+    #align(center)[#block(text(size: 1.7em)[T])#block[A]#block[University of X]]
+    "###);
+}
+
+#[test]
+fn maketitle_without_document() {
+    assert_snapshot!(convert_text(r#"\title{T}\author{A}\maketitle"#).unwrap(), @r#"#align(center)[#block(text(size: 1.7em)[T])#block[A]#block(datetime.today().display("[month repr:long] [day padding:none], [year]"))]"#);
+    assert_snapshot!(convert_text(r#"\today"#).unwrap(), @r#"#datetime.today().display("[month repr:long] [day padding:none], [year]")"#);
+}
@@ -2,7 +2,7 @@ use super::prelude::*;
 
 #[test]
 fn easy() {
-    assert_snapshot!(convert_text(r#"\begin{equation}\end{equation}"#).unwrap(), @"$ aligned() $");
+    assert_snapshot!(convert_text(r#"\begin{equation}\end{equation}"#).unwrap(), @r#"#[#set math.equation(numbering: "(1)"); $  $]"#);
     assert_snapshot!(convert_math(r#"\begin{equation}\end{equation}"#).unwrap(), @"aligned()");
 }
 
@@ -31,13 +31,36 @@ fn arguments() {
 a & b \\
 c & d
 \end{array}"#).unwrap(), @r###"
-    mitexarray(arg0: l c ,
-    a  zws , b  zws ;
+    mat(delim: #none, &
+    a  zws , b  zws ;&
     c  zws , d 
     )
     "###);
 }
 
+#[test]
+fn augment() {
+    assert_snapshot!(convert_math(
+            r#"\begin{array}{cc|c}1 & 2 & 3\\ \hline 4 & 5 & 6\end{array}"#).unwrap(), @"mat(delim: #none, augment: #(hline: 1, vline: 2), 1  zws , 2  zws , 3 zws ;   4  zws , 5  zws , 6 )");
+    // rules around the matrix and after the last row are dropped
+    assert_snapshot!(convert_math(
+            r#"\begin{bmatrix}\hline a\\ \hline b\\ \hline c\\ \hline\end{bmatrix}"#).unwrap(), @"bmatrix(augment: #(hline: (1, 2)),   a zws ;   b zws ;   c zws ;  )");
+    assert_snapshot!(convert_math(
+            r#"\begin{array}{|c|c|}a & b\\[2pt]\hline c & d\end{array}"#).unwrap(), @"mat(delim: #none, augment: #(hline: 1, vline: 1), a  zws , b zws ;#box(height: 0.7em + 2pt)   c  zws , d )");
+}
+
+#[test]
+fn column_alignments() {
+    assert_snapshot!(convert_math(
+            r#"\begin{array}{rr}1 & -2\end{array}"#).unwrap(), @"mat(delim: #none, align: #right, 1  zws , - 2 )");
+    assert_snapshot!(convert_math(
+            r#"\begin{array}{lcr}a & b & c\end{array}"#).unwrap(), @"mat(delim: #none, &a  zws , b  zws , c & )");
+    assert_snapshot!(convert_math(
+            r#"\begin{pmatrix*}[r]-1 & 2\end{pmatrix*}"#).unwrap(), @"pmatrix(align: #right, - 1  zws , 2 )");
+    assert_snapshot!(convert_math(
+            r#"\begin{bmatrix*}a & b\end{bmatrix*}"#).unwrap(), @"bmatrix(a  zws , b )");
+}
+
 #[test]
 fn space_around_and() {
     assert_snapshot!(convert_math(
@@ -0,0 +1,122 @@
+use super::prelude::*;
+
+#[test]
+fn unknown_command() {
+    let input = r#"$a + \foo{b}$"#;
+    let err = convert_text(input).unwrap_err();
+    assert_eq!(err.kind, ConvertErrorKind::UnknownCommand);
+    assert_eq!(err.name.as_deref(), Some("foo"));
+    assert_eq!(&input[err.range.clone()], r#"\foo"#);
+    assert_snapshot!(err, @r###"error: unknown command: \foo"###);
+}
+
+#[test]
+fn unknown_command_sym() {
+    let input = r#"\left\0"#;
+    let err = convert_math(input).unwrap_err();
+    assert_eq!(err.kind, ConvertErrorKind::UnknownCommand);
+    assert_eq!(err.name.as_deref(), Some("0"));
+    assert_eq!(&input[err.range.clone()], r#"\0"#);
+}
+
+#[test]
+fn unknown_environment() {
+    let input = "a\n\\begin{foo} b \\end{foo}";
+    let err = convert_text(input).unwrap_err();
+    assert_eq!(err.kind, ConvertErrorKind::UnknownEnvironment);
+    assert_eq!(err.name.as_deref(), Some("foo"));
+    assert_eq!(&input[err.range.clone()], "foo");
+    assert_eq!(err.line_column(input), (2, 8));
+    assert_snapshot!(err, @"error: unknown environment: foo");
+}
+
+#[test]
+fn invalid_formula() {
+    let input = r#"x \[a\)"#;
+    let err = convert_text(input).unwrap_err();
+    assert_eq!(err.kind, ConvertErrorKind::InvalidFormula);
+    assert_eq!(err.name, None);
+    assert_eq!(&input[err.range.clone()], r#"\[a\)"#);
+}
+
+#[test]
+fn item_outside_list() {
+    let input = r#"a \item b"#;
+    let err = convert_text(input).unwrap_err();
+    assert_eq!(err.kind, ConvertErrorKind::ItemOutsideList);
+    assert_eq!(&input[err.range.clone()], r#"\item"#);
+    assert_snapshot!(err, @"error: item command outside of itemize or enumerate");
+}
+
+#[test]
+fn bad_tabular_alignment() {
+    let input = r#"\begin{tabular}{c|x} a & b \end{tabular}"#;
+    let err = convert_text(input).unwrap_err();
+    assert_eq!(err.kind, ConvertErrorKind::BadTabularAlignment);
+    assert_eq!(err.name.as_deref(), Some("x"));
+    assert_eq!(&input[err.range.clone()], "x");
+    assert_snapshot!(err, @"error: unknown alignment: x");
+}
+
+#[test]
+fn too_many_columns() {
+    for spec in ["*{99999999999}{c}", "*{1000}{*{1000}{c}}", "*{400}{cc}"] {
+        let input = format!(r#"\begin{{tabular}}{{{spec}}} a \end{{tabular}}"#);
+        let err = convert_text(&input).unwrap_err();
+        assert_eq!(err.kind, ConvertErrorKind::TooManyColumns);
+    }
+    // repeated lines add no columns
+    assert!(convert_text(r#"\begin{tabular}{*{99999999999}{|}c} a \end{tabular}"#).is_ok());
+    let err = convert_text(r#"\begin{tabular}{*{600}{c}} a \end{tabular}"#).unwrap_err();
+    assert_snapshot!(err, @"error: too many columns in alignment, at most 500 are supported");
+}
+
+#[test]
+fn missing_column_spec() {
+    for input in [
+        r#"\begin{tabular}\end{tabular}"#,
+        r#"\begin{tabular} a \end{tabular}"#,
+        r#"\begin{tabular}[t]"#,
+    ] {
+        let err = convert_text(input).unwrap_err();
+        assert_eq!(err.kind, ConvertErrorKind::MissingArgument);
+    }
+    let err = convert_text(r#"\begin{tabular}\end{tabular}"#).unwrap_err();
+    assert_snapshot!(err, @"error: missing argument: tabular");
+}
+
+#[test]
+fn missing_image() {
+    for input in [r#"\includegraphics x"#, r#"\includegraphics[width=1cm]"#] {
+        let err = convert_text(input).unwrap_err();
+        assert_eq!(err.kind, ConvertErrorKind::MissingArgument);
+    }
+    let err = convert_text(r#"a \includegraphics"#).unwrap_err();
+    assert_snapshot!(err, @"error: missing argument: includegraphics");
+}
+
+#[test]
+fn unexpected_token() {
+    let input = r#"\left{.}a\right{.}"#;
+    let err = convert_math(input).unwrap_err();
+    assert_eq!(err.kind, ConvertErrorKind::UnexpectedToken);
+    assert_eq!(err.name.as_deref(), Some("}"));
+    assert_eq!(&input[err.range.clone()], "}");
+    assert_snapshot!(err, @"error: unexpected token: }");
+}
+
+#[test]
+fn after_macro_definition() {
+    let input = "\\newcommand{\\abc}{x}\n\\abc + \\foo";
+    let err = convert_math(input).unwrap_err();
+    assert_eq!(err.kind, ConvertErrorKind::UnknownCommand);
+    assert_eq!(&input[err.range.clone()], r#"\foo"#);
+    assert_eq!(err.line_column(input), (2, 8));
+}
+
+#[test]
+fn line_column_counts_chars() {
+    let input = "αβ\n  γ $\\foo$";
+    let err = convert_text(input).unwrap_err();
+    assert_eq!(err.line_column(input), (2, 6));
+}
@@ -0,0 +1,68 @@
+use super::prelude::*;
+
+#[test]
+fn markup_chars() {
+    assert_snapshot!(convert_text(r#"a < b > c"#).unwrap(), @r#"a \< b \> c"#);
+    assert_snapshot!(convert_text(r#"`code`"#).unwrap(), @"‘code‘");
+    assert_snapshot!(convert_text(r#"a*b_c"#).unwrap(), @r#"a\*b\_c"#);
+    assert_snapshot!(convert_text(r#"\#1 \$ \_ \&"#).unwrap(), @r"\#1 \$ \_ &");
+    assert_snapshot!(convert_text(r#"[x] a]b"#).unwrap(), @r#"\[x\] a\]b"#);
+    assert_snapshot!(convert_text(r#"a~b "quote""#).unwrap(), @r#"a\~b \"quote\""#);
+    assert_snapshot!(convert_text(r#"x = y + z - w"#).unwrap(), @"x = y + z - w");
+}
+
+#[test]
+fn control_symbols() {
+    assert_snapshot!(convert_text(r#"\{x\} \%"#).unwrap(), @"{x} %");
+    assert_snapshot!(convert_text(r#"a\,b\;c\!d\ e"#).unwrap(), @"a#h(3em / 18);b#h(5em / 18);c#h(-3em / 18);d  e");
+    assert_snapshot!(convert_text(r#"hy\-phen \|"#).unwrap(), @"hy-?phen ‖");
+}
+
+#[test]
+fn comments() {
+    assert_snapshot!(convert_text(r#"a//b a/*b*/"#).unwrap(), @r#"a\/\/b a\/\*b\*\/"#);
+}
+
+#[test]
+fn shorthands() {
+    assert_snapshot!(convert_text(r#"half-?way a-b a--b a---b"#).unwrap(), @r#"half\-?way a-b a–b a—b"#);
+}
+
+#[test]
+fn line_start() {
+    assert_snapshot!(convert_text(r#"-"#).unwrap(), @r#"\-"#);
+    assert_snapshot!(convert_text(r#"- item"#).unwrap(), @r#"\- item"#);
+    assert_snapshot!(convert_text(r#"+ 1"#).unwrap(), @r#"\+ 1"#);
+    assert_snapshot!(convert_text(r#"= Title"#).unwrap(), @r#"\= Title"#);
+    assert_snapshot!(convert_text(r#"== Sub"#).unwrap(), @r#"\== Sub"#);
+    assert_snapshot!(convert_text(r#"==a"#).unwrap(), @"==a");
+    assert_snapshot!(convert_text(r#"1. one"#).unwrap(), @r#"1\. one"#);
+    assert_snapshot!(convert_text(r#"12. twelve"#).unwrap(), @r#"12\. twelve"#);
+    assert_snapshot!(convert_text(r#"1.5 kg"#).unwrap(), @"1.5 kg");
+    assert_snapshot!(convert_text("a\\\\\n- b\n  + c").unwrap(), @r###"
+    a\ 
+    \- b
+    \+ c
+    "###);
+}
+
+#[test]
+fn content_blocks() {
+    assert_snapshot!(convert_text(r#"\textbf{- a}"#).unwrap(), @r#"#strong[\- a];"#);
+    assert_snapshot!(convert_text(r#"\textbf{= b} \emph{+ c}"#).unwrap(), @r#"#strong[\= b]; #emph[\+ c];"#);
+    assert_snapshot!(convert_text(r#"\textbf{]} \emph{[}"#).unwrap(), @r#"#strong[\]]; #emph[\[];"#);
+    assert_snapshot!(convert_text(r#"\textcolor[rgb#x]{red}{a}"#).unwrap(), @r#"#colortext([rgb\#x], [red])[a];"#);
+}
+
+#[test]
+fn labels() {
+    assert_snapshot!(convert_text(r#"a\label{eq:1}"#).unwrap(), @"a<eq:1>");
+    assert_snapshot!(convert_text(r#"a\label{fig:a b}"#).unwrap(), @"a<fig:a-b>");
+    assert_snapshot!(convert_text(r#"a\label{a"b}"#).unwrap(), @"a<a-b>");
+    assert_snapshot!(convert_text(r#"\begin{equation}x\label{a>b}\end{equation}"#).unwrap(), @r#"#[#set math.equation(numbering: "(1)"); $ x  $<a-b>]"#);
+}
+
+#[test]
+fn strings() {
+    assert_snapshot!(convert_text(r#"\includegraphics{my "file".png}"#).unwrap(), @r#"#image("my \"file\".png")"#);
+}
@@ -7,14 +7,7 @@ fn figure() {
         \includegraphics[width=0.5\textwidth, height=3cm, angle=45]{example-image.png}
         \caption{This is an example image.}
         \label{fig:example}
-    \end{figure}"###).unwrap(), @r###"
-    #figure(caption: [This is an example image.],)[
-
-    #image(width: 0.5 * 100%, height: 3cm, "example-image.png")
-
-
-    ];<fig:example>
-    "###);
+    \end{figure}"###).unwrap(), @r#"#figure(kind: image, placement: top, caption: [This is an example image.])[#rotate(-45deg, reflow: true)[#image(width: 50%, height: 3cm, fit: "stretch", "example-image.png")]];<fig:example>"#);
 }
 
 #[test]
@@ -32,9 +25,7 @@ fn table() {
         \caption{This is an example table.}
         \label{tab:example}
     \end{table}"###).unwrap(), @r###"
-    #figure(caption: [This is an example table.],)[
-
-    #table(stroke: none,
+    #figure(kind: table, placement: top, caption: [This is an example table.])[#table(stroke: none,
     columns: 2,
     align: (center, center, ),
     table.vline(stroke: .5pt, x: 0), table.vline(stroke: .5pt, x: 1), table.vline(stroke: .5pt, x: 2), 
@@ -44,9 +35,61 @@ fn table() {
     [John ], [25 ],
     [Jane ], [22 ],
     table.hline(stroke: .5pt),
-    );
+    );];<tab:example>
+    "###);
+}
+
+#[test]
+fn placement() {
+    assert_snapshot!(convert_text(r#"\begin{figure}[H]\centering a\end{figure}"#).unwrap(), @"#figure(kind: image)[a];");
+    assert_snapshot!(convert_text(r#"\begin{figure}[!b]\centering a\end{figure}"#).unwrap(), @"#figure(kind: image, placement: bottom)[a];");
+    assert_snapshot!(convert_text(r#"\begin{figure}[htbp]\centering a\end{figure}"#).unwrap(), @"#figure(kind: image, placement: auto)[a];");
+    assert_snapshot!(convert_text(r#"\begin{figure*}\centering a\end{figure*}"#).unwrap(), @r#"#figure(kind: image, placement: auto, scope: "parent")[a];"#);
+    // the content is left-aligned without `\centering`
+    assert_snapshot!(convert_text(r#"\begin{figure}a\caption{A}\end{figure}"#).unwrap(), @"#figure(kind: image, caption: [A])[#align(left)[a]];");
+}
 
+#[test]
+fn captions() {
+    assert_snapshot!(convert_text(r#"\begin{figure}\centering a\caption[Short]{A long caption}\end{figure}"#).unwrap(), @r###"
+    #show outline: it => { state("mitex-outline", false).update(true); it; state("mitex-outline", false).update(false) }
+
+    #figure(kind: image, caption: [#context if state("mitex-outline", false).get() [Short] else [A long caption]])[a];
+    "###);
+    assert_snapshot!(convert_text(r#"\begin{figure}\centering
+a \caption{A}\label{a}
+b \caption{B}\label{b}
+\end{figure}"#).unwrap(), @r###"
+    #figure(kind: image, caption: [A])[a];<a>
+    #figure(kind: image, caption: [B])[b];<b>
+    "###);
+    // captions above their tables
+    assert_snapshot!(convert_text(r#"\begin{table}[t]\centering
+\caption{A} a
+\caption{B} b
+\end{table}"#).unwrap(), @r###"
+    #place(top, float: true)[#figure(kind: table, caption: [A])[a];
+    #figure(kind: table, caption: [B])[b];]
+    "###);
+}
 
-    ];<tab:example>
+#[test]
+fn subfigures() {
+    assert_snapshot!(convert_text(r#"\begin{figure}\centering
+\begin{subfigure}[b]{0.45\textwidth}\centering a\caption{A}\label{fig:a}\end{subfigure}
+\hfill
+\subcaptionbox{B\label{fig:b}}[0.4\textwidth]{b}
+\caption{Both}\label{fig:ab}
+\end{figure}"#).unwrap(), @r###"
+    #figure(kind: image, caption: [Both])[#counter(figure.where(kind: "subfigure")).update(0)#box(width: 45%)[#figure(kind: "subfigure", supplement: none, numbering: "(a)", caption: figure.caption(separator: [ ], [A]))[a];<fig:a>]
+    #h(1fr) 
+    #box(width: 40%)[#figure(kind: "subfigure", supplement: none, numbering: "(a)", caption: figure.caption(separator: [ ], [B]))[b];<fig:b>]];<fig:ab>
     "###);
 }
+
+#[test]
+fn wrapfigure() {
+    // only approximated, as Typst does not wrap the text around the figure,
+    // which floats to the top of the page at its side instead
+    assert_snapshot!(convert_text(r#"\begin{wrapfigure}{r}{0.3\textwidth}\centering a\caption{A}\end{wrapfigure}"#).unwrap(), @"#place(top + right, float: true)[#box(width: 30%)[#figure(kind: image, caption: [A])[a];]]");
+}
@@ -30,7 +30,7 @@ fn curly_scope() {
 fn env_scope() {
     // Note: this is a valid but incompleted AST, converter should handle it
     // correctly
-    assert_snapshot!(convert_text(r#"\begin{array}$\end{array}$"#).unwrap(), @"$ mitexarray(arg0: ,) $#math.equation(block: false, $$);");
+    assert_snapshot!(convert_text(r#"\begin{array}$\end{array}$"#).unwrap(), @"$ mat(delim: #none, ) $#math.equation(block: false, $$);");
     // Note: this is a valid but incompleted AST, converter should handle it
     // correctly
     assert_snapshot!(convert_text(r#"$\begin{array}$\end{array}"#).unwrap_err(), @"error: formula is not valid");
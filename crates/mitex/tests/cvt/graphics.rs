@@ -0,0 +1,71 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::prelude::*;
+use mitex::ConvertOptions;
+
+/// Create a directory with empty files at some paths, named after a test
+fn workspace(test: &str, files: &[&str]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mitex-graphics-{test}"));
+    let _ = fs::remove_dir_all(&dir);
+    for file in files {
+        let path = dir.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "").unwrap();
+    }
+    dir
+}
+
+fn convert_in(input: &str, dir: &Path) -> String {
+    let options = ConvertOptions::default()
+        .spec(DEFAULT_SPEC.clone())
+        .base_dir(dir);
+    mitex::convert(input, &options).unwrap().output
+}
+
+#[test]
+fn options() {
+    assert_snapshot!(convert_text(r#"\includegraphics[width=\linewidth]{a.png}"#).unwrap(), @r#"#image(width: 100%, "a.png")"#);
+    assert_snapshot!(convert_text(r#"\includegraphics[width=0.8\columnwidth, height=0.3\textheight, keepaspectratio]{a.png}"#).unwrap(), @r#"#image(width: 80%, height: 30%, fit: "contain", "a.png")"#);
+    assert_snapshot!(convert_text(r#"\includegraphics[height=2in]{a.png}"#).unwrap(), @r#"#image(height: 2in, "a.png")"#);
+    assert_snapshot!(convert_text(r#"\includegraphics[scale=0.3, angle=90]{a.png}"#).unwrap(), @r#"#rotate(-90deg, reflow: true)[#scale(30%, reflow: true)[#image("a.png")]]"#);
+    assert_snapshot!(convert_text(r#"\includegraphics[page=2]{a.pdf}"#).unwrap(), @r#"#image(page: 2, "a.pdf")"#);
+    // unknown options and lengths are dropped
+    assert_snapshot!(convert_text(r#"\includegraphics[width=3\baselineskip, draft]{a.png}"#).unwrap(), @r#"#image("a.png")"#);
+}
+
+#[test]
+fn trim() {
+    assert_snapshot!(convert_text(r#"\includegraphics[trim=10 20 30 40, clip]{a.png}"#).unwrap(), @r#"#box(clip: true)[#pad(left: -10pt, bottom: -20pt, right: -30pt, top: -40pt)[#image("a.png")]]"#);
+    assert_snapshot!(convert_text(r#"\includegraphics[trim={1cm 0cm 1cm 0cm}]{a.png}"#).unwrap(), @r#"#pad(left: -1cm, bottom: -0cm, right: -1cm, top: -0cm)[#image("a.png")]"#);
+}
+
+#[test]
+fn graphics_path() {
+    let dir = workspace(
+        "path",
+        &[
+            "a.png",
+            "figs/b.jpg",
+            "img/c.svg",
+            "img/d.pdf",
+            "figs/e.png",
+        ],
+    );
+    let input = r#"\graphicspath{{figs/}{img/}}
+\includegraphics{a} \includegraphics{b} \includegraphics{c.svg} \includegraphics{d} \includegraphics{e.png}"#;
+    assert_snapshot!(convert_in(input, &dir), @r###"
+
+    #image("a.png") #image("figs/b.jpg") #image("img/c.svg") #image("img/d.pdf") #image("figs/e.png")
+    "###);
+    // `\graphicspath` of the preamble applies to the body, and the names of
+    // missing files are kept
+    let input = r#"\graphicspath{{img}}
+\begin{document}\includegraphics{c} \includegraphics{missing}\end{document}"#;
+    assert_snapshot!(convert_in(input, &dir), @r#"#image("img/c.svg") #image("missing")"#);
+}
+
+#[test]
+fn without_base_dir() {
+    assert_snapshot!(convert_text(r#"\graphicspath{{figs/}}\includegraphics{a}"#).unwrap(), @r#"#image("a")"#);
+}
@@ -0,0 +1,114 @@
+use std::fmt::Write;
+
+use super::prelude::*;
+use mitex::rowan::ast::AstNode;
+use mitex::syntax::{CmdItem, EnvItem};
+use mitex::{HandlerContext, HandlerError, Handlers, LaTeXMode};
+
+fn convert_text_with(input: &str, handlers: &Handlers) -> Result<String, ConvertError> {
+    mitex::convert_text_with_handlers(input, Some(DEFAULT_SPEC.clone()), handlers)
+}
+
+fn convert_math_with(input: &str, handlers: &Handlers) -> Result<String, ConvertError> {
+    mitex::convert_math_with_handlers(input, Some(DEFAULT_SPEC.clone()), handlers)
+}
+
+/// `\R` is `RR` in math mode and `ℝ` in text mode
+fn convert_reals(ctx: &mut HandlerContext, _cmd: &CmdItem) -> Result<(), HandlerError> {
+    match ctx.mode() {
+        LaTeXMode::Math => ctx.write_str("RR ")?,
+        LaTeXMode::Text => ctx.write_str("ℝ")?,
+    }
+    Ok(())
+}
+
+/// `\underline{x}` is `#highlight[x];`, converting its argument in text mode
+fn convert_highlight(ctx: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError> {
+    ctx.write_str("#highlight[")?;
+    let prev = ctx.enter_mode(LaTeXMode::Text);
+    for arg in cmd.arguments() {
+        ctx.convert(arg.into())?;
+    }
+    ctx.exit_mode(prev);
+    ctx.write_str("];")?;
+    Ok(())
+}
+
+/// `\begin{note}x\end{note}` is `#note[x];`
+fn convert_note(ctx: &mut HandlerContext, env: &EnvItem) -> Result<(), HandlerError> {
+    ctx.write_str("#note[")?;
+    for child in env.syntax().children_with_tokens() {
+        if matches!(
+            child.kind(),
+            mitex::syntax::SyntaxKind::ItemBegin | mitex::syntax::SyntaxKind::ItemEnd
+        ) {
+            continue;
+        }
+        ctx.convert(child)?;
+    }
+    ctx.write_str("];")?;
+    Ok(())
+}
+
+fn handlers() -> Handlers {
+    let mut handlers = Handlers::default();
+    handlers
+        .add_command("R", convert_reals)
+        .add_command("underline", convert_highlight)
+        .add_env("note", convert_note);
+    handlers
+}
+
+#[test]
+fn command() {
+    let handlers = handlers();
+    assert_snapshot!(convert_text_with(r#"x \R $\R^2$"#, &handlers).unwrap(), @"x ℝ #math.equation(block: false, $RR ^(2 )$);");
+    assert_snapshot!(convert_text_with(r#"a \underline{b \textbf{c}} d"#, &handlers).unwrap(), @"a #highlight[b #strong[c];]; d");
+    assert_snapshot!(convert_math_with(r#"\R \underline{b}"#, &handlers).unwrap(), @"RR  #highlight[b];");
+}
+
+#[test]
+fn environment() {
+    let handlers = handlers();
+    assert_snapshot!(convert_text_with(r#"\begin{note}a $x$\end{note}"#, &handlers).unwrap(), @"#note[a #math.equation(block: false, $x $);];");
+}
+
+#[test]
+fn closure() {
+    let mut handlers = Handlers::default();
+    handlers.add_command(
+        "foo",
+        |ctx: &mut HandlerContext, _: &CmdItem| -> Result<(), HandlerError> {
+            ctx.write_str("#foo();")?;
+            Ok(())
+        },
+    );
+    assert_snapshot!(convert_text_with(r#"a \foo b"#, &handlers).unwrap(), @"a #foo(); b");
+}
+
+#[test]
+fn override_builtin() {
+    let mut handlers = Handlers::default();
+    handlers.add_command("textbf", convert_highlight);
+    assert_snapshot!(convert_text_with(r#"\textbf{a}"#, &handlers).unwrap(), @"#highlight[a];");
+    // fall back to the command specification
+    handlers.remove_command("textbf");
+    assert_snapshot!(convert_text_with(r#"\textbf{a}"#, &handlers).unwrap(), @"#textbf[a];");
+}
+
+#[test]
+fn error() {
+    fn convert_error(_: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError> {
+        let range = cmd.syntax().text_range();
+        Err(ConvertError::with_name(
+            ConvertErrorKind::UnknownCommand,
+            range.start().into()..range.end().into(),
+            "bad",
+        ))?
+    }
+    let mut handlers = Handlers::default();
+    handlers.add_command("bad", convert_error);
+    let input = r#"\newcommand{\x}{y} a \bad"#;
+    let err = convert_text_with(input, &handlers).unwrap_err();
+    assert_eq!(&input[err.range], r#"\bad"#);
+}
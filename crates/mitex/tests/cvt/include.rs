@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+use super::prelude::*;
+
+/// Inline the files of a project given as pairs of paths and texts, with
+/// `main.tex` as its main file
+fn inline(files: &[(&str, &str)]) -> Result<mitex::Inlined, mitex::IncludeError> {
+    let files = files.iter().copied().collect::<HashMap<_, _>>();
+    mitex::inline_includes(Path::new("main.tex"), Path::new("."), &mut |path| {
+        let path = path.to_str().unwrap().replace('\\', "/");
+        files
+            .get(path.as_str())
+            .map(|text| text.to_string())
+            .ok_or_else(|| io::ErrorKind::NotFound.into())
+    })
+}
+
+fn inline_text(files: &[(&str, &str)]) -> String {
+    match inline(files) {
+        Ok(inlined) => inlined.text,
+        Err(err) => err.to_string().replace('\\', "/"),
+    }
+}
+
+#[test]
+fn input_and_include() {
+    assert_snapshot!(inline_text(&[
+        ("main.tex", r#"a \input{one} b \include{dir/two.tex} c \input three"#),
+        ("one.tex", "1"),
+        ("dir/two.tex", "2"),
+        ("three.tex", "3"),
+    ]), @"a 1 b 2 c 3");
+    assert_snapshot!(inline_text(&[
+        ("main.tex", r#"\input{chapters/one}"#),
+        ("chapters/one.tex", r#"1 \input{chapters/two}"#),
+        ("chapters/two.tex", "2"),
+    ]), @"1 2");
+}
+
+#[test]
+fn skipped_inclusions() {
+    assert_snapshot!(inline_text(&[
+        ("main.tex", "% \\input{one}\n\\verb|\\input{one}| \\newcommand{\\inc}[1]{\\input{#1}}\n\\begin{verbatim}\n\\input{one}\n\\end{verbatim}"),
+    ]), @r###"
+    % \input{one}
+    \verb|\input{one}| \newcommand{\inc}[1]{\input{#1}}
+    \begin{verbatim}
+    \input{one}
+    \end{verbatim}
+    "###);
+    assert_snapshot!(inline_text(&[
+        ("main.tex", "a \\input{one} b"),
+        ("one.tex", "1\\endinput 2"),
+    ]), @"a 1 b");
+    assert_snapshot!(inline_text(&[
+        ("main.tex", r#"\url{a%20b} \input{one}"#),
+        ("one.tex", "1"),
+    ]), @r#"\url{a%20b} 1"#);
+}
+
+#[test]
+fn verb_without_argument() {
+    // `\verb` at the end of a line or of the file has no argument
+    assert_snapshot!(inline_text(&[
+        ("main.tex", "a \\verb\n\\input{one}"),
+        ("one.tex", "1"),
+    ]), @r###"
+    a \verb
+    1
+    "###);
+    assert_snapshot!(inline_text(&[
+        ("main.tex", "\\input{one} \\verb|b"),
+        ("one.tex", "1"),
+    ]), @r#"1 \verb|b"#);
+    assert_snapshot!(inline_text(&[
+        ("main.tex", "\\input{one} \\verb"),
+        ("one.tex", "1"),
+    ]), @r#"1 \verb"#);
+}
+
+#[test]
+fn import_and_subfile() {
+    assert_snapshot!(inline_text(&[
+        ("main.tex", r#"\import{parts/}{one} \subfile{sub/s}"#),
+        ("parts/one.tex", r#"1 \subimport{deep/}{two} \input{three}"#),
+        ("parts/deep/two.tex", "2"),
+        ("parts/three.tex", "3"),
+        ("sub/s.tex", "\\documentclass[../main]{subfiles}\n\\begin{document}\ns \\input{four}\n\\end{document}"),
+        ("sub/four.tex", "4"),
+    ]), @r###"
+    1 2 3 
+    s 4
+    "###);
+}
+
+#[test]
+fn macros_across_files() {
+    let inlined = inline(&[
+        ("main.tex", r#"\input{macros} $\R$"#),
+        ("macros.tex", r#"\newcommand{\R}{\mathbb{R}}"#),
+    ])
+    .unwrap();
+    assert_snapshot!(convert_text(&inlined.text).unwrap(), @" #math.equation(block: false, $bb(R )$);");
+}
+
+#[test]
+fn include_errors() {
+    assert_snapshot!(inline_text(&[
+        ("main.tex", "a\n  \\input{one}"),
+    ]), @"main.tex:2:3: error: file `one` not found");
+    assert_snapshot!(inline_text(&[
+        ("main.tex", r#"\input{one}"#),
+        ("one.tex", r#"\input{two}"#),
+        ("two.tex", r#"\input{main}"#),
+    ]), @"error: cyclic inclusion: main.tex -> one.tex -> two.tex -> main.tex");
+    assert_snapshot!(inline_text(&[
+        ("main.tex", r#"\input{one}"#),
+        ("one.tex", r#"\input{one}"#),
+    ]), @"error: cyclic inclusion: main.tex -> one.tex -> one.tex");
+}
+
+#[test]
+fn source_of_offsets() {
+    let inlined = inline(&[
+        ("main.tex", "a\n\\input{one} b"),
+        ("one.tex", "x\n\\unknown"),
+    ])
+    .unwrap();
+    let err = convert_text(&inlined.text).unwrap_err();
+    let (path, text, offset) = inlined.source(err.range.start).unwrap();
+    let err = ConvertError {
+        range: offset..offset,
+        ..err
+    };
+    assert_eq!(path, Path::new("one.tex"));
+    assert_eq!(err.line_column(text), (2, 1));
+}
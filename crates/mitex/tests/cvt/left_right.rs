@@ -9,7 +9,7 @@ fn base() {
     assert_snapshot!(convert_math(r#"\left\langle a\right\|"#).unwrap(), @"lr(chevron.l  a || )");
     // Note: this is an invalid expression
     // Error handling
-    assert_snapshot!(convert_math(r#"\left{.}a\right{.}"#).unwrap_err(), @r###"error: error unexpected: "}""###);
+    assert_snapshot!(convert_math(r#"\left{.}a\right{.}"#).unwrap_err(), @"error: unexpected token: }");
     // Note: this is an invalid expression
     // Error handling
     assert_snapshot!(convert_math(r#"\begin{equation}\left.\right\end{equation}"#).unwrap(), @"aligned(lr( ))");
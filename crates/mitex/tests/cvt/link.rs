@@ -0,0 +1,22 @@
+use super::prelude::*;
+
+#[test]
+fn url() {
+    assert_snapshot!(convert_text(r#"See \url{https://a.org/x%20y#top_1~u}."#).unwrap(), @r#"See #link("https://a.org/x%20y#top_1~u");."#);
+    assert_snapshot!(convert_text(r#"\href{https://a.org/?q=1\#top}{the \emph{site}} \href{mailto:a@b.org}{mail}"#).unwrap(), @r#"#link("https://a.org/?q=1#top")[the #emph[site];]; #link("mailto:a@b.org")[mail];"#);
+    assert_snapshot!(convert_math(r#"\url{a"b}"#).unwrap(), @r#"#link("a\"b")"#);
+}
+
+#[test]
+fn hyperref() {
+    assert_snapshot!(convert_text(r#"Go to \hyperref[sec:intro]{the introduction}."#).unwrap(), @"Go to #link(<sec:intro>)[the introduction];.");
+}
+
+#[test]
+fn footnote_mark() {
+    assert_snapshot!(convert_text(r#"A\footnotemark{} and B\footnotemark[7].
+\footnotetext{First.}
+\footnotetext[7]{Seventh.}"#).unwrap(), @"A#footnote[First.]; and B#footnote[Seventh.];.");
+    // a text without a mark is a footnote where it is
+    assert_snapshot!(convert_text(r#"A.\footnotetext{Text.} B\footnotemark"#).unwrap(), @"A.#footnote[Text.]; B");
+}
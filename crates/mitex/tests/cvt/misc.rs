@@ -177,10 +177,10 @@ a & b & c
 \end{array}$"#
         ).unwrap(),
         @r###"
-    mitexarray(arg0: l c r ,
-    1  zws , 2  zws , 3 zws ;
+    mat(delim: #none, &
+    1  zws , 2  zws , 3 zws & ;&
     a  zws , b  zws , c 
-    )
+    & )
     "###
     );
 }
@@ -195,8 +195,8 @@ a & b & c
         ).unwrap(),
         @r###"
     aligned(
-    1  & 2  & 3 \ 
-    a  & b  & c 
+    1  & 2  quad & 3 \ 
+    a  & b  quad & c 
     )
     "###
     );
@@ -208,8 +208,8 @@ a & b & c
         ).unwrap(),
         @r###"
     aligned(
-    1  & 2  & 3 \ 
-    a  & b  & c 
+    1  & 2  quad & 3 \ 
+    a  & b  quad & c 
     )
     "###
     );
@@ -275,5 +275,5 @@ fn test_convert_formula() {
 #[test]
 fn test_fuzzing() {
     assert_snapshot!(convert_math(r#"\left\0"#).unwrap_err(), @r###"error: unknown command: \0"###);
-    assert_snapshot!(convert_math(r#"\end{}"#).unwrap_err(), @r###"error: error unexpected: """###);
+    assert_snapshot!(convert_math(r#"\end{}"#).unwrap_err(), @"error: unexpected token");
 }
@@ -0,0 +1,59 @@
+use super::prelude::*;
+
+#[test]
+fn numbered() {
+    assert_snapshot!(convert_text(r#"\begin{equation}x\end{equation}"#).unwrap(), @r#"#[#set math.equation(numbering: "(1)"); $ x  $]"#);
+    assert_snapshot!(convert_text(r#"\begin{equation*}x\end{equation*}"#).unwrap(), @"#[#set math.equation(numbering: none); $ x  $]");
+    assert_snapshot!(convert_text(r#"\begin{gather*}a\\b\end{gather*}"#).unwrap(), @r#"#[#set math.equation(numbering: none); $ a \ b  $]"#);
+    assert_snapshot!(convert_math(r#"\begin{align*}a&=b\end{align*}"#).unwrap(), @"aligned(a &= b )");
+}
+
+#[test]
+fn rows() {
+    assert_snapshot!(convert_text(r#"\begin{align}a&=b\\c&=d\end{align}"#).unwrap(), @r###"
+    #[#set math.equation(numbering: "(1)"); #[#show math.equation: set align(left); #grid(columns: (1fr, auto, auto, 1fr), align: (horizon, right + horizon, left + horizon, horizon), row-gutter: (0.65em,),
+      [], $display(a )$, grid.cell(colspan: 2)[$ zws = b  $],
+      [], $display(c )$, grid.cell(colspan: 2)[$ zws = d  $],
+    )]]
+    "###);
+    assert_snapshot!(convert_text(r#"\begin{align}a&=b\nonumber\\c&=d\label{eq:c}\\\end{align}"#).unwrap(), @r#"#[#set math.equation(numbering: "(1)"); $ a &= b  \ c &= d  $<eq:c>]"#);
+    assert_snapshot!(convert_text(r#"\begin{gather}a\label{eq:a}\\b\notag\\c\label{eq:c}\end{gather}"#).unwrap(), @r###"
+    #[#set math.equation(numbering: "(1)"); $ a  $<eq:a>
+    #math.equation(block: true, numbering: none, $ b   $)
+    $ c  $<eq:c>]
+    "###);
+}
+
+#[test]
+fn aligned_rows() {
+    // the cells of the rows share the columns of a grid, so the `=` of rows
+    // whose left sides differ in width are aligned
+    assert_snapshot!(convert_text(r#"\begin{align}x&=1\label{eq:x}\\a+b+c&=2\end{align}"#).unwrap(), @r###"
+    #[#set math.equation(numbering: "(1)"); #[#show math.equation: set align(left); #grid(columns: (1fr, auto, auto, 1fr), align: (horizon, right + horizon, left + horizon, horizon), row-gutter: (0.65em,),
+      [], $display(x )$, grid.cell(colspan: 2)[$ zws = 1  $<eq:x>],
+      [], $display(a + b + c )$, grid.cell(colspan: 2)[$ zws = 2  $],
+    )]]
+    "###);
+    assert_snapshot!(convert_text(r#"\begin{align}x&=1&y&=2\\xx&=3\end{align}"#).unwrap(), @r###"
+    #[#set math.equation(numbering: "(1)"); #[#show math.equation: set align(left); #grid(columns: (1fr, auto, auto, auto, auto, 1fr), align: (horizon, right + horizon, left + horizon, right + horizon, left + horizon, horizon), column-gutter: (0pt, 0pt, 1em, 0pt, 0pt), row-gutter: (0.65em,),
+      [], $display(x )$, $display(zws = 1 )$, $display(y )$, grid.cell(colspan: 2)[$ zws = 2  $],
+      [], $display(x x )$, grid.cell(colspan: 4)[$ zws = 3  $],
+    )]]
+    "###);
+}
+
+#[test]
+fn tags() {
+    assert_snapshot!(convert_text(r#"\begin{equation*}x\tag{1a}\label{eq:x}\end{equation*}"#).unwrap(), @"#[#set math.equation(numbering: none); #math.equation(block: true, numbering: _ => [(1a)], $ x  $)<eq:x> #counter(math.equation).update(n => n - 1);]");
+    assert_snapshot!(convert_text(r#"\begin{align}a\tag*{A}\\b\end{align}"#).unwrap(), @r###"
+    #[#set math.equation(numbering: "(1)"); #math.equation(block: true, numbering: _ => [A], $ a  $) #counter(math.equation).update(n => n - 1);
+    $ b  $]
+    "###);
+    assert_snapshot!(convert_text(r#"\begin{align*}a&=b\tag{A}\label{eq:a}\\c&=d\tag*{B}\end{align*}"#).unwrap(), @r###"
+    #[#set math.equation(numbering: none); #[#show math.equation: set align(left); #grid(columns: (1fr, auto, auto, 1fr), align: (horizon, right + horizon, left + horizon, horizon), row-gutter: (0.65em,),
+      [], $display(a )$, grid.cell(colspan: 2)[#math.equation(block: true, numbering: _ => [(A)], $ zws = b  $)<eq:a> #counter(math.equation).update(n => n - 1);],
+      [], $display(c )$, grid.cell(colspan: 2)[#math.equation(block: true, numbering: _ => [B], $ zws = d  $) #counter(math.equation).update(n => n - 1);],
+    )]]
+    "###);
+    assert_snapshot!(convert_math(r#"\begin{align*}a\tag{1}\end{align*}"#).unwrap(), @"aligned(a )");
+}
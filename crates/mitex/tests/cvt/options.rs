@@ -0,0 +1,79 @@
+use super::prelude::*;
+use mitex::{ConvertOptions, ErrorPolicy, LaTeXMode, LabelPolicy, MathStyle};
+
+fn convert_with(input: &str, options: ConvertOptions) -> String {
+    let options = options.spec(DEFAULT_SPEC.clone());
+    mitex::convert(input, &options).unwrap().output
+}
+
+#[test]
+fn default_is_text_mode() {
+    let input = r#"a $\alpha$"#;
+    assert_eq!(
+        convert_with(input, ConvertOptions::default()),
+        convert_text(input).unwrap()
+    );
+}
+
+#[test]
+fn math_style() {
+    let math = || ConvertOptions::default().mode(LaTeXMode::Math);
+    assert_snapshot!(convert_with(r#"\alpha"#, math()), @"alpha ");
+    assert_snapshot!(convert_with(r#"\alpha"#, math().math_style(MathStyle::Inline)), @"$alpha $");
+    assert_snapshot!(convert_with(r#"\alpha"#, math().math_style(MathStyle::Display)), @"$ alpha  $");
+    // no effect in text mode
+    assert_snapshot!(convert_with(r#"a"#, ConvertOptions::default().math_style(MathStyle::Display)), @"a");
+}
+
+#[test]
+fn labels() {
+    let input = r#"\section{A}\label{sec:a}
+\begin{equation}x\label{eq:x}\end{equation}"#;
+    assert_snapshot!(convert_with(input, ConvertOptions::default()), @r###"
+    #heading(level: 1)[A];<sec:a>
+    #[#set math.equation(numbering: "(1)"); $ x  $<eq:x>]
+    "###);
+    assert_snapshot!(convert_with(input, ConvertOptions::default().labels(LabelPolicy::Drop)), @r###"
+    #heading(level: 1)[A];
+    #[#set math.equation(numbering: "(1)"); $ x  $]
+    "###);
+}
+
+#[test]
+fn wrap_inline_equations() {
+    let input = r#"a $x$ and $$y$$"#;
+    assert_snapshot!(convert_with(input, ConvertOptions::default()), @"a #math.equation(block: false, $x $); and $ y  $");
+    assert_snapshot!(convert_with(input, ConvertOptions::default().wrap_inline_equations(false)), @"a $x $ and $ y  $");
+}
+
+#[test]
+fn list_indent() {
+    let input = r#"\begin{itemize}
+\item a
+\begin{enumerate}
+\item b
+\end{enumerate}
+\end{itemize}"#;
+    assert_eq!(
+        convert_with(input, ConvertOptions::default()),
+        "\n  \n-  a\n  \n    \n  +  b\n    \n  "
+    );
+    assert_eq!(
+        convert_with(input, ConvertOptions::default().list_indent(4)),
+        "\n    \n-  a\n    \n        \n    +  b\n        \n    "
+    );
+}
+
+#[test]
+fn error_policy() {
+    let input = r#"a \unknown b"#;
+    let options = ConvertOptions::default().spec(DEFAULT_SPEC.clone());
+    let err = mitex::convert(input, &options).unwrap_err();
+    assert_eq!(err.kind, ConvertErrorKind::UnknownCommand);
+
+    let options = options.error_policy(ErrorPolicy::Recover);
+    let res = mitex::convert(input, &options).unwrap();
+    assert_eq!(res.diagnostics.len(), 1);
+    assert!(res.source_map.is_none());
+    assert_snapshot!(res.output, @r###"a #text(fill: red, raw("\\unknown")); b"###);
+}
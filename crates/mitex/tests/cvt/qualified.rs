@@ -0,0 +1,60 @@
+use super::prelude::*;
+use mitex::{ConvertOptions, LaTeXMode, Target};
+
+fn convert_qualified(input: &str, mode: LaTeXMode, target: Target) -> String {
+    let options = ConvertOptions::default()
+        .spec(DEFAULT_SPEC.clone())
+        .mode(mode)
+        .target(target)
+        .qualified(true);
+    mitex::convert(input, &options).unwrap().output
+}
+
+fn math(input: &str) -> String {
+    convert_qualified(input, LaTeXMode::Math, Target::MitexScope)
+}
+
+fn standalone_math(input: &str) -> String {
+    convert_qualified(input, LaTeXMode::Math, Target::Standalone)
+}
+
+fn standalone_prelude(input: &str) -> String {
+    let options = ConvertOptions::default()
+        .spec(DEFAULT_SPEC.clone())
+        .mode(LaTeXMode::Math)
+        .target(Target::Standalone)
+        .qualified(true);
+    mitex::convert(input, &options).unwrap().prelude
+}
+
+#[test]
+fn builtins() {
+    assert_snapshot!(math(r#"\alpha \to \infty"#), @"sym.alpha  ->  sym.oo ");
+    assert_snapshot!(math(r#"\hat{x} + \ddot{y} + \overline{z}"#), @"math.hat(x ) +  math.dot.double(y ) +  math.overline(z )");
+    // accents are functions, and the symbols of the same names are kept
+    assert_snapshot!(math(r#"\tilde{y} \vec{v} \sim \cdot"#), @"math.tilde(y ) math.arrow(v ) sym.tilde  sym.dot.c");
+    assert_snapshot!(math(r#"\sin x \le \max_i a_i"#), @"math.sin  x  <=  math.max _(i ) a _(i )");
+    assert_snapshot!(math(r#"\left( x \right) ~ {}_1 x"#), @r"math.lr(\(  x  \) ) sym.space.nobreak  sym.zws _(1 ) x ");
+}
+
+#[test]
+fn shorthands() {
+    assert_snapshot!(math(r#"a \ne b \rightarrow c"#), @"a  !=  b  ->  c ");
+}
+
+#[test]
+fn scope() {
+    assert_snapshot!(math(r#"\frac{1}{2} + \sqrt{x}"#), @"mitex.frac(1 ,2 ) +  mitex.mitexsqrt(x )");
+    assert_snapshot!(math(r#"\begin{pmatrix} a & \\ c & d \end{pmatrix}"#), @"mitex.pmatrix( a  sym.zws , sym.zws ; c  sym.zws , d  )");
+    assert_snapshot!(convert_qualified(r#"\textcolor{red}{a} $\alpha$"#, LaTeXMode::Text, Target::MitexScope), @"#mitex.colortext(none, [red])[a]; #math.equation(block: false, $sym.alpha $);");
+}
+
+#[test]
+fn standalone() {
+    assert_snapshot!(standalone_prelude(r#"\xrightarrow{f} \pmod{n}"#), @r###"
+    #let pmod = it => $math.quad (math.mod math.thick it)$
+    #let xrightarrow = it => $math.limits(math.stretch(#math.arrow.r)^#it)$
+    "###);
+    assert_snapshot!(standalone_math(r#"\frac{1}{2} + \sqrt[3]{x} + \xcancel{y}"#), @"math.frac(1 ,2 ) +  math.root(3 , x ) +  math.cancel(y )");
+    assert_snapshot!(standalone_math(r#"\mathbf{x} \begin{pmatrix} a \end{pmatrix}"#), @r#"mitexmathbf(x ) math.mat(delim: "(",  a  )"#);
+}
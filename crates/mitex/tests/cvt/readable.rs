@@ -0,0 +1,114 @@
+use super::prelude::*;
+use mitex::{ConvertOptions, LaTeXMode, MathStyle};
+
+fn readable(mode: LaTeXMode) -> ConvertOptions {
+    ConvertOptions::default()
+        .spec(DEFAULT_SPEC.clone())
+        .mode(mode)
+        .readable(true)
+}
+
+fn convert_readable_text(input: &str) -> String {
+    mitex::convert(input, &readable(LaTeXMode::Text))
+        .unwrap()
+        .output
+}
+
+fn convert_readable_math(input: &str) -> String {
+    mitex::convert(input, &readable(LaTeXMode::Math))
+        .unwrap()
+        .output
+}
+
+#[test]
+fn numbers() {
+    assert_snapshot!(convert_readable_math(r#"1.5 + x_{10} - 3.x2"#), @"1.5 + x_(10) - 3 . x 2");
+}
+
+#[test]
+fn upright_words() {
+    assert_snapshot!(convert_readable_math(r#"\mathrm{max}(a, b) + \operatorname{argmax}_x f(x)"#), @r###"upright("max")\(a\, b\) + operatorname("argmax")_(x) f \(x\)"###);
+    // only plain words are written as strings
+    assert_snapshot!(convert_readable_math(r#"\mathrm{d x} + \mathrm{x_1}"#), @"upright(d x) + upright(x_(1))");
+    assert_snapshot!(convert_readable_text(r#"\mathrm{max}"#), @"upright[max];");
+}
+
+#[test]
+fn math_spacing() {
+    assert_snapshot!(convert_readable_math(r#"\frac{a}{b}  +  \sqrt[3]{x^2}\cdot\left( a \right)"#), @r###"frac(a, b) + mitexsqrt(\[3\], x^(2))dot.c lr(\(a\))"###);
+    assert_snapshot!(convert_readable_math(r#"\text{a  "b"}\quad\sin x"#), @r###"#textmath[a \"b\"]; quad sin x"###);
+}
+
+#[test]
+fn equations() {
+    assert_snapshot!(convert_readable_text(r#"a $x + 1$ and $$x + 1$$"#), @"a #math.equation(block: false, $x + 1$); and $ x + 1 $");
+    let display = readable(LaTeXMode::Math).math_style(MathStyle::Display);
+    assert_snapshot!(mitex::convert(r#"x + 1"#, &display).unwrap().output, @"$ x + 1 $");
+    let inline = readable(LaTeXMode::Math).math_style(MathStyle::Inline);
+    assert_snapshot!(mitex::convert(r#"x + 1"#, &inline).unwrap().output, @"$x + 1$");
+}
+
+#[test]
+fn matrices() {
+    assert_snapshot!(convert_readable_math(r#"\begin{pmatrix} 1 & 0 \\ 0 & 1 \end{pmatrix}"#), @"pmatrix(1, 0; 0, 1)");
+    // empty cells are kept by `zws`
+    assert_snapshot!(convert_readable_math(r#"\begin{matrix} & a \\ b & \end{matrix}"#), @"matrix(zws, a; b,)");
+}
+
+#[test]
+fn lists() {
+    assert_snapshot!(convert_readable_text(r#"\begin{itemize}
+\item first
+\item second
+  \begin{enumerate}
+    \item nested
+  \end{enumerate}
+\end{itemize}"#), @r###"
+    - first
+
+    - second
+
+      + nested
+    "###);
+}
+
+#[test]
+fn tables() {
+    assert_snapshot!(convert_readable_text(r#"\begin{tabular}{|c|l|}
+\hline
+A & B \\
+1 & 2 \\
+\end{tabular}"#), @r###"
+    #table(stroke: none,
+      columns: 2,
+      align: (center, left,),
+      table.vline(stroke: .5pt, x: 0), table.vline(stroke: .5pt, x: 1), table.vline(stroke: .5pt, x: 2),
+      table.hline(stroke: .5pt),
+      [A ], [B ],
+      [1 ], [2 ],
+    );
+    "###);
+}
+
+#[test]
+fn text_spacing() {
+    assert_snapshot!(convert_readable_text("a  b   \n\n\n\nc \\textbf{d}  e\n"), @r###"
+    a b
+
+    c #strong[d]; e
+    "###);
+}
+
+#[test]
+fn source_map() {
+    let input = r#"$\frac{a}{b}$ and \textbf{c}"#;
+    let res = mitex::convert(input, &readable(LaTeXMode::Text).source_map(true)).unwrap();
+    let source_map = res.source_map.unwrap();
+    let frac = res.output.find("frac").unwrap();
+    assert_eq!(&input[source_map.to_input(frac).unwrap()], r#"\frac{a}{b}"#);
+    let c = input.rfind('c').unwrap();
+    assert_eq!(&res.output[source_map.to_output(c).unwrap()], "c");
+    for m in source_map.mappings() {
+        assert!(m.output.end <= res.output.len());
+    }
+}
@@ -0,0 +1,89 @@
+use std::fmt::Write;
+
+use super::prelude::*;
+use mitex::rowan::ast::AstNode;
+use mitex::syntax::CmdItem;
+use mitex::{ConvertOptions, ErrorPolicy, HandlerContext, HandlerError, Handlers, LaTeXMode};
+
+#[test]
+fn no_errors() {
+    let res = convert_math_recovering(r#"\frac{a}{b}"#);
+    assert_snapshot!(res.output, @"frac(a ,b )");
+    assert!(res.diagnostics.is_empty());
+}
+
+#[test]
+fn unknown_command() {
+    let input = r#"$a + \mycmd{b} + c$ and \textbf{d}"#;
+    let res = convert_text_recovering(input);
+    assert_snapshot!(res.output, @r###"#math.equation(block: false, $a  +  #text(fill: red, raw("\\mycmd"));b  +  c $); and #strong[d];"###);
+    assert_eq!(res.diagnostics.len(), 1);
+    assert_eq!(res.diagnostics[0].kind, ConvertErrorKind::UnknownCommand);
+    assert_eq!(&input[res.diagnostics[0].range.clone()], r#"\mycmd"#);
+}
+
+#[test]
+fn unknown_environment() {
+    let res = convert_text_recovering("a \\begin{foo}\"x\"\\end{foo} b");
+    assert_snapshot!(res.output, @r###"a #text(fill: red, raw("\\begin{foo}\"x\"\\end{foo}")); b"###);
+    assert_eq!(
+        res.diagnostics[0].kind,
+        ConvertErrorKind::UnknownEnvironment
+    );
+}
+
+#[test]
+fn multiple_errors() {
+    let input = "\\foo x\n\\baz \\item \\[y\\)";
+    let res = convert_text_recovering(input);
+    let kinds = res.diagnostics.iter().map(|e| e.kind).collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            ConvertErrorKind::UnknownCommand,
+            ConvertErrorKind::UnknownCommand,
+            ConvertErrorKind::ItemOutsideList,
+            ConvertErrorKind::InvalidFormula,
+        ]
+    );
+    assert_eq!(res.diagnostics[1].line_column(input), (2, 1));
+}
+
+#[test]
+fn keeps_state_after_error() {
+    let res = convert_text_recovering(r#"\begin{itemize}\item a \foo \item b\end{itemize}"#);
+    assert_snapshot!(res.output, @r###"
+
+      -  a #text(fill: red, raw("\\foo")); 
+      -  b
+    "###);
+    assert_eq!(res.diagnostics.len(), 1);
+}
+
+#[test]
+fn nested_error() {
+    // `\boxed{x}` fails after writing `frac(x, `, which is dropped
+    fn convert_boxed(ctx: &mut HandlerContext, cmd: &CmdItem) -> Result<(), HandlerError> {
+        ctx.write_str("frac(")?;
+        for arg in cmd.arguments() {
+            ctx.convert(arg.into())?;
+        }
+        ctx.write_str(", ")?;
+        let range = cmd.syntax().text_range();
+        Err(ConvertError::with_name(
+            ConvertErrorKind::UnknownCommand,
+            range.start().into()..range.end().into(),
+            "boxed",
+        ))?
+    }
+    let mut handlers = Handlers::default();
+    handlers.add_command("boxed", convert_boxed);
+    let options = ConvertOptions::default()
+        .mode(LaTeXMode::Math)
+        .spec(DEFAULT_SPEC.clone())
+        .handlers(handlers)
+        .error_policy(ErrorPolicy::Recover);
+    let res = mitex::convert(r#"\frac{a}{\sqrt{\boxed{b}}} + c"#, &options).unwrap();
+    assert_snapshot!(res.output, @r#"frac(a ,mitexsqrt(#text(fill: red, raw("\\boxed{b}"));)) +  c"#);
+    assert_eq!(res.diagnostics.len(), 1);
+}
@@ -0,0 +1,53 @@
+use super::prelude::*;
+
+#[test]
+fn refs() {
+    assert_snapshot!(convert_text(r#"\ref{fig:a_b} \eqref{eq:1.2}"#).unwrap(), @"@fig:a_b[] @eq:1.2[]");
+    assert_snapshot!(convert_text(r#"\autoref{fig:a} \Cref{fig:a} \cref{fig:a}"#).unwrap(), @"@fig:a @fig:a #lower(ref(<fig:a>));");
+    assert_snapshot!(convert_text(r#"\cref{a,b} \Cref{a, b, c}"#).unwrap(), @"#lower(ref(<a>)); and #lower(ref(<b>)); @a, @b and @c");
+}
+
+#[test]
+fn ref_keys() {
+    assert_snapshot!(convert_text(r#"\ref{a b} \autoref{a b}"#).unwrap(), @"@a-b[] @a-b");
+    assert_snapshot!(convert_text(r#"\ref{sec:} \autoref{a.}"#).unwrap(), @"#ref(<sec:>, supplement: none); #ref(<a.>);");
+    // the text after a reference does not continue its name
+    assert_snapshot!(convert_text(r#"\autoref{a}s \autoref{a}. \autoref{a}.b"#).unwrap(), @"#ref(<a>);s @a. #ref(<a>);.b");
+    assert_snapshot!(convert_text(r#"\ref{}"#).unwrap(), @"");
+}
+
+#[test]
+fn cleaned_keys() {
+    // keys outside the label syntax are cleaned, apart from the other labels
+    assert_snapshot!(convert_text(r#"\section{A}\label{a b}\section{B}\label{a-b}\section{C}\label{a/b}\section{D}\label{x#1}
+\ref{a b} \ref{a-b} \eqref{a/b} \autoref{x#1} \cref{a b,a/b}"#).unwrap(), @r###"
+    #heading(level: 1)[A];<a-b-2>#heading(level: 1)[B];<a-b>#heading(level: 1)[C];<a-b-3>#heading(level: 1)[D];<x-1>
+    @a-b-2[] @a-b[] @a-b-3[] @x-1 #lower(ref(<a-b-2>)); and #lower(ref(<a-b-3>));
+    "###);
+}
+
+#[test]
+fn ref_in_math() {
+    assert_snapshot!(convert_math(r#"x_{\ref{a}}"#).unwrap(), @"x _(#ref(<a>, supplement: none))");
+}
+
+#[test]
+fn labels() {
+    assert_snapshot!(convert_text(r#"\section{Intro}\label{sec:intro}"#).unwrap(), @"#heading(level: 1)[Intro];<sec:intro>");
+    assert_snapshot!(convert_text(r#"\subsection{Intro \label{sec:intro}}"#).unwrap(), @"#heading(level: 2)[Intro ];<sec:intro>");
+    assert_snapshot!(convert_text(r#"\begin{equation}x\label{eq:a b}\end{equation}"#).unwrap(), @r#"#[#set math.equation(numbering: "(1)"); $ x  $<eq:a-b>]"#);
+    assert_snapshot!(convert_text(r#"\begin{figure}\caption{Cap\label{fig:a}}\end{figure}"#).unwrap(), @"#figure(kind: image, caption: [Cap])[];<fig:a>");
+    assert_snapshot!(convert_text(r#"\label{}"#).unwrap(), @"");
+}
+
+#[test]
+fn table_labels() {
+    assert_snapshot!(convert_text(r#"\begin{table}\caption{T}\label{tab:t}\begin{tabular}{c}a\end{tabular}\end{table}"#).unwrap(), @r###"
+    #figure(kind: table, caption: [T])[#align(left)[#table(stroke: none,
+    columns: 1,
+    align: (center, ),
+
+    [a],
+    );]];<tab:t>
+    "###);
+}
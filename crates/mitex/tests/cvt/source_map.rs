@@ -0,0 +1,76 @@
+use super::prelude::*;
+
+/// Print each mapping as `output <- input`
+fn dump(input: &str, res: &mitex::Converted) -> String {
+    let source_map = res.source_map.as_ref().unwrap();
+    let mut lines = vec![];
+    for m in source_map.mappings() {
+        lines.push(format!(
+            "{:?} <- {:?}",
+            &res.output[m.output.clone()],
+            &input[m.input.clone()]
+        ));
+    }
+    lines.join("\n")
+}
+
+#[test]
+fn not_requested() {
+    assert!(convert_text_recovering("a").source_map.is_none());
+}
+
+#[test]
+fn math() {
+    let input = r#"\frac{1}{x} + \sqrt{y}"#;
+    let res = convert_math_with_source_map(input);
+    assert_snapshot!(dump(input, &res), @r###"
+    "frac(1 ,x ) +  mitexsqrt(y )" <- "\\frac{1}{x} + \\sqrt{y}"
+    "frac(1 ,x )" <- "\\frac{1}{x}"
+    "1 " <- "{1}"
+    "1 " <- "1"
+    "x " <- "{x}"
+    "x " <- "x"
+    "+  " <- "+ "
+    "mitexsqrt(y )" <- "\\sqrt{y}"
+    "y " <- "{y}"
+    "y " <- "y"
+    "###);
+}
+
+#[test]
+fn text() {
+    let input = "a \\textbf{b} $x^2$\n\\begin{itemize}\n\\item c\n\\end{itemize}";
+    let res = convert_text_with_source_map(input);
+    assert_snapshot!(dump(input, &res), @r###"
+    "a #strong[b]; #math.equation(block: false, $x ^(2 )$);\n\n  \n-  c\n  " <- "a \\textbf{b} $x^2$\n\\begin{itemize}\n\\item c\n\\end{itemize}"
+    "a " <- "a "
+    "#strong[b];" <- "\\textbf{b}"
+    "b" <- "{b}"
+    "b" <- "b"
+    "#math.equation(block: false, $x ^(2 )$);" <- "$x^2$"
+    "x ^(2 )" <- "x^2"
+    "x " <- "x"
+    "\n  \n-  c\n  " <- "\\begin{itemize}\n\\item c\n\\end{itemize}"
+    "\n- " <- "\\item"
+    "c\n  " <- "c\n"
+    "###);
+}
+
+#[test]
+fn lookup() {
+    let input = r#"a $\alpha + \beta$ b"#;
+    let res = convert_text_with_source_map(input);
+    let source_map = res.source_map.unwrap();
+
+    let output_beta = res.output.find("beta").unwrap();
+    let input_beta = input.find(r#"\beta"#).unwrap();
+    assert_eq!(
+        &input[source_map.to_input(output_beta).unwrap()],
+        r#"\beta"#
+    );
+    assert_eq!(
+        &res.output[source_map.to_output(input_beta).unwrap()],
+        "beta "
+    );
+    assert_eq!(source_map.to_input(res.output.len()), None);
+}
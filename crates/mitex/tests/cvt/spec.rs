@@ -0,0 +1,67 @@
+use super::prelude::*;
+use mitex::command_preludes::*;
+use mitex::CommandSpec;
+
+/// Number of items in the default spec and the project spec
+fn overlay_len() -> usize {
+    let project = project_spec();
+    let added = project
+        .items()
+        .filter(|(name, _)| DEFAULT_SPEC.get(name).is_none());
+    DEFAULT_SPEC.items().count() + added.count()
+}
+
+fn project_spec() -> CommandSpec {
+    let mut builder = SpecBuilder::default();
+    builder
+        .add_command("R", define_symbol("RR"))
+        .add_command("norm", define_command_with_alias(1, "norm"))
+        .add_command("alpha", define_symbol("beta"));
+    builder.build()
+}
+
+#[test]
+fn custom_spec() {
+    let spec = project_spec();
+    let res = mitex_convert_math(r#"\R \norm{x}"#, Some(spec.clone()));
+    assert_snapshot!(res.unwrap(), @"RR  norm(x )");
+    // not in the custom spec
+    let err = mitex_convert_math(r#"\sum"#, Some(spec)).unwrap_err();
+    assert_eq!(err.kind, ConvertErrorKind::UnknownCommand);
+}
+
+#[test]
+fn overlay() {
+    let spec = DEFAULT_SPEC.overlay(project_spec());
+    let res = mitex_convert_math(r#"\R \norm{x} + \alpha \sum"#, Some(spec.clone()));
+    assert_snapshot!(res.unwrap(), @"RR  norm(x ) +  beta  sum ");
+    let res = mitex_convert_text(r#"\textbf{\R}"#, Some(spec));
+    assert_snapshot!(res.unwrap(), @"#strong[RR ];");
+}
+
+#[test]
+fn overlay_items() {
+    let spec = DEFAULT_SPEC.overlay(project_spec());
+    assert_eq!(spec.items().filter(|(name, _)| *name == "alpha").count(), 1);
+    assert_eq!(spec.items().count(), overlay_len());
+
+    let mut builder = SpecBuilder::default();
+    builder.add_command("R", define_symbol("bb(R)"));
+    let spec = spec.overlay(builder.build());
+    assert_eq!(spec.get_cmd("R").unwrap().alias.as_deref(), Some("bb(R)"));
+    assert_eq!(
+        spec.get_cmd("alpha").unwrap().alias.as_deref(),
+        Some("beta")
+    );
+}
+
+#[test]
+fn overlay_to_bytes() {
+    let spec = DEFAULT_SPEC.overlay(project_spec());
+    let spec = CommandSpec::from_bytes(&spec.to_bytes());
+    assert_eq!(spec.items().count(), overlay_len());
+    assert_eq!(
+        spec.get_cmd("alpha").unwrap().alias.as_deref(),
+        Some("beta")
+    );
+}
@@ -0,0 +1,85 @@
+use super::prelude::*;
+use mitex::{ConvertOptions, Converted, LaTeXMode, Target};
+
+fn convert_standalone(input: &str, mode: LaTeXMode) -> Converted {
+    let options = ConvertOptions::default()
+        .spec(DEFAULT_SPEC.clone())
+        .mode(mode)
+        .target(Target::Standalone);
+    mitex::convert(input, &options).unwrap()
+}
+
+fn convert_standalone_math(input: &str) -> Converted {
+    convert_standalone(input, LaTeXMode::Math)
+}
+
+#[test]
+fn native() {
+    let res = convert_standalone_math(r#"\frac{a}{b} + \sqrt{x} + \sqrt[3]{y}"#);
+    assert_snapshot!(res.output, @"frac(a ,b ) +  sqrt(x ) +  root(3 , y )");
+    assert_eq!(res.prelude, "");
+    let res = convert_standalone_math(r#"\begin{pmatrix} 1 & 0 \\ 0 & 1 \end{pmatrix}"#);
+    assert_snapshot!(res.output, @r###"mat(delim: "(",  1  zws , 0  zws ; 0  zws , 1  )"###);
+    let res = convert_standalone_math(r#"\xcancel{x} \bcancel{y} \begin{rcases} a \end{rcases}"#);
+    assert_snapshot!(res.output, @"cancel(x ) cancel(inverted: #true, y ) cases(reverse: #true,  a  )");
+}
+
+#[test]
+fn symbols() {
+    let res = convert_standalone_math(r#"a\!b \varDelta \lvert x \rvert"#);
+    assert_snapshot!(res.output, @r###"a #h(-(3/18) * 1em)  b  italic(Delta)   #math.class("opening", "|")   x  #math.class("closing", "|")  "###);
+    assert_eq!(res.prelude, "");
+}
+
+#[test]
+fn helpers() {
+    let res = convert_standalone_math(r#"\dfrac{1}{2} + \operatorname{rank} A + \dfrac{3}{4}"#);
+    assert_snapshot!(res.output, @"dfrac(1 ,2 ) +  operatorname(r a n k ) A  +  dfrac(3 ,4 )");
+    assert_snapshot!(res.prelude, @r###"
+    #let dfrac = (num, den) => $math.display((num)/(den))$
+    #let operatorname = it => math.op(math.upright(it))
+    "###);
+}
+
+#[test]
+fn helper_dependencies() {
+    let res = convert_standalone(r#"\textcolor{red}{a} $\mathscr{A}$"#, LaTeXMode::Text);
+    assert_snapshot!(res.output, @"#colortext(none, [red])[a]; #math.equation(block: false, $mathscr(A )$);");
+    let defined = res
+        .prelude
+        .lines()
+        .filter_map(|line| line.strip_prefix("#let "))
+        .map(|line| line.split(" = ").next().unwrap())
+        .collect::<Vec<_>>();
+    // dependencies are defined first, once
+    assert_eq!(
+        defined,
+        vec![
+            "mitex-color-map",
+            "get-tex-color",
+            "colortext",
+            "get-tex-str-from-arr",
+            "get-tex-str",
+            "mathscr",
+        ]
+    );
+}
+
+#[test]
+fn text_mode_names() {
+    // names without `#` are plain text in text mode
+    let res = convert_standalone(r#"\textbf{a} matrix $\textbf{b}$"#, LaTeXMode::Text);
+    assert_snapshot!(res.output, @"#strong[a]; matrix #math.equation(block: false, $#math.bold[b];$);");
+    assert_eq!(res.prelude, "");
+}
+
+#[test]
+fn mitex_scope_target() {
+    let options = ConvertOptions::default()
+        .spec(DEFAULT_SPEC.clone())
+        .mode(LaTeXMode::Math);
+    let input = r#"\dfrac{1}{2} + \sqrt{x}"#;
+    let res = mitex::convert(input, &options).unwrap();
+    assert_eq!(res.output, convert_math(input).unwrap());
+    assert_eq!(res.prelude, "");
+}
@@ -23,3 +23,118 @@ fn tabular() {
     );
     "###);
 }
+
+#[test]
+fn column_specs() {
+    assert_snapshot!(convert_text(r#"\begin{tabular}{p{3cm}m{0.2\textwidth}b{1in}X}a\end{tabular}"#).unwrap(), @r###"
+    #table(stroke: none,
+    columns: (3cm, 20%, 1in, 1fr, ),
+    align: (left + top, left + horizon, left + bottom, left, ),
+
+    [a], [], [], [],
+    );
+    "###);
+    assert_snapshot!(convert_text(r#"\begin{tabular}[t]{@{}*{3}{c}|!{\vrule}>{\centering\arraybackslash}p{2cm}@{}}a\end{tabular}"#).unwrap(), @r###"
+    #table(stroke: none,
+    columns: (auto, auto, auto, 2cm, ),
+    align: (center, center, center, center + top, ),
+    table.vline(stroke: .5pt, x: 3), 
+    [a], [], [], [],
+    );
+    "###);
+    assert_snapshot!(convert_text(r#"\begin{tabular*}{\textwidth}{l||r}a & b\end{tabular*}"#).unwrap(), @r###"
+    #table(stroke: none,
+    columns: 2,
+    align: (left, right, ),
+    table.vline(stroke: .5pt, x: 1), 
+    [a ], [b],
+    );
+    "###);
+    assert_snapshot!(convert_text(r#"\begin{tabularx}{\linewidth}{lX}a & b\end{tabularx}"#).unwrap(), @r###"
+    #table(stroke: none,
+    columns: (auto, 1fr, ),
+    align: (left, left, ),
+
+    [a ], [b],
+    );
+    "###);
+}
+
+#[test]
+fn spans() {
+    assert_snapshot!(convert_text(r#"\begin{tabular}{|c|c|c|}
+\multicolumn{2}{|l|}{wide} & c \\
+\multirow{2}{*}{tall} & b & c \\
+ & b & c \\
+\multicolumn{2}{c}{\multirow[t]{2}{3cm}{both}} & c \\
+\multicolumn{2}{c}{} & c \\
+a \\
+\end{tabular}"#).unwrap(), @r###"
+    #table(stroke: none,
+    columns: 3,
+    align: (center, center, center, ),
+    table.vline(stroke: .5pt, x: 0), table.vline(stroke: .5pt, x: 1), table.vline(stroke: .5pt, x: 2), table.vline(stroke: .5pt, x: 3), 
+    table.cell(colspan: 2, align: left)[wide], [c ],
+    table.cell(rowspan: 2)[tall], [b ], [c ],
+    [b ], [c ],
+    table.cell(colspan: 2, rowspan: 2, align: center)[both], [c ],
+    [c ],
+    [a ], [], [],
+    );
+    "###);
+}
+
+#[test]
+fn rules() {
+    assert_snapshot!(convert_text(r#"\begin{tabular}{ccc}
+\toprule
+a & b & c \\ \cline{2-3}
+a & b & c \\[2pt] \cmidrule(lr){1-2}
+a & b & c \\
+\bottomrule
+\end{tabular}"#).unwrap(), @r###"
+    #table(stroke: none,
+    columns: 3,
+    align: (center, center, center, ),
+
+    table.hline(stroke: 1pt),
+    [a ], [b ], [c ],
+    table.hline(start: 1, end: 3, stroke: .5pt),
+    [a ], [b ], [c ],
+    table.hline(start: 0, end: 2, stroke: .5pt),
+    [a ], [b ], [c ],
+    table.hline(stroke: 1pt),
+    );
+    "###);
+}
+
+#[test]
+fn longtable() {
+    assert_snapshot!(convert_text(r#"\begin{longtable}{cc}
+\caption{Long}\label{tab:long} \\
+A & B \\ \hline
+\endfirsthead
+A & B (continued) \\ \hline
+\endhead
+\hline \multicolumn{2}{r}{continued} \\
+\endfoot
+\hline
+\endlastfoot
+a & b \\
+\end{longtable}"#).unwrap(), @r###"
+    #[#show figure: set block(breakable: true); #figure(kind: table, caption: [Long],)[#table(stroke: none,
+    columns: 2,
+    align: (center, center, ),
+
+    table.header(
+    [A ], [B ],
+    table.hline(stroke: .5pt),
+    ),
+    [a ], [b ],
+    table.footer(
+    table.hline(stroke: .5pt),
+    table.cell(colspan: 2, align: right)[continued],
+    ),
+    );];<tab:long>]
+    "###);
+}
@@ -0,0 +1,104 @@
+use super::prelude::*;
+
+#[test]
+fn definitions() {
+    assert_snapshot!(convert_text(r#"\documentclass{article}
+\usepackage{amsthm}
+\newtheorem{thm}{Theorem}[section]
+\newtheorem{lem}[thm]{Lemma}
+\theoremstyle{definition}
+\newtheorem{defn}{Definition}
+\theoremstyle{remark}
+\newtheorem*{rem}{Remarque}
+\begin{document}
+\begin{lem}\label{lem:a} A lemma. \end{lem}
+\end{document}"#).unwrap(), @r###"
+    #let mitex-theorems = (
+      "thm": (kind: "thm", supplement: "Theorem", style: "plain", within: 1, numbered: true),
+      "lem": (kind: "thm", supplement: "Lemma", style: "plain", within: 1, numbered: true),
+      "defn": (kind: "defn", supplement: "Definition", style: "definition", within: 0, numbered: true),
+      "rem": (kind: "rem", supplement: "Remarque", style: "remark", within: 0, numbered: false),
+    )
+    #let mitex-theorem(name, title: none, body) = {
+      let (kind, supplement, style, within, numbered) = mitex-theorems.at(name)
+      let pattern = if within > 0 {
+        (..n) => {
+          let levels = (counter(heading).get() + (0,) * within).slice(0, within)
+          numbering("1.1", ..levels, ..n)
+        }
+      } else { "1" }
+      let number = if numbered [ #context counter(figure.where(kind: kind)).display(pattern)]
+      let head = [#supplement#number#if title != none [ (#title)].]
+      let body = if style == "plain" {
+        [#strong(head) #emph(body)]
+      } else if style == "definition" {
+        [#strong(head) #body]
+      } else {
+        [#emph(head) #body]
+      }
+      figure(
+        kind: kind, supplement: supplement, numbering: if numbered { pattern }, outlined: false,
+        block(width: 100%, align(left, body)),
+      )
+    }
+    #show figure.where(kind: "defn"): it => it.body
+    #show figure.where(kind: "rem"): it => it.body
+    #show figure.where(kind: "thm"): it => it.body
+    #show heading: it => { if it.level <= 1 { counter(figure.where(kind: "thm")).update(0) }; it }
+
+    #mitex-theorem("lem")[A lemma.];<lem:a>
+    "###);
+}
+
+#[test]
+fn environments() {
+    assert_snapshot!(convert_text(r#"\newtheorem{thm}{Theorem}
+\begin{thm}[Fermat]\label{thm:fermat}
+No $x^n + y^n = z^n$.
+\end{thm}
+\begin{thm}[A [b] c] Body. \end{thm}
+See \ref{thm:fermat}."#).unwrap(), @r###"
+    #let mitex-theorems = (
+      "thm": (kind: "thm", supplement: "Theorem", style: "plain", within: 0, numbered: true),
+    )
+    #let mitex-theorem(name, title: none, body) = {
+      let (kind, supplement, style, within, numbered) = mitex-theorems.at(name)
+      let pattern = if within > 0 {
+        (..n) => {
+          let levels = (counter(heading).get() + (0,) * within).slice(0, within)
+          numbering("1.1", ..levels, ..n)
+        }
+      } else { "1" }
+      let number = if numbered [ #context counter(figure.where(kind: kind)).display(pattern)]
+      let head = [#supplement#number#if title != none [ (#title)].]
+      let body = if style == "plain" {
+        [#strong(head) #emph(body)]
+      } else if style == "definition" {
+        [#strong(head) #body]
+      } else {
+        [#emph(head) #body]
+      }
+      figure(
+        kind: kind, supplement: supplement, numbering: if numbered { pattern }, outlined: false,
+        block(width: 100%, align(left, body)),
+      )
+    }
+    #show figure.where(kind: "thm"): it => it.body
+
+
+    #mitex-theorem("thm", title: [Fermat])[No #math.equation(block: false, $x ^(n ) +  y ^(n ) =  z ^(n )$);.];<thm:fermat>
+    #mitex-theorem("thm", title: [A \[b\] c])[Body.];
+    See @thm:fermat[].
+    "###);
+}
+
+#[test]
+fn proof() {
+    assert_snapshot!(convert_text(r#"\begin{proof}
+Trivial.
+\end{proof}
+\begin{proof}[Proof of \ref{thm:a}] Done. \end{proof}"#).unwrap(), @r###"
+    #block(width: 100%)[#emph[Proof.] Trivial. #h(1fr) $square$];
+    #block(width: 100%)[#emph[Proof of @thm:a[].] Done. #h(1fr) $square$];
+    "###);
+}
@@ -0,0 +1,78 @@
+use super::prelude::*;
+
+#[test]
+fn verb() {
+    assert_snapshot!(convert_text(r#"\verb|a_b%c{|"#).unwrap(), @"`a_b%c{`");
+    assert_snapshot!(convert_text(r#"\verb*+x y+"#).unwrap(), @"`x␣y`");
+    // backticks cannot be enclosed by the inline raw text
+    assert_snapshot!(convert_text(r#"\verb!a`b!"#).unwrap(), @r#"#raw("a`b")"#);
+    assert_snapshot!(convert_math(r#"x \verb|_1|"#).unwrap(), @r#"x  #raw("_1")"#);
+    // without a closing delimiter
+    assert_snapshot!(convert_text(r#"\verb|a"#).unwrap(), @"|a");
+}
+
+#[test]
+fn verbatim() {
+    assert_snapshot!(convert_text(r#"\begin{verbatim}
+  if (x) { % not a comment
+\end{verbatim}"#).unwrap(), @r###"
+    ```
+      if (x) { % not a comment
+    ```
+    "###);
+    assert_snapshot!(convert_text(r#"\begin{verbatim*}a b\end{verbatim*}"#).unwrap(), @r###"
+    ```
+    a␣b
+    ```
+    "###);
+    assert_snapshot!(convert_text(r#"\begin{verbatim}\end{verbatim}"#).unwrap(), @r###"
+    ```
+
+    ```
+    "###);
+}
+
+#[test]
+fn languages() {
+    assert_snapshot!(convert_text(r#"\begin{lstlisting}[language=Python, caption={A, B}]
+print("}")
+\end{lstlisting}"#).unwrap(), @r###"
+    ```python
+    print("}")
+    ```
+    "###);
+    assert_snapshot!(convert_text(r#"\begin{lstlisting}[language={[Sharp]C}]
+x;
+\end{lstlisting}"#).unwrap(), @r###"
+    ```c
+    x;
+    ```
+    "###);
+    assert_snapshot!(convert_text(r#"\begin{minted}[linenos]{C++}
+a```b
+\end{minted}"#).unwrap(), @r###"
+    ````c++
+    a```b
+    ````
+    "###);
+    assert_snapshot!(convert_text(r#"\begin{Verbatim}[numbers=left]
+x
+\end{Verbatim}"#).unwrap(), @r###"
+    ```
+    x
+    ```
+    "###);
+}
+
+#[test]
+fn unterminated() {
+    // the body runs to the end of the input, as in TeX
+    assert_snapshot!(convert_text(r#"a
+\begin{verbatim}
+x_1 & \foo{"#).unwrap(), @r###"
+    a
+    ```
+    x_1 & \foo{
+    ```
+    "###);
+}
@@ -98,32 +98,101 @@
   subsection: define-cmd(1, alias: "#heading(level: 2)"),
   subsubsection: define-cmd(1, alias: "#heading(level: 3)"),
   footnote: define-cmd(1, alias: "#footnote"),
-  cite: define-cmd(1, alias: "#mitexcite", handle: it => cite(label(get-tex-str(it)))),
+  footnotemark: define-glob-cmd("{,b}", "footnotemark"),
+  footnotetext: define-glob-cmd("{,b}t", "footnotetext"),
+  url: define-cmd(1),
+  href: define-cmd(2),
+  hyperref: define-glob-cmd("{,b}t", "hyperref"),
+  cite: define-glob-cmd("{,b}{,b}t", "#mitexcite", handle: it => cite(label(get-tex-str(it)))),
+  citep: define-glob-cmd("{,b}{,b}t", "citep"),
+  citet: define-glob-cmd("{,b}{,b}t", "citet"),
+  citeauthor: define-glob-cmd("{,b}{,b}t", "citeauthor"),
+  citeyear: define-glob-cmd("{,b}{,b}t", "citeyear"),
+  citeyearpar: define-glob-cmd("{,b}{,b}t", "citeyearpar"),
+  parencite: define-glob-cmd("{,b}{,b}t", "parencite"),
+  textcite: define-glob-cmd("{,b}{,b}t", "textcite"),
+  autocite: define-glob-cmd("{,b}{,b}t", "autocite"),
+  fullcite: define-glob-cmd("{,b}{,b}t", "fullcite"),
+  nocite: define-cmd(1),
+  bibliography: define-cmd(1),
+  bibliographystyle: define-cmd(1),
+  addbibresource: define-glob-cmd("{,b}t", "addbibresource"),
+  printbibliography: define-glob-cmd("{,b}", "printbibliography"),
+  bibitem: define-glob-cmd("{,b}t", "bibitem"),
+  newtheorem: define-glob-cmd("t{,b}t{,b}", "newtheorem"),
+  "newtheorem*": define-cmd(2),
+  theoremstyle: define-cmd(1),
+  qedhere: ignore-sym,
   emph: define-cmd(1, alias: "#emph"),
   label: define-cmd(1, alias: "mitexlabel", handle: ignore-me),
   tag: define-cmd(1, alias: "mitexlabel", handle: ignore-me),
+  "tag*": define-cmd(1, alias: "mitexlabel", handle: ignore-me),
   ref: define-cmd(1, alias: "#mitexref", handle: it => ref(label(get-tex-str(it)))),
   eqref: define-cmd(1, alias: "#mitexref"),
+  autoref: define-cmd(1, alias: "#mitexref"),
+  cref: define-cmd(1, alias: "#mitexref"),
+  Cref: define-cmd(1, alias: "#mitexref"),
   item: ignore-sym,
+  verb: define-cmd(1),
+  "verb*": define-cmd(1),
+  // document structure
+  usepackage: define-glob-cmd("{,b}t", "usepackage"),
+  title: define-glob-cmd("{,b}t", "title"),
+  author: define-glob-cmd("{,b}t", "author"),
+  affil: define-glob-cmd("{,b}t", "affil"),
+  date: define-cmd(1),
+  keywords: define-cmd(1),
+  thanks: define-cmd(1, alias: "#footnote"),
+  maketitle: ignore-sym,
+  and: ignore-sym,
+  today: ignore-sym,
   // environments for text mode
   itemize: define-env(none, kind: "is-itemize"),
   enumerate: define-env(none, kind: "is-enumerate"),
   quote: define-env(none, alias: "quote(block: true)"),
   abstract: define-env(none, alias: "quote(block: true)"),
+  thebibliography: define-env(1),
+  verbatim: define-env(none, alias: "raw"),
+  "verbatim*": define-env(none, alias: "raw"),
+  Verbatim: define-glob-env("{,b}", alias: "raw"),
+  lstlisting: define-glob-env("{,b}", alias: "raw"),
+  minted: define-glob-env("{,b}t", alias: "raw"),
   figure: define-glob-env("{,b}", kind: "is-figure", alias: "figure"),
   table: define-glob-env("{,b}", kind: "is-figure", alias: "figure"),
-  tabular: define-env(1, kind: "is-table", alias: "table"),
+  "figure*": define-glob-env("{,b}", kind: "is-figure", alias: "figure"),
+  "table*": define-glob-env("{,b}", kind: "is-figure", alias: "figure"),
+  subfigure: define-glob-env("{,b}t", kind: "is-figure", alias: "figure"),
+  subtable: define-glob-env("{,b}t", kind: "is-figure", alias: "figure"),
+  wrapfigure: define-glob-env("{,b}t{,b}t", kind: "is-figure", alias: "figure"),
+  wraptable: define-glob-env("{,b}t{,b}t", kind: "is-figure", alias: "figure"),
+  tabular: define-glob-env("{,b}t", kind: "is-table", alias: "table"),
+  "tabular*": define-glob-env("t{,b}t", kind: "is-table", alias: "table"),
+  tabularx: define-glob-env("t{,b}t", kind: "is-table", alias: "table"),
+  longtable: define-glob-env("{,b}t", kind: "is-table", alias: "table"),
   // commands for figure
   centering: ignore-sym,
+  raggedright: ignore-sym,
+  raggedleft: ignore-sym,
+  hfill: define-sym("#h(1fr)"),
+  subcaptionbox: define-glob-cmd("t{,b}{,b}t", "subcaptionbox"),
   textwidth: sym,
-  caption: define-cmd(1, alias: "mitexcaption", handle: ignore-me),
+  caption: define-glob-cmd("{,b}t", "mitexcaption", handle: ignore-me),
   includegraphics: define-glob-cmd("{,b}t", "#miteximage", handle: ignore-me),
+  graphicspath: define-cmd(1),
   // commands for tabular
   toprule: define-glob-cmd("{,b}", "toprule"),
   midrule: define-glob-cmd("{,b}", "midrule"),
   bottomrule: define-glob-cmd("{,b}", "bottomrule"),
   hline: ignore-sym,
   vline: ignore-sym,
+  cline: define-cmd(1),
+  cmidrule: define-glob-cmd("{,b}{,p}t", "cmidrule"),
+  multicolumn: define-cmd(3),
+  multirow: define-glob-cmd("{,b}t{,b}t{,b}t", "multirow"),
+  endfirsthead: ignore-sym,
+  endhead: ignore-sym,
+  endfoot: ignore-sym,
+  endlastfoot: ignore-sym,
   // Spaces: \! \, \> \: \; \ \quad \qquad
   "!": define-sym("negthinspace", sym: h(-(3/18) * 1em)),
   negthinspace: of-sym(h(-(3/18) * 1em)),
@@ -302,6 +371,12 @@
   u: define-cmd(1, alias: "breve"),
   v: define-cmd(1, alias: "caron"),
   r: define-cmd(1, alias: "circle"),
+  "'": define-cmd(1, alias: "acute"),
+  "`": define-cmd(1, alias: "grave"),
+  "\"": define-cmd(1, alias: "dot.double"),
+  "~": define-cmd(1, alias: "tilde"),
+  "=": define-cmd(1, alias: "macron"),
+  ".": define-cmd(1, alias: "dot"),
   vec: define-cmd(1, alias: "arrow"),
   overrightarrow: define-cmd(1, alias: "arrow"),
   overleftarrow: define-cmd(1, alias: "arrow.l"),
@@ -777,6 +852,16 @@
   smallsmile: define-sym("⌣"),
   smile: define-sym("⌣"),
   ss: define-sym("ß"),
+  SS: define-sym("\"SS\""),
+  l: define-sym("ł"),
+  L: define-sym("Ł"),
+  dh: define-sym("ð"),
+  DH: define-sym("Ð"),
+  TH: define-sym("Þ"),
+  ng: define-sym("ŋ"),
+  NG: define-sym("Ŋ"),
+  dj: define-sym("đ"),
+  DJ: define-sym("Đ"),
   surd: define-sym("\√"),
   tg: define-sym("tg"),
   th: of-sym(math.op("th")),
@@ -929,6 +1014,7 @@
   textdagger: define-sym("dagger"),
   textdaggerdbl: define-sym("dagger.double"),
   textdegree: define-sym("degree"),
+  textdollar: define-sym("\\$"),
   textdollarsign: define-sym("\\$"),
   textellipsis: define-sym("..."),
   textemdash: define-sym("---"),
@@ -940,6 +1026,28 @@
   textquoteleft: define-sym("quote.l.single"),
   textquoteright: define-sym("quote.r.single"),
   textregistered: define-sym("®"),
+  textquotesingle: define-sym("quote.single"),
+  textquotedbl: define-sym("quote.double"),
+  quotesinglbase: define-sym("quote.low.single"),
+  quotedblbase: define-sym("quote.low.double"),
+  guillemotleft: define-sym("quote.angle.l.double"),
+  guillemetleft: define-sym("quote.angle.l.double"),
+  guillemotright: define-sym("quote.angle.r.double"),
+  guillemetright: define-sym("quote.angle.r.double"),
+  guilsinglleft: define-sym("quote.angle.l.single"),
+  guilsinglright: define-sym("quote.angle.r.single"),
+  textexclamdown: define-sym("excl.inv"),
+  textquestiondown: define-sym("quest.inv"),
+  textbullet: define-sym("bullet"),
+  textperiodcentered: define-sym("dot.c"),
+  textsection: define-sym("section"),
+  textparagraph: define-sym("pilcrow"),
+  textcopyright: define-sym("copyright"),
+  texttrademark: define-sym("trademark"),
+  texteuro: define-sym("euro"),
+  textyen: define-sym("yen"),
+  textcent: define-sym("cent"),
+  textvisiblespace: define-sym("␣"),
   textperthousand: define-sym("permille"),
   textsterling: define-sym("pound"),
   textunderscore: define-sym("\\_"),
@@ -1031,6 +1139,13 @@
   Bmatrix: matrix-handle(delim: "{"),
   vmatrix: matrix-handle(delim: "|"),
   Vmatrix: matrix-handle(delim: "||"),
+  // mathtools matrices with an optional column alignment, e.g. `[r]`
+  "matrix*": define-glob-env("{,b}", kind: "is-matrix", alias: "matrix"),
+  "pmatrix*": define-glob-env("{,b}", kind: "is-matrix", alias: "pmatrix"),
+  "bmatrix*": define-glob-env("{,b}", kind: "is-matrix", alias: "bmatrix"),
+  "Bmatrix*": define-glob-env("{,b}", kind: "is-matrix", alias: "Bmatrix"),
+  "vmatrix*": define-glob-env("{,b}", kind: "is-matrix", alias: "vmatrix"),
+  "Vmatrix*": define-glob-env("{,b}", kind: "is-matrix", alias: "Vmatrix"),
   smallmatrix: matrix-handle(handle: (..args) => math.inline(math.mat.with(delim: none, ..args))),
   array: define-env(1, kind: "is-matrix", alias: "mitexarray", handle: (arg0: ("l",), ..args) => {
     if args.pos().len() == 0 {
@@ -1067,6 +1182,10 @@
   alignedat: define-env(1, kind: "is-math", alias: "alignedat", handle: (arg0: none, it) => pad(y: 0.2em, block(math.op(it)))),
   align: define-env(none, kind: "is-math", alias: "aligned"),
   "align*": define-env(none, kind: "is-math", alias: "aligned"),
+  alignat: define-env(1, kind: "is-math", alias: "alignedat"),
+  "alignat*": define-env(1, kind: "is-math", alias: "alignedat"),
+  flalign: define-env(none, kind: "is-math", alias: "aligned"),
+  "flalign*": define-env(none, kind: "is-math", alias: "aligned"),
   equation: define-env(none, kind: "is-math", alias: "aligned"),
   "equation*": define-env(none, kind: "is-math", alias: "aligned"),
   split: define-env(none, kind: "is-math", alias: "aligned"),
@@ -1096,6 +1215,8 @@
   vspace: define-cmd(1, handle: it => v(eval(get-tex-str(it)))),
   hspace: define-cmd(1, handle: it => h(eval(get-tex-str(it)))),
   text: define-cmd(1, alias: "#textmath", handle: it => it),
+  intertext: define-cmd(1, alias: "#textmath", handle: it => it),
+  shortintertext: define-cmd(1, alias: "#textmath", handle: it => it),
   textmd: define-cmd(1, alias: "#textmd", handle: it => it),
   textnormal: define-cmd(1, alias: "#textnormal", handle: it => it),
   textbf: define-cmd(1, alias: "#textbf", handle: math.bold),
@@ -1178,7 +1299,6 @@
   newcounter: ignore-sym,
   newlength: ignore-sym,
   newsavebox: ignore-sym,
-  newtheorem: ignore-sym,
   newfont: ignore-sym,
   ProvidesClass: ignore-sym,
   LoadClass: ignore-sym,